@@ -1,22 +1,27 @@
 use std::io::{self, BufRead};
 
-use anyhow::Result;
+use sedenion_engine::error::Diagnostic;
 use sedenion_engine::numeric_evaluator::evaluate;
 
-fn main() -> Result<()> {
+fn main() -> io::Result<()> {
     let stdin = io::stdin();
-    loop {
-        let expression;
-        {
-            expression = stdin.lock().lines().next().unwrap().unwrap();
-        }
+    for expression in stdin.lock().lines() {
+        let expression = expression?;
 
         if expression == "exit" {
             break;
         }
 
-        let result = evaluate(&expression)?;
-        println!("= {}", result);
+        match evaluate(&expression) {
+            Ok(result) => println!("= {}", result),
+            Err(error) => {
+                let diagnostic = Diagnostic::new(&error, &expression);
+                match diagnostic.snippet() {
+                    Some(snippet) => eprintln!("{}\n{}", diagnostic.message(), snippet),
+                    None => eprintln!("{}", diagnostic.message()),
+                }
+            }
+        }
     }
 
     Ok(())
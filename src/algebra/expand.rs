@@ -0,0 +1,20 @@
+use crate::calculus::build::binop;
+use crate::parser::{Expr, Op};
+
+use super::multivariate::MultiPolynomial;
+
+/// Multiplies out products and integer powers in `expr` and collects like
+/// terms, so equivalent polynomials always come out the same. Terms are
+/// ordered by decreasing degree, then alphabetically. Parts that aren't
+/// polynomial, like `sin(x)`, are kept as they are with their arguments
+/// expanded. Both sides of an equation are expanded separately.
+pub fn expand(expr: &Expr) -> Expr {
+    match expr.unspanned() {
+        Expr::BinOp {
+            lhs,
+            op: Op::Equals,
+            rhs,
+        } => binop(expand(lhs), Op::Equals, expand(rhs)),
+        expr => MultiPolynomial::from_expr(expr).to_expr(),
+    }
+}
@@ -0,0 +1,353 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::calculus::build::{binop, neg, number};
+use crate::parser::{Expr, Op};
+
+use super::multivariate::{integer_expr, rational_expr, MultiPolynomial, Powers};
+use super::univariate::Univariate;
+
+/// Largest constant or leading coefficient whose divisors are tried as
+/// rational roots. Numbers up to it have at most 240 divisors, so there are
+/// at most about a hundred thousand candidates.
+const MAX_ROOT_SEARCH: u64 = 1_000_000;
+
+/// Factors `expr` over the rationals: common factors are taken out first, then
+/// differences of squares are split, and polynomials in a single variable are
+/// split into square-free parts and further into linear and quadratic
+/// factors where they have rational roots. Factors that can't be split are
+/// kept expanded. Both sides of an equation are factored separately.
+pub fn factor(expr: &Expr) -> Expr {
+    match expr.unspanned() {
+        Expr::BinOp {
+            lhs,
+            op: Op::Equals,
+            rhs,
+        } => binop(factor(lhs), Op::Equals, factor(rhs)),
+        expr => Factorization::of(&MultiPolynomial::from_expr(expr)).to_expr(),
+    }
+}
+
+/// A polynomial written as a constant times powers of primitive polynomials.
+struct Factorization {
+    constant: BigRational,
+    factors: Vec<(MultiPolynomial, u32)>,
+}
+
+impl Factorization {
+    fn of(polynomial: &MultiPolynomial) -> Self {
+        let mut result = Self {
+            constant: BigRational::one(),
+            factors: Vec::new(),
+        };
+        if let Some(constant) = polynomial.as_constant() {
+            result.constant = constant;
+            return result;
+        }
+
+        let (content, polynomial) = primitive(polynomial);
+        result.constant = content;
+
+        // Atoms that divide every term
+        let mut common = polynomial
+            .leading()
+            .map(|(powers, _)| powers.clone())
+            .unwrap_or_default();
+        for (powers, _) in polynomial.terms() {
+            common.retain(|atom, exponent| match powers.get(atom) {
+                Some(&other) => {
+                    *exponent = (*exponent).min(other);
+                    true
+                }
+                None => false,
+            });
+        }
+        let mut rest = MultiPolynomial::zero();
+        for (powers, coefficient) in polynomial.terms() {
+            let mut powers = powers.clone();
+            for (atom, exponent) in &common {
+                match powers[atom] - exponent {
+                    0 => powers.remove(atom),
+                    remaining => powers.insert(atom.clone(), remaining),
+                };
+            }
+            rest = rest.add(&MultiPolynomial::term(powers, coefficient.clone()));
+        }
+        for (atom, exponent) in common {
+            result.push(MultiPolynomial::atom(atom), exponent);
+        }
+
+        if rest.as_constant().is_none() {
+            result.split(rest, 1);
+        }
+        result
+    }
+
+    /// Adds `polynomial^multiplicity`, which must be primitive, factoring it
+    /// further where possible.
+    fn split(&mut self, polynomial: MultiPolynomial, multiplicity: u32) {
+        if let Some((a, b)) = difference_of_squares(&polynomial) {
+            for factor in [a, b] {
+                self.merge(Factorization::of(&factor), multiplicity);
+            }
+            return;
+        }
+
+        let Some((atom, univariate)) = polynomial.as_univariate() else {
+            self.push(polynomial, multiplicity);
+            return;
+        };
+        // The square-free parts are monic
+        self.constant *= univariate.lead().pow(multiplicity as i32);
+        for (part, power) in univariate.square_free() {
+            let (constant, factors) = split_square_free(part);
+            self.constant *= constant.pow((multiplicity * power) as i32);
+            for factor in factors {
+                let factor = MultiPolynomial::from_univariate(&atom, &factor);
+                match difference_of_squares(&factor) {
+                    Some(_) => self.merge(Factorization::of(&factor), multiplicity * power),
+                    None => self.push(factor, multiplicity * power),
+                }
+            }
+        }
+    }
+
+    /// Multiplies by `other^multiplicity`.
+    fn merge(&mut self, other: Factorization, multiplicity: u32) {
+        self.constant *= other.constant.pow(multiplicity as i32);
+        for (factor, power) in other.factors {
+            self.push(factor, power * multiplicity);
+        }
+    }
+
+    /// Multiplies by `factor^multiplicity`, normalizing the factor so its
+    /// leading coefficient is a positive integer.
+    fn push(&mut self, factor: MultiPolynomial, multiplicity: u32) {
+        let (content, factor) = primitive(&factor);
+        self.constant *= content.pow(multiplicity as i32);
+        match self.factors.iter_mut().find(|(other, _)| *other == factor) {
+            Some((_, power)) => *power += multiplicity,
+            None => self.factors.push((factor, multiplicity)),
+        }
+    }
+
+    fn to_expr(&self) -> Expr {
+        let mut factors = self.factors.clone();
+        // Monomials first, then by degree
+        factors.sort_by(|(a, _), (b, _)| {
+            let long = |factor: &MultiPolynomial| factor.terms().count() > 1;
+            long(a).cmp(&long(b)).then_with(|| a.compare(b))
+        });
+
+        let mut constant = self.constant.clone();
+        let mut product: Option<Expr> = None;
+        for (factor, multiplicity) in factors {
+            let term = match (&product, factor.terms().count(), multiplicity) {
+                // The integer part of the constant goes into a leading monomial
+                (None, 1, _) => {
+                    let numerator = BigRational::from_integer(constant.numer().clone());
+                    constant /= &numerator;
+                    factor.pow(multiplicity).scale(&numerator).to_expr()
+                }
+                (_, 1, _) => factor.pow(multiplicity).to_expr(),
+                (_, _, 1) => factor.to_expr(),
+                _ => binop(factor.to_expr(), Op::Power, number(multiplicity as f64)),
+            };
+            product = Some(match product {
+                Some(product) => binop(product, Op::Multiply, term),
+                None => term,
+            });
+        }
+
+        let Some(product) = product else {
+            let value = rational_expr(&constant.abs());
+            return if constant.is_negative() {
+                neg(value)
+            } else {
+                value
+            };
+        };
+
+        let numerator = constant.numer().abs();
+        let mut result = if numerator.is_one() {
+            product
+        } else {
+            binop(integer_expr(&numerator), Op::Multiply, product)
+        };
+        if constant.is_negative() {
+            result = neg(result);
+        }
+        if constant.is_integer() {
+            result
+        } else {
+            binop(result, Op::Divide, integer_expr(constant.denom()))
+        }
+    }
+}
+
+/// The content of `polynomial`, signed like its leading coefficient, and the
+/// polynomial divided by it, which has coprime integer coefficients.
+fn primitive(polynomial: &MultiPolynomial) -> (BigRational, MultiPolynomial) {
+    let (numerators, denominators) = polynomial.terms().fold(
+        (BigInt::zero(), BigInt::one()),
+        |(numerators, denominators), (_, c)| {
+            (numerators.gcd(c.numer()), denominators.lcm(c.denom()))
+        },
+    );
+    if numerators.is_zero() {
+        return (BigRational::one(), polynomial.clone());
+    }
+
+    let mut content = BigRational::new(numerators, denominators);
+    if polynomial.leading().is_some_and(|(_, c)| c.is_negative()) {
+        content = -content;
+    }
+    (content.clone(), polynomial.scale(&content.recip()))
+}
+
+/// The square root of `value` if it is the square of a rational.
+fn rational_sqrt(value: &BigRational) -> Option<BigRational> {
+    if value.is_negative() {
+        return None;
+    }
+    let root = |n: &BigInt| Some(n.sqrt()).filter(|root| root * root == *n);
+    Some(BigRational::new(root(value.numer())?, root(value.denom())?))
+}
+
+/// `a - b` and `a + b` if `polynomial` is `a^2 - b^2`.
+fn difference_of_squares(
+    polynomial: &MultiPolynomial,
+) -> Option<(MultiPolynomial, MultiPolynomial)> {
+    let mut terms = polynomial.terms();
+    let (first, second) = (terms.next()?, terms.next()?);
+    if terms.next().is_some() || first.1.is_negative() == second.1.is_negative() {
+        return None;
+    }
+
+    let root = |(powers, coefficient): (&Powers, &BigRational)| {
+        let mut halves = Powers::new();
+        for (atom, exponent) in powers {
+            if exponent % 2 != 0 {
+                return None;
+            }
+            halves.insert(atom.clone(), exponent / 2);
+        }
+        Some(MultiPolynomial::term(
+            halves,
+            rational_sqrt(&coefficient.abs())?,
+        ))
+    };
+    let (square, subtracted) = if first.1.is_positive() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let (a, b) = (root(square)?, root(subtracted)?);
+    Some((a.sub(&b), a.add(&b)))
+}
+
+/// Splits a square-free polynomial into a constant and factors with rational
+/// coefficients. Factors without rational roots are kept whole.
+fn split_square_free(polynomial: Univariate) -> (BigRational, Vec<Univariate>) {
+    let (mut constant, mut rest) = polynomial.primitive();
+    let mut factors = Vec::new();
+
+    while rest.degree() > 2 {
+        let Some(root) = rational_root(&rest) else {
+            break;
+        };
+        let linear = Univariate::linear(BigRational::one(), -root);
+        rest = rest.div_rem(&linear).0;
+        factors.push(linear);
+    }
+
+    if rest.degree() == 2 {
+        match quadratic_roots(&rest) {
+            Some((a, b)) => {
+                constant *= rest.lead();
+                factors.push(Univariate::linear(BigRational::one(), -a));
+                factors.push(Univariate::linear(BigRational::one(), -b));
+            }
+            None => factors.push(rest),
+        }
+    } else if rest.degree() > 0 {
+        factors.push(rest);
+    }
+    (constant, factors)
+}
+
+/// The rational roots of a quadratic, smallest first, if it has any.
+fn quadratic_roots(polynomial: &Univariate) -> Option<(BigRational, BigRational)> {
+    let [c, b, a] = polynomial.coefficients() else {
+        return None;
+    };
+    let two = BigRational::from_integer(2.into());
+    let four = BigRational::from_integer(4.into());
+    let root = rational_sqrt(&(b * b - four * a * c))?;
+    let (x, y) = ((-b - &root) / (&two * a), (-b + &root) / (two * a));
+    Some(if x <= y { (x, y) } else { (y, x) })
+}
+
+/// The smallest rational root of a polynomial with integer coefficients, found
+/// among the quotients of the divisors of its constant and leading
+/// coefficients.
+fn rational_root(polynomial: &Univariate) -> Option<BigRational> {
+    let coefficients = polynomial.coefficients();
+    if coefficients.first()?.is_zero() {
+        return Some(BigRational::zero());
+    }
+
+    let bounded = |c: &BigRational| c.numer().abs().to_u64().filter(|&n| n <= MAX_ROOT_SEARCH);
+    let constant = bounded(coefficients.first()?)?;
+    let lead = bounded(coefficients.last()?)?;
+
+    // A root p/q in lowest terms has q - p dividing f(1) and q + p dividing
+    // f(-1), which rules out most candidates without evaluating at them
+    let at_one = polynomial.evaluate(&BigRational::one());
+    let at_minus_one = polynomial.evaluate(&-BigRational::one());
+    let divides = |d: i64, value: &BigRational| match d {
+        0 => value.is_zero(),
+        d => value.numer().is_multiple_of(&BigInt::from(d)),
+    };
+
+    let lead_divisors = divisors(lead);
+    let mut smallest: Option<BigRational> = None;
+    for p in divisors(constant) {
+        for &q in lead_divisors.iter().filter(|&&q| p.gcd(&q) == 1) {
+            let q = q as i64;
+            for p in [-(p as i64), p as i64] {
+                if !divides(q - p, &at_one) || !divides(q + p, &at_minus_one) {
+                    continue;
+                }
+                let candidate = BigRational::new(p.into(), q.into());
+                if smallest
+                    .as_ref()
+                    .is_some_and(|smallest| &candidate >= smallest)
+                {
+                    continue;
+                }
+                if polynomial.evaluate(&candidate).is_zero() {
+                    smallest = Some(candidate);
+                }
+            }
+        }
+    }
+    smallest
+}
+
+fn divisors(n: u64) -> Vec<u64> {
+    let mut divisors = Vec::new();
+    let mut i = 1;
+    while i * i <= n {
+        if n.is_multiple_of(i) {
+            divisors.push(i);
+            if i * i != n {
+                divisors.push(n / i);
+            }
+        }
+        i += 1;
+    }
+    divisors
+}
@@ -0,0 +1,9 @@
+//! Algebraic manipulation of polynomial expressions.
+
+mod expand;
+mod factor;
+pub(crate) mod multivariate;
+mod univariate;
+
+pub use expand::expand;
+pub use factor::factor;
@@ -0,0 +1,499 @@
+use std::cmp::Ordering;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::calculus::build::{binop, neg, number};
+use crate::math::Rational;
+use crate::parser::{Expr, Op};
+
+use super::univariate::Univariate;
+
+/// Highest total degree of a power expanded when reading `(...)^n` as a
+/// polynomial. Larger powers are left opaque.
+const MAX_DEGREE: u64 = 256;
+
+/// Most products of two terms computed in a single multiplication while
+/// expanding a power, which bounds both its time and its number of terms.
+const MAX_PRODUCTS: usize = 1 << 20;
+
+/// Something a polynomial term can be a power of.
+#[derive(Debug, Clone)]
+pub(crate) enum Atom {
+    Variable(String),
+    /// A part of the expression that isn't polynomial, like `sin(x)` or `pi`,
+    /// identified by how it prints.
+    Opaque {
+        key: String,
+        expr: Expr,
+    },
+}
+
+impl Atom {
+    fn opaque(expr: Expr) -> Self {
+        Atom::Opaque {
+            key: expr.to_string(),
+            expr,
+        }
+    }
+
+    fn key(&self) -> (u8, &str) {
+        match self {
+            Atom::Variable(name) => (0, name),
+            Atom::Opaque { key, .. } => (1, key),
+        }
+    }
+
+    fn to_expr(&self, exponent: u32) -> Expr {
+        match self {
+            Atom::Variable(name) => Expr::Monomial {
+                coefficient: 1.0,
+                variable: name.clone(),
+                exponent: exponent as f64,
+            },
+            Atom::Opaque { expr, .. } if exponent == 1 => expr.clone(),
+            // Written as one monomial, since `x^(a)^n` would read as `x^(a^n)`
+            Atom::Opaque {
+                expr:
+                    Expr::Monomial {
+                        coefficient,
+                        variable,
+                        exponent: power,
+                    },
+                ..
+            } => Expr::Monomial {
+                coefficient: coefficient.powi(exponent as i32),
+                variable: variable.clone(),
+                exponent: power * exponent as f64,
+            },
+            Atom::Opaque { expr, .. } => binop(expr.clone(), Op::Power, number(exponent as f64)),
+        }
+    }
+}
+
+impl PartialEq for Atom {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Atom {}
+
+impl PartialOrd for Atom {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Atom {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The atoms of a term with their exponents, none of them zero.
+pub(crate) type Powers = BTreeMap<Atom, u32>;
+
+/// Polynomial in any number of variables with exact rational coefficients.
+/// Like terms are always collected, so equal polynomials have equal
+/// representations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MultiPolynomial {
+    terms: BTreeMap<Powers, BigRational>,
+}
+
+impl MultiPolynomial {
+    pub(crate) fn zero() -> Self {
+        Self {
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn constant(value: BigRational) -> Self {
+        Self::term(Powers::new(), value)
+    }
+
+    pub(crate) fn variable(name: &str) -> Self {
+        Self::atom(Atom::Variable(name.to_string()))
+    }
+
+    pub(crate) fn atom(atom: Atom) -> Self {
+        Self::term(Powers::from([(atom, 1)]), BigRational::one())
+    }
+
+    pub(crate) fn term(powers: Powers, coefficient: BigRational) -> Self {
+        let mut terms = BTreeMap::new();
+        if !coefficient.is_zero() {
+            terms.insert(powers, coefficient);
+        }
+        Self { terms }
+    }
+
+    /// Reads `expr` as a polynomial. Parts that aren't polynomial become
+    /// opaque atoms, expanded inside where possible.
+    pub(crate) fn from_expr(expr: &Expr) -> Self {
+        match expr {
+            Expr::Number(n) => match Rational::from_f64(*n) {
+                Some(n) => Self::constant(n.0),
+                None => Self::atom(Atom::opaque(Expr::Number(*n))),
+            },
+            Expr::Rational(n) => Self::constant(n.0.clone()),
+            Expr::Spanned { expr, .. } => Self::from_expr(expr),
+            Expr::UnaryMinus(inner) => Self::from_expr(inner).neg(),
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => {
+                let coefficient = Self::from_expr(&Expr::Number(*coefficient));
+                let power = match exponent {
+                    0.0 => Self::constant(BigRational::one()),
+                    e if e.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(e) => {
+                        let atom = Atom::Variable(variable.clone());
+                        Self::term(Powers::from([(atom, *e as u32)]), BigRational::one())
+                    }
+                    _ => Self::atom(Atom::opaque(Expr::Monomial {
+                        coefficient: 1.0,
+                        variable: variable.clone(),
+                        exponent: *exponent,
+                    })),
+                };
+                coefficient.mul(&power)
+            }
+            Expr::BinOp { lhs, op, rhs } => {
+                let matrices = has_matrix(lhs) || has_matrix(rhs);
+                let lhs = Self::from_expr(lhs);
+                let rhs = Self::from_expr(rhs);
+                let opaque = |lhs: &Self, rhs: &Self| {
+                    Self::atom(Atom::opaque(binop(lhs.to_expr(), *op, rhs.to_expr())))
+                };
+                match op {
+                    Op::Add => lhs.add(&rhs),
+                    Op::Subtract => lhs.sub(&rhs),
+                    // Matrix products don't commute, so they stay in operand order
+                    Op::Multiply | Op::Power if matrices => opaque(&lhs, &rhs),
+                    Op::Multiply => lhs.mul(&rhs),
+                    Op::Divide => match rhs.as_constant() {
+                        Some(divisor) if !divisor.is_zero() => lhs.scale(&divisor.recip()),
+                        _ => opaque(&lhs, &rhs),
+                    },
+                    Op::Power => {
+                        let exponent = rhs.as_constant().filter(|n| n.is_integer());
+                        match (exponent.and_then(|n| n.to_i32()), lhs.as_constant()) {
+                            (Some(n), None) if n >= 0 => lhs
+                                .bounded_pow(n as u32)
+                                .unwrap_or_else(|| opaque(&lhs, &rhs)),
+                            // Constant bases can have negative exponents
+                            (Some(n), Some(base)) if n >= 0 || !base.is_zero() => {
+                                Self::constant(base.pow(n))
+                            }
+                            _ => opaque(&lhs, &rhs),
+                        }
+                    }
+                    Op::Modulo | Op::Equals | Op::Convert => opaque(&lhs, &rhs),
+                }
+            }
+            Expr::Function { name, args } => Self::atom(Atom::opaque(Expr::Function {
+                name: name.clone(),
+                args: args
+                    .iter()
+                    .map(|arg| Self::from_expr(arg).to_expr())
+                    .collect(),
+            })),
+            Expr::Constant(_) | Expr::Matrix(_) => Self::atom(Atom::opaque(expr.clone())),
+        }
+    }
+
+    /// The terms with their coefficients, in no particular order.
+    pub(crate) fn terms(&self) -> impl Iterator<Item = (&Powers, &BigRational)> {
+        self.terms.iter()
+    }
+
+    /// The term that comes first when the polynomial is written out.
+    pub(crate) fn leading(&self) -> Option<(&Powers, &BigRational)> {
+        self.terms
+            .iter()
+            .min_by(|(a, _), (b, _)| compare_terms(a, b))
+    }
+
+    /// The only atom in the polynomial and its coefficients, lowest degree
+    /// first, `None` unless there is exactly one atom.
+    pub(crate) fn as_univariate(&self) -> Option<(Atom, Univariate)> {
+        let mut atoms = self.terms.keys().flat_map(|powers| powers.keys());
+        let atom = atoms.next()?;
+        if atoms.any(|other| other != atom) {
+            return None;
+        }
+
+        let mut coefficients = Vec::new();
+        for (powers, coefficient) in &self.terms {
+            let degree = powers.get(atom).copied().unwrap_or(0) as usize;
+            if coefficients.len() <= degree {
+                coefficients.resize(degree + 1, BigRational::zero());
+            }
+            coefficients[degree] = coefficient.clone();
+        }
+        Some((atom.clone(), Univariate::new(coefficients)))
+    }
+
+    pub(crate) fn from_univariate(atom: &Atom, polynomial: &Univariate) -> Self {
+        let mut result = Self::zero();
+        for (degree, coefficient) in polynomial.coefficients().iter().enumerate() {
+            let powers = match degree {
+                0 => Powers::new(),
+                degree => Powers::from([(atom.clone(), degree as u32)]),
+            };
+            result.add_term(powers, coefficient.clone());
+        }
+        result
+    }
+
+    /// The value of the polynomial if it has no atoms.
+    pub(crate) fn as_constant(&self) -> Option<BigRational> {
+        match self.terms.iter().next() {
+            None => Some(BigRational::zero()),
+            Some((powers, value)) if powers.is_empty() && self.terms.len() == 1 => {
+                Some(value.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn scale(&self, factor: &BigRational) -> Self {
+        if factor.is_zero() {
+            return Self::zero();
+        }
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .map(|(powers, coefficient)| (powers.clone(), coefficient * factor))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn neg(&self) -> Self {
+        self.scale(&-BigRational::one())
+    }
+
+    /// Adds `coefficient` times `powers` to the polynomial in place.
+    fn add_term(&mut self, powers: Powers, coefficient: BigRational) {
+        match self.terms.entry(powers) {
+            Entry::Vacant(entry) => {
+                if !coefficient.is_zero() {
+                    entry.insert(coefficient);
+                }
+            }
+            Entry::Occupied(mut entry) => {
+                let sum = entry.get_mut();
+                // Integers skip the reduction of the fraction
+                match sum.is_integer() && coefficient.is_integer() {
+                    true => *sum = BigRational::from_integer(sum.numer() + coefficient.numer()),
+                    false => *sum += coefficient,
+                }
+                if entry.get().is_zero() {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for (powers, coefficient) in &other.terms {
+            result.add_term(powers.clone(), coefficient.clone());
+        }
+        result
+    }
+
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub(crate) fn mul(&self, other: &Self) -> Self {
+        let mut result = Self::zero();
+        for (a, x) in &self.terms {
+            for (b, y) in &other.terms {
+                let mut powers = a.clone();
+                for (atom, exponent) in b {
+                    *powers.entry(atom.clone()).or_insert(0) += exponent;
+                }
+                // Integers skip the reduction of the fraction
+                let product = match x.is_integer() && y.is_integer() {
+                    true => BigRational::from_integer(x.numer() * y.numer()),
+                    false => x * y,
+                };
+                result.add_term(powers, product);
+            }
+        }
+        result
+    }
+
+    pub(crate) fn pow(&self, exponent: u32) -> Self {
+        self.pow_within(exponent, usize::MAX)
+            .expect("there is no limit on the number of products")
+    }
+
+    /// `self^exponent`, or `None` when its degree is above [`MAX_DEGREE`] or
+    /// a multiplication would take more than [`MAX_PRODUCTS`] products.
+    fn bounded_pow(&self, exponent: u32) -> Option<Self> {
+        if self.degree() as u64 * exponent as u64 > MAX_DEGREE {
+            return None;
+        }
+        self.pow_within(exponent, MAX_PRODUCTS)
+    }
+
+    /// `self^exponent` by repeated squaring, giving up before a
+    /// multiplication of more than `max_products` pairs of terms.
+    fn pow_within(&self, mut exponent: u32, max_products: usize) -> Option<Self> {
+        let products = |a: &Self, b: &Self| a.terms.len().saturating_mul(b.terms.len());
+        let mut result = Self::constant(BigRational::one());
+        let mut base = self.clone();
+        loop {
+            if exponent & 1 == 1 {
+                if products(&result, &base) > max_products {
+                    return None;
+                }
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent == 0 {
+                return Some(result);
+            }
+            if products(&base, &base) > max_products {
+                return None;
+            }
+            base = base.mul(&base);
+        }
+    }
+
+    /// The terms in the order they are written out.
+    pub(crate) fn sorted_terms(&self) -> Vec<(&Powers, &BigRational)> {
+        let mut terms: Vec<(&Powers, &BigRational)> = self.terms.iter().collect();
+        terms.sort_by(|(a, _), (b, _)| compare_terms(a, b));
+        terms
+    }
+
+    /// The highest total degree of any term, zero for the zero polynomial.
+    pub(crate) fn degree(&self) -> u32 {
+        self.terms.keys().map(degree).max().unwrap_or(0)
+    }
+
+    /// Orders polynomials by degree, then term by term as they are written
+    /// out, smaller coefficients first.
+    pub(crate) fn compare(&self, other: &Self) -> Ordering {
+        self.degree().cmp(&other.degree()).then_with(|| {
+            for ((a, x), (b, y)) in self.sorted_terms().into_iter().zip(other.sorted_terms()) {
+                let ordering = compare_terms(a, b).then_with(|| x.cmp(y));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            self.terms.len().cmp(&other.terms.len())
+        })
+    }
+
+    /// The polynomial as a sum of terms, highest degree first and atoms in
+    /// alphabetical order within each degree.
+    pub(crate) fn to_expr(&self) -> Expr {
+        let mut result: Option<Expr> = None;
+        for (powers, coefficient) in self.sorted_terms() {
+            let term = term_expr(powers, &coefficient.abs());
+            result = Some(match (result, coefficient.is_negative()) {
+                (None, false) => term,
+                (None, true) => neg(term),
+                (Some(sum), false) => binop(sum, Op::Add, term),
+                (Some(sum), true) => binop(sum, Op::Subtract, term),
+            });
+        }
+        result.unwrap_or(Expr::Number(0.0))
+    }
+}
+
+/// Whether there is a matrix anywhere in `expr`.
+fn has_matrix(expr: &Expr) -> bool {
+    match expr {
+        Expr::Matrix(_) => true,
+        Expr::Spanned { expr, .. } | Expr::UnaryMinus(expr) => has_matrix(expr),
+        Expr::BinOp { lhs, rhs, .. } => has_matrix(lhs) || has_matrix(rhs),
+        Expr::Function { args, .. } => args.iter().any(has_matrix),
+        Expr::Number(_) | Expr::Rational(_) | Expr::Constant(_) | Expr::Monomial { .. } => false,
+    }
+}
+
+fn degree(powers: &Powers) -> u32 {
+    powers.values().sum()
+}
+
+/// Graded lexicographic order: higher degrees first, then higher powers of the
+/// alphabetically first atoms.
+fn compare_terms(a: &Powers, b: &Powers) -> Ordering {
+    degree(b).cmp(&degree(a)).then_with(|| {
+        let mut atoms: Vec<&Atom> = a.keys().chain(b.keys()).collect();
+        atoms.sort();
+        atoms
+            .into_iter()
+            .map(|atom| {
+                let exponent = |powers: &Powers| powers.get(atom).copied().unwrap_or(0);
+                exponent(b).cmp(&exponent(a))
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+/// `value` as an expression, using a quotient when it isn't an integer.
+pub(crate) fn rational_expr(value: &BigRational) -> Expr {
+    let numerator = integer_expr(value.numer());
+    if value.is_integer() {
+        numerator
+    } else {
+        binop(numerator, Op::Divide, integer_expr(value.denom()))
+    }
+}
+
+/// `n` as an expression, which stays exact when `n` has more digits than an
+/// `f64` holds.
+pub(crate) fn integer_expr(n: &BigInt) -> Expr {
+    Expr::from(Rational(BigRational::from_integer(n.clone())))
+}
+
+/// `coefficient` times the atoms in `powers`, with the numerator folded into
+/// a leading monomial and the denominator dividing the whole term.
+fn term_expr(powers: &Powers, coefficient: &BigRational) -> Expr {
+    let mut factors = powers
+        .iter()
+        .map(|(atom, &exponent)| atom.to_expr(exponent));
+    let Some(first) = factors.next() else {
+        return rational_expr(coefficient);
+    };
+
+    let numerator = coefficient.numer();
+    let exact = Rational(BigRational::from_integer(numerator.clone())).to_exact_f64();
+    let first = match (first, exact) {
+        (
+            Expr::Monomial {
+                variable, exponent, ..
+            },
+            Some(numerator),
+        ) => Expr::Monomial {
+            coefficient: numerator,
+            variable,
+            exponent,
+        },
+        (first, _) if numerator.is_one() => first,
+        (first, _) => binop(integer_expr(numerator), Op::Multiply, first),
+    };
+    let product = factors.fold(first, |product, factor| {
+        binop(product, Op::Multiply, factor)
+    });
+
+    if coefficient.is_integer() {
+        product
+    } else {
+        binop(product, Op::Divide, integer_expr(coefficient.denom()))
+    }
+}
@@ -0,0 +1,160 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// Polynomial in one variable with exact rational coefficients, lowest degree
+/// first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Univariate(Vec<BigRational>);
+
+impl Univariate {
+    pub(crate) fn new(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Self(coefficients)
+    }
+
+    /// `a*x + b`.
+    pub(crate) fn linear(a: BigRational, b: BigRational) -> Self {
+        Self::new(vec![b, a])
+    }
+
+    pub(crate) fn coefficients(&self) -> &[BigRational] {
+        &self.0
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The degree, zero for the zero polynomial.
+    pub(crate) fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    pub(crate) fn lead(&self) -> BigRational {
+        self.0.last().cloned().unwrap_or_else(BigRational::zero)
+    }
+
+    fn coefficient(&self, degree: usize) -> BigRational {
+        self.0
+            .get(degree)
+            .cloned()
+            .unwrap_or_else(BigRational::zero)
+    }
+
+    pub(crate) fn scale(&self, factor: &BigRational) -> Self {
+        Self::new(self.0.iter().map(|c| c * factor).collect())
+    }
+
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        let len = self.0.len().max(other.0.len());
+        Self::new(
+            (0..len)
+                .map(|i| self.coefficient(i) - other.coefficient(i))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn derivative(&self) -> Self {
+        Self::new(
+            self.0
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| c * BigRational::from_integer(i.into()))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn evaluate(&self, x: &BigRational) -> BigRational {
+        self.0
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * x + c)
+    }
+
+    /// Quotient and remainder of dividing by `divisor`, which must not be zero.
+    pub(crate) fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let lead = divisor.lead();
+        let mut remainder = self.0.clone();
+        let mut quotient = vec![BigRational::zero(); self.0.len().saturating_sub(divisor.degree())];
+
+        for i in (0..quotient.len()).rev() {
+            let factor = &remainder[i + divisor.degree()] / &lead;
+            for (j, c) in divisor.0.iter().enumerate() {
+                remainder[i + j] -= &factor * c;
+            }
+            quotient[i] = factor;
+        }
+
+        (Self::new(quotient), Self::new(remainder))
+    }
+
+    /// The monic greatest common divisor.
+    pub(crate) fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b);
+            (a, b) = (b, remainder);
+        }
+        if a.is_zero() {
+            a
+        } else {
+            a.scale(&a.lead().recip())
+        }
+    }
+
+    /// The polynomial scaled to have coprime integer coefficients and a
+    /// positive leading coefficient, together with the factor taken out.
+    pub(crate) fn primitive(&self) -> (BigRational, Self) {
+        let numerators = self
+            .0
+            .iter()
+            .fold(BigInt::zero(), |acc, c| acc.gcd(c.numer()));
+        let denominators = self
+            .0
+            .iter()
+            .fold(BigInt::one(), |acc, c| acc.lcm(c.denom()));
+        if numerators.is_zero() {
+            return (BigRational::one(), self.clone());
+        }
+
+        let mut content = BigRational::new(numerators, denominators);
+        if self.lead().is_negative() {
+            content = -content;
+        }
+        (content.clone(), self.scale(&content.recip()))
+    }
+
+    /// Square-free factors with their multiplicities by Yun's algorithm. The
+    /// factors are monic and their product is the polynomial divided by its
+    /// leading coefficient.
+    pub(crate) fn square_free(&self) -> Vec<(Self, u32)> {
+        let mut factors = Vec::new();
+        if self.degree() == 0 {
+            return factors;
+        }
+
+        let derivative = self.derivative();
+        let a = self.gcd(&derivative);
+        let mut b = self.div_rem(&a).0;
+        let mut c = derivative.div_rem(&a).0;
+        let mut d = c.sub(&b.derivative());
+
+        let mut multiplicity = 1;
+        while b.degree() > 0 {
+            let a = b.gcd(&d);
+            b = b.div_rem(&a).0;
+            c = d.div_rem(&a).0;
+            d = c.sub(&b.derivative());
+            if a.degree() > 0 {
+                factors.push((a, multiplicity));
+            }
+            multiplicity += 1;
+        }
+        factors
+    }
+}
@@ -0,0 +1,200 @@
+//! Constructors for the expressions produced by the calculus routines. They fold
+//! numbers and drop neutral operands as they go, so the results stay small
+//! before they are handed to the optimizer.
+
+use crate::parser::{Expr, Op};
+
+pub(crate) fn number(n: f64) -> Expr {
+    if n < 0.0 {
+        Expr::UnaryMinus(Box::new(Expr::Number(-n)))
+    } else {
+        Expr::Number(n)
+    }
+}
+
+/// The value of `expr` if it is a plain (possibly negated) number.
+pub(crate) fn as_number(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Number(n) => Some(*n),
+        Expr::Rational(n) => Some(n.to_f64()),
+        Expr::UnaryMinus(inner) => as_number(inner).map(|n| -n),
+        Expr::Spanned { expr, .. } => as_number(expr),
+        _ => None,
+    }
+}
+
+pub(crate) fn binop(lhs: Expr, op: Op, rhs: Expr) -> Expr {
+    Expr::BinOp {
+        lhs: Box::new(lhs),
+        op,
+        rhs: Box::new(rhs),
+    }
+}
+
+pub(crate) fn neg(expr: Expr) -> Expr {
+    match expr {
+        Expr::UnaryMinus(inner) => *inner,
+        Expr::Monomial {
+            coefficient,
+            variable,
+            exponent,
+        } => Expr::Monomial {
+            coefficient: -coefficient,
+            variable,
+            exponent,
+        },
+        expr => match as_number(&expr) {
+            Some(n) => number(-n),
+            None => Expr::UnaryMinus(Box::new(expr)),
+        },
+    }
+}
+
+pub(crate) fn add(lhs: Expr, rhs: Expr) -> Expr {
+    match (as_number(&lhs), as_number(&rhs)) {
+        (Some(a), Some(b)) => number(a + b),
+        (Some(0.0), _) => rhs,
+        (_, Some(0.0)) => lhs,
+        (_, Some(b)) if b < 0.0 => binop(lhs, Op::Subtract, number(-b)),
+        _ => match rhs {
+            Expr::UnaryMinus(rhs) => binop(lhs, Op::Subtract, *rhs),
+            Expr::Monomial { coefficient, .. } if coefficient < 0.0 => {
+                binop(lhs, Op::Subtract, neg(rhs))
+            }
+            rhs => binop(lhs, Op::Add, rhs),
+        },
+    }
+}
+
+pub(crate) fn sub(lhs: Expr, rhs: Expr) -> Expr {
+    add(lhs, neg(rhs))
+}
+
+pub(crate) fn mul(lhs: Expr, rhs: Expr) -> Expr {
+    match (as_number(&lhs), as_number(&rhs)) {
+        (Some(a), Some(b)) => number(a * b),
+        (Some(0.0), _) | (_, Some(0.0)) => Expr::Number(0.0),
+        (Some(1.0), _) => rhs,
+        (_, Some(1.0)) => lhs,
+        (Some(-1.0), _) => neg(rhs),
+        (_, Some(-1.0)) => neg(lhs),
+        (Some(a), _) => scaled(a, rhs),
+        (_, Some(b)) => scaled(b, lhs),
+        _ => match (reciprocal(&lhs), reciprocal(&rhs)) {
+            // (1/a)*b = b/a
+            (Some(denominator), _) => div(rhs, denominator.clone()),
+            (_, Some(denominator)) => div(lhs, denominator.clone()),
+            _ => binop(lhs, Op::Multiply, rhs),
+        },
+    }
+}
+
+/// `factor*expr`, folded into `expr` when it is a monomial. Numbers go in
+/// front, so `x*2` comes out as `2*x`.
+fn scaled(factor: f64, expr: Expr) -> Expr {
+    match expr {
+        Expr::Monomial {
+            coefficient,
+            variable,
+            exponent,
+        } => Expr::Monomial {
+            coefficient: factor * coefficient,
+            variable,
+            exponent,
+        },
+        Expr::UnaryMinus(inner) => scaled(-factor, *inner),
+        expr => binop(number(factor), Op::Multiply, expr),
+    }
+}
+
+/// `a` if `expr` is `1/a`.
+fn reciprocal(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::BinOp {
+            lhs,
+            op: Op::Divide,
+            rhs,
+        } if as_number(lhs) == Some(1.0) => Some(rhs),
+        _ => None,
+    }
+}
+
+pub(crate) fn div(lhs: Expr, rhs: Expr) -> Expr {
+    match (as_number(&lhs), as_number(&rhs)) {
+        (Some(a), Some(b)) if b != 0.0 && (a / b).fract() == 0.0 => number(a / b),
+        (Some(0.0), _) => Expr::Number(0.0),
+        (_, Some(1.0)) => lhs,
+        (_, Some(-1.0)) => neg(lhs),
+        (_, Some(b)) if b != 0.0 && matches!(lhs, Expr::Monomial { .. }) => scaled(1.0 / b, lhs),
+        _ => match reciprocal(&lhs) {
+            // (1/a)/b = 1/(a*b)
+            Some(denominator) => {
+                binop(Expr::Number(1.0), Op::Divide, mul(denominator.clone(), rhs))
+            }
+            None => binop(lhs, Op::Divide, rhs),
+        },
+    }
+}
+
+pub(crate) fn pow(base: Expr, exponent: Expr) -> Expr {
+    if let (Some(a), Some(b)) = (as_number(&base), as_number(&exponent)) {
+        if a.powf(b).fract() == 0.0 {
+            return number(a.powf(b));
+        }
+    }
+
+    match as_number(&exponent) {
+        Some(0.0) => Expr::Number(1.0),
+        Some(1.0) => base,
+        // (x^a)^n = x^(a*n)
+        Some(n) => match base {
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } if coefficient == 1.0 && n.fract() == 0.0 => Expr::Monomial {
+                coefficient,
+                variable,
+                exponent: exponent * n,
+            },
+            base => binop(base, Op::Power, number(n)),
+        },
+        None => binop(base, Op::Power, exponent),
+    }
+}
+
+pub(crate) fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::Function {
+        name: name.to_string(),
+        args,
+    }
+}
+
+/// Whether `expr` changes with `variable`. Calls are assumed to depend on their
+/// arguments only.
+pub(crate) fn depends_on(expr: &Expr, variable: &str) -> bool {
+    match expr {
+        Expr::Number(_) | Expr::Rational(_) | Expr::Constant(_) => false,
+        Expr::Monomial {
+            coefficient,
+            variable: name,
+            exponent,
+        } => name == variable && *coefficient != 0.0 && *exponent != 0.0,
+        Expr::UnaryMinus(inner) => depends_on(inner, variable),
+        Expr::BinOp { lhs, rhs, .. } => depends_on(lhs, variable) || depends_on(rhs, variable),
+        Expr::Function { args, .. } => match expr.as_series() {
+            // The index of a sum or product is bound, not free
+            Some(series) => {
+                depends_on(series.from, variable)
+                    || depends_on(series.to, variable)
+                    || (series.index != variable && depends_on(series.body, variable))
+            }
+            None => args.iter().any(|arg| depends_on(arg, variable)),
+        },
+        Expr::Matrix(rows) => rows
+            .iter()
+            .flatten()
+            .any(|entry| depends_on(entry, variable)),
+        Expr::Spanned { expr, .. } => depends_on(expr, variable),
+    }
+}
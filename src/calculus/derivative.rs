@@ -0,0 +1,274 @@
+use crate::error::CalculusError;
+use crate::parser::{Constant, Expr, Op, Optimize, Series, SeriesKind};
+
+use super::build::{add, call, depends_on, div, mul, neg, number, pow, sub};
+
+/// Differentiates `expr` with respect to `variable` and simplifies the result.
+/// Both sides of an equation are differentiated. Angles are taken to be in
+/// radians.
+pub fn differentiate(expr: &Expr, variable: &str) -> Result<Expr, CalculusError> {
+    Ok(derive(expr, variable)?.optimize_expression())
+}
+
+fn derive(expr: &Expr, variable: &str) -> Result<Expr, CalculusError> {
+    if !depends_on(expr, variable) {
+        return Ok(Expr::Number(0.0));
+    }
+
+    match expr {
+        Expr::Number(_) | Expr::Rational(_) | Expr::Constant(_) => Ok(Expr::Number(0.0)),
+        Expr::Spanned { expr, .. } => derive(expr, variable),
+        // Matrices are differentiated entry by entry
+        Expr::Matrix(rows) => Ok(Expr::Matrix(
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|entry| derive(entry, variable))
+                        .collect::<Result<Vec<Expr>, CalculusError>>()
+                })
+                .collect::<Result<Vec<Vec<Expr>>, CalculusError>>()?,
+        )),
+        Expr::UnaryMinus(inner) => Ok(neg(derive(inner, variable)?)),
+        // c*x^n => c*n*x^(n-1)
+        Expr::Monomial {
+            coefficient,
+            variable: name,
+            exponent,
+        } => Ok(match exponent - 1.0 {
+            0.0 => number(coefficient * exponent),
+            reduced => Expr::Monomial {
+                coefficient: coefficient * exponent,
+                variable: name.to_owned(),
+                exponent: reduced,
+            },
+        }),
+        Expr::BinOp { lhs, op, rhs } => {
+            let (u, v) = (lhs.unspanned().clone(), rhs.unspanned().clone());
+            let (du, dv) = (derive(&u, variable)?, derive(&v, variable)?);
+
+            match op {
+                Op::Add => Ok(add(du, dv)),
+                Op::Subtract => Ok(sub(du, dv)),
+                Op::Multiply => Ok(add(mul(du, v), mul(u, dv))),
+                Op::Divide if !depends_on(&v, variable) => Ok(div(du, v)),
+                Op::Divide => Ok(div(
+                    sub(mul(du, v.clone()), mul(u, dv)),
+                    pow(v, Expr::Number(2.0)),
+                )),
+                // u % v = u - v*floor(u/v), where floor is flat almost everywhere
+                Op::Modulo => Ok(sub(du, mul(dv, call("floor", vec![div(u, v)])))),
+                Op::Power => Ok(derive_power(u, v, du, dv, variable)),
+                Op::Equals => Ok(Expr::BinOp {
+                    lhs: Box::new(du),
+                    op: Op::Equals,
+                    rhs: Box::new(dv),
+                }),
+                Op::Convert => Err(CalculusError::NotDifferentiable("to".to_string())),
+            }
+        }
+        Expr::Function { name, args } => match expr.as_series() {
+            Some(series) => derive_series(series, variable),
+            None => derive_function(name, args, variable),
+        },
+    }
+}
+
+/// Derivative of `sum(k, a, b, f)`, which is `sum(k, a, b, f')` as long as the
+/// bounds are constant.
+fn derive_series(series: Series, variable: &str) -> Result<Expr, CalculusError> {
+    let bounded = depends_on(series.from, variable) || depends_on(series.to, variable);
+    match series.kind {
+        SeriesKind::Sum if !bounded => Ok(call(
+            series.kind.name(),
+            vec![
+                Expr::Monomial {
+                    coefficient: 1.0,
+                    variable: series.index.to_string(),
+                    exponent: 1.0,
+                },
+                series.from.clone(),
+                series.to.clone(),
+                derive(series.body, variable)?,
+            ],
+        )),
+        _ => Err(CalculusError::NotDifferentiable(format!(
+            "{}()",
+            series.kind.name()
+        ))),
+    }
+}
+
+fn derive_power(u: Expr, v: Expr, du: Expr, dv: Expr, variable: &str) -> Expr {
+    // (u^n)' = n*u^(n-1)*u'
+    if !depends_on(&v, variable) {
+        let reduced = sub(v.clone(), Expr::Number(1.0));
+        return mul(mul(v, pow(u, reduced)), du);
+    }
+
+    // (a^v)' = a^v*ln(a)*v'
+    if !depends_on(&u, variable) {
+        let ln = match u {
+            Expr::Constant(Constant::E) => Expr::Number(1.0),
+            ref u => call("ln", vec![u.clone()]),
+        };
+        return mul(mul(pow(u, v), ln), dv);
+    }
+
+    // (u^v)' = u^v*(v'*ln(u) + v*u'/u)
+    let inner = add(
+        mul(dv, call("ln", vec![u.clone()])),
+        div(mul(v.clone(), du), u.clone()),
+    );
+    mul(pow(u, v), inner)
+}
+
+/// Derivative of a call to one of the built-in functions.
+fn derive_function(name: &str, args: &[Expr], variable: &str) -> Result<Expr, CalculusError> {
+    let args: Vec<Expr> = args.iter().map(|arg| arg.unspanned().clone()).collect();
+
+    match args.as_slice() {
+        [u] => match derive_unary(name, u) {
+            // Chain rule
+            Some(outer) => Ok(mul(outer, derive(u, variable)?)),
+            None => derive_variadic(name, args, variable),
+        },
+        [u, v] => {
+            let (du, dv) = (derive(u, variable)?, derive(v, variable)?);
+            derive_binary(name, u.clone(), v.clone(), du, dv, variable)
+        }
+        _ => derive_variadic(name, args, variable),
+    }
+}
+
+/// `f'(u)` for a function `f` of one argument, `None` when `f` isn't one.
+fn derive_unary(name: &str, u: &Expr) -> Option<Expr> {
+    let f = |name: &str| call(name, vec![u.clone()]);
+    let square = |expr: Expr| pow(expr, Expr::Number(2.0));
+    let one = || Expr::Number(1.0);
+
+    let derivative = match name {
+        "sin" => f("cos"),
+        "cos" => neg(f("sin")),
+        "tan" => square(f("sec")),
+        "sec" => mul(f("sec"), f("tan")),
+        "csc" => neg(mul(f("csc"), f("cot"))),
+        "cot" => neg(square(f("csc"))),
+        "asin" => div(one(), call("sqrt", vec![sub(one(), square(u.clone()))])),
+        "acos" => neg(div(
+            one(),
+            call("sqrt", vec![sub(one(), square(u.clone()))]),
+        )),
+        "atan" => div(one(), add(one(), square(u.clone()))),
+        "sinh" => f("cosh"),
+        "cosh" => f("sinh"),
+        "tanh" => sub(one(), square(f("tanh"))),
+        "asinh" => div(one(), call("sqrt", vec![add(square(u.clone()), one())])),
+        "acosh" => div(one(), call("sqrt", vec![sub(square(u.clone()), one())])),
+        "atanh" => div(one(), sub(one(), square(u.clone()))),
+        "exp" => f("exp"),
+        "ln" => div(one(), u.clone()),
+        "log" | "log10" => div(one(), mul(u.clone(), call("ln", vec![Expr::Number(10.0)]))),
+        "log2" => div(one(), mul(u.clone(), call("ln", vec![Expr::Number(2.0)]))),
+        // Step functions are flat everywhere they are differentiable
+        "floor" | "ceil" | "round" | "trunc" | "sign" => Expr::Number(0.0),
+        "fract" => one(),
+        "abs" => f("sign"),
+        "sqrt" => div(one(), mul(Expr::Number(2.0), f("sqrt"))),
+        "cbrt" => div(one(), mul(Expr::Number(3.0), square(f("cbrt")))),
+        _ => return None,
+    };
+    Some(derivative)
+}
+
+/// Derivative of `f(u, v)` for a function `f` of two arguments.
+fn derive_binary(
+    name: &str,
+    u: Expr,
+    v: Expr,
+    du: Expr,
+    dv: Expr,
+    variable: &str,
+) -> Result<Expr, CalculusError> {
+    match name {
+        // log(b, x) = ln(x)/ln(b)
+        "log" => derive(&div(call("ln", vec![v]), call("ln", vec![u])), variable),
+        "pow" => Ok(derive_power(u, v, du, dv, variable)),
+        // atan2(y, x)' = (x*y' - y*x')/(x^2 + y^2)
+        "atan2" => Ok(div(
+            sub(mul(v.clone(), du), mul(u.clone(), dv)),
+            add(pow(v, Expr::Number(2.0)), pow(u, Expr::Number(2.0))),
+        )),
+        // min(u, v) = (u + v - |u - v|)/2 and max(u, v) = (u + v + |u - v|)/2
+        "min" | "max" => {
+            let spread = mul(call("sign", vec![sub(u, v)]), sub(du.clone(), dv.clone()));
+            let sum = add(du, dv);
+            let numerator = match name {
+                "min" => sub(sum, spread),
+                _ => add(sum, spread),
+            };
+            Ok(div(numerator, Expr::Number(2.0)))
+        }
+        // nthroot(x, n)' = nthroot(x, n)/(n*x)*x'
+        "nthroot" if !depends_on(&v, variable) => Ok(mul(
+            div(call("nthroot", vec![u.clone(), v.clone()]), mul(v, u)),
+            du,
+        )),
+        _ => derive_variadic(name, vec![u, v], variable),
+    }
+}
+
+fn derive_variadic(name: &str, args: Vec<Expr>, variable: &str) -> Result<Expr, CalculusError> {
+    match name {
+        // hypot(a, b, ...)' = (a*a' + b*b' + ...)/hypot(a, b, ...)
+        "hypot" => {
+            let mut numerator = Expr::Number(0.0);
+            for arg in &args {
+                numerator = add(numerator, mul(arg.clone(), derive(arg, variable)?));
+            }
+            Ok(div(numerator, call("hypot", args)))
+        }
+        // sum(a, b, ...)' = a' + b' + ... and mean(a, b, ...)' = sum(a, b, ...)'/n
+        "sum" | "mean" => {
+            let count = args.len() as f64;
+            let mut derivative = Expr::Number(0.0);
+            for arg in &args {
+                derivative = add(derivative, derive(arg, variable)?);
+            }
+            match name {
+                "mean" => Ok(div(derivative, Expr::Number(count))),
+                _ => Ok(derivative),
+            }
+        }
+        // product(a, b, ...)' = a'*b*... + a*b'*... + ...
+        "product" => {
+            let mut derivative = Expr::Number(0.0);
+            for (i, arg) in args.iter().enumerate() {
+                let mut term = derive(arg, variable)?;
+                for (j, other) in args.iter().enumerate() {
+                    if i != j {
+                        term = mul(term, other.clone());
+                    }
+                }
+                derivative = add(derivative, term);
+            }
+            Ok(derivative)
+        }
+        // min(a) = a, and min(a, b, c, ...) = min(min(a, b), c, ...)
+        "min" | "max" => match args.as_slice() {
+            [u] => derive(u, variable),
+            [u, v, rest @ ..] => {
+                let mut nested = vec![call(name, vec![u.clone(), v.clone()])];
+                nested.extend_from_slice(rest);
+                derive(&call(name, nested), variable)
+            }
+            [] => Err(CalculusError::NotDifferentiable(format!("{name}()"))),
+        },
+        "nthroot" | "factorial" | "binomial" | "gcd" | "lcm" | "median" | "mode" | "variance"
+        | "stdev" | "pvariance" | "pstdev" | "percentile" | "quantile" | "binompdf"
+        | "poissoncdf" => Err(CalculusError::NotDifferentiable(format!("{name}()"))),
+        _ => Err(CalculusError::UnknownFunction {
+            name: name.to_string(),
+            found: args.len(),
+        }),
+    }
+}
@@ -0,0 +1,506 @@
+use crate::error::{CalculusError, Error, EvaluatorError};
+use crate::math::round;
+use crate::numeric_evaluator::{evaluate_with, Context};
+use crate::parser::{Constant, Expr, Op, Optimize};
+
+use super::build::{add, as_number, call, depends_on, div, mul, neg, number, pow, sub};
+use super::derivative::differentiate;
+use super::polynomial::Polynomial;
+
+/// How often integration by parts may recurse into another integral.
+const MAX_DEPTH: usize = 8;
+
+/// Points at which two expressions are compared when looking for a
+/// substitution, on both sides of zero so `abs` and other even functions
+/// don't pass for their restriction to positive numbers.
+const SAMPLES: [f64; 12] = [
+    -2.61, -1.17, -0.73, -0.31, -0.13, 0.13, 0.31, 0.47, 0.73, 0.89, 1.17, 2.61,
+];
+
+/// Number of pieces the interval of a definite integral is cut into when
+/// looking for poles.
+const POLE_GRID: usize = 256;
+
+/// Number of steps taken towards the largest value of the integrand within
+/// a piece of the grid.
+const POLE_STEPS: usize = 80;
+
+/// How much larger than anywhere on the grid the integrand must get within a
+/// piece to count as a pole.
+const POLE_RATIO: f64 = 1e8;
+
+/// Computes an antiderivative of `expr` with respect to `variable`, leaving out
+/// the constant of integration. Angles are taken to be in radians.
+pub fn integrate(expr: &Expr, variable: &str) -> Result<Expr, CalculusError> {
+    let expr = expr.clone().strip_spans();
+    match antiderivative(&expr, variable, 0) {
+        Some(result) => Ok(result.optimize_expression()),
+        None => Err(CalculusError::CannotIntegrate(expr.to_string())),
+    }
+}
+
+/// Integrates `expr` over `variable` from `a` to `b`. The antiderivative is
+/// used when one can be found, otherwise the integral is approximated
+/// numerically. Integrands with a pole between `a` and `b` are reported as
+/// [`CalculusError::Unbounded`] rather than integrated across it.
+pub fn integrate_definite(expr: &Expr, variable: &str, a: f64, b: f64) -> Result<f64, Error> {
+    let mut ctx = Context::new();
+    let mut at = |expr: &Expr, x: f64| {
+        ctx.set_variable(variable, x);
+        evaluate_with(expr, &ctx)
+    };
+
+    if has_pole(&mut |x| at(expr, x), a.min(b), a.max(b))? {
+        let expr = expr.clone().strip_spans();
+        return Err(CalculusError::Unbounded(expr.to_string()).into());
+    }
+
+    if let Ok(antiderivative) = integrate(expr, variable) {
+        if let (Ok(upper), Ok(lower)) = (at(&antiderivative, b), at(&antiderivative, a)) {
+            if (upper - lower).is_finite() {
+                return Ok(round(upper - lower, 15));
+            }
+        }
+    }
+    Ok(round(simpson(&mut |x| at(expr, x), a, b)?, 15))
+}
+
+fn variable_expr(variable: &str) -> Expr {
+    Expr::Monomial {
+        coefficient: 1.0,
+        variable: variable.to_string(),
+        exponent: 1.0,
+    }
+}
+
+fn ln_abs(expr: Expr) -> Expr {
+    call("ln", vec![call("abs", vec![expr])])
+}
+
+fn antiderivative(expr: &Expr, variable: &str, depth: usize) -> Option<Expr> {
+    if !depends_on(expr, variable) {
+        return Some(mul(expr.clone(), variable_expr(variable)));
+    }
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    match expr {
+        Expr::UnaryMinus(inner) => Some(neg(antiderivative(inner, variable, depth)?)),
+        // c*x^-1 => c*ln|x|, c*x^n => c/(n+1)*x^(n+1)
+        Expr::Monomial {
+            coefficient,
+            variable: name,
+            exponent,
+        } => Some(match exponent + 1.0 {
+            0.0 => mul(number(*coefficient), ln_abs(variable_expr(name))),
+            raised => Expr::Monomial {
+                coefficient: coefficient / raised,
+                variable: name.to_owned(),
+                exponent: raised,
+            },
+        }),
+        Expr::BinOp { lhs, op, rhs } => match op {
+            Op::Add => Some(add(
+                antiderivative(lhs, variable, depth)?,
+                antiderivative(rhs, variable, depth)?,
+            )),
+            Op::Subtract => Some(sub(
+                antiderivative(lhs, variable, depth)?,
+                antiderivative(rhs, variable, depth)?,
+            )),
+            Op::Multiply if !depends_on(lhs, variable) => {
+                Some(mul(*lhs.clone(), antiderivative(rhs, variable, depth)?))
+            }
+            Op::Multiply if !depends_on(rhs, variable) => {
+                Some(mul(*rhs.clone(), antiderivative(lhs, variable, depth)?))
+            }
+            Op::Multiply => polynomial(expr, variable)
+                .or_else(|| substitution(lhs, rhs, variable))
+                .or_else(|| substitution(rhs, lhs, variable))
+                .or_else(|| substitution(lhs, &first_power(rhs), variable))
+                .or_else(|| substitution(rhs, &first_power(lhs), variable))
+                .or_else(|| by_parts(lhs, rhs, variable, depth))
+                .or_else(|| by_parts(rhs, lhs, variable, depth)),
+            Op::Divide if !depends_on(rhs, variable) => {
+                Some(div(antiderivative(lhs, variable, depth)?, *rhs.clone()))
+            }
+            Op::Divide => match rhs.as_ref() {
+                // a/(c*x^n) => a/c*x^-n
+                Expr::Monomial {
+                    coefficient,
+                    variable: name,
+                    exponent,
+                } => {
+                    let inverse = Expr::Monomial {
+                        coefficient: 1.0 / coefficient,
+                        variable: name.to_owned(),
+                        exponent: -exponent,
+                    };
+                    antiderivative(&mul(*lhs.clone(), inverse), variable, depth)
+                }
+                _ => rational(lhs, rhs, variable)
+                    .or_else(|| substitution(lhs, &reciprocal(rhs), variable)),
+            },
+            Op::Power => substitution(&Expr::Number(1.0), expr, variable)
+                .or_else(|| polynomial(expr, variable)),
+            Op::Modulo | Op::Equals | Op::Convert => None,
+        },
+        Expr::Function { .. } => substitution(&Expr::Number(1.0), expr, variable),
+        Expr::Number(_)
+        | Expr::Rational(_)
+        | Expr::Constant(_)
+        | Expr::Matrix(_)
+        | Expr::Spanned { .. } => None,
+    }
+}
+
+/// `1/expr`, kept as a power so it can be matched by [`outer_antiderivative`].
+fn reciprocal(expr: &Expr) -> Expr {
+    Expr::BinOp {
+        lhs: Box::new(expr.clone()),
+        op: Op::Power,
+        rhs: Box::new(neg(Expr::Number(1.0))),
+    }
+}
+
+/// `expr^1`, so `g'*g` can be integrated as `g^2/2`.
+fn first_power(expr: &Expr) -> Expr {
+    Expr::BinOp {
+        lhs: Box::new(expr.clone()),
+        op: Op::Power,
+        rhs: Box::new(Expr::Number(1.0)),
+    }
+}
+
+fn polynomial(expr: &Expr, variable: &str) -> Option<Expr> {
+    let polynomial = Polynomial::from_expr(expr, variable)?;
+    Some(polynomial.antiderivative().to_expr(variable))
+}
+
+/// `F(g)` and `g` for an integrand `f(g)` whose antiderivative `F` is known.
+fn outer_antiderivative(expr: &Expr) -> Option<(Expr, Expr)> {
+    match expr {
+        Expr::Function { name, args } if args.len() == 1 => {
+            let u = args[0].clone();
+            let f = |name: &str| call(name, vec![u.clone()]);
+            let square = || pow(u.clone(), Expr::Number(2.0));
+            // u*ln(u) - u
+            let u_ln_u = |ln: Expr| sub(mul(u.clone(), ln), u.clone());
+
+            let result = match name.as_str() {
+                "sin" => neg(f("cos")),
+                "cos" => f("sin"),
+                "tan" => neg(ln_abs(f("cos"))),
+                "sec" => ln_abs(add(f("sec"), f("tan"))),
+                "csc" => neg(ln_abs(add(f("csc"), f("cot")))),
+                "cot" => ln_abs(f("sin")),
+                "sinh" => f("cosh"),
+                "cosh" => f("sinh"),
+                "tanh" => call("ln", vec![f("cosh")]),
+                "exp" => f("exp"),
+                "ln" => u_ln_u(f("ln")),
+                "log" | "log10" => div(u_ln_u(f("ln")), call("ln", vec![Expr::Number(10.0)])),
+                "log2" => div(u_ln_u(f("ln")), call("ln", vec![Expr::Number(2.0)])),
+                "asin" => add(
+                    mul(u.clone(), f("asin")),
+                    call("sqrt", vec![sub(Expr::Number(1.0), square())]),
+                ),
+                "acos" => sub(
+                    mul(u.clone(), f("acos")),
+                    call("sqrt", vec![sub(Expr::Number(1.0), square())]),
+                ),
+                "atan" => sub(
+                    mul(u.clone(), f("atan")),
+                    div(
+                        call("ln", vec![add(Expr::Number(1.0), square())]),
+                        Expr::Number(2.0),
+                    ),
+                ),
+                "sqrt" => mul(
+                    div(Expr::Number(2.0), Expr::Number(3.0)),
+                    mul(u.clone(), f("sqrt")),
+                ),
+                "cbrt" => mul(
+                    div(Expr::Number(3.0), Expr::Number(4.0)),
+                    mul(u.clone(), f("cbrt")),
+                ),
+                "abs" => div(mul(u.clone(), f("abs")), Expr::Number(2.0)),
+                _ => return None,
+            };
+            Some((result, u))
+        }
+        Expr::BinOp {
+            lhs,
+            op: Op::Power,
+            rhs,
+        } => match (as_number(rhs), lhs.as_ref()) {
+            (Some(2.0), Expr::Function { name, args }) if args.len() == 1 => {
+                let u = args[0].clone();
+                let f = |name: &str| call(name, vec![u.clone()]);
+                let half = || div(u.clone(), Expr::Number(2.0));
+                let sin_2u = || {
+                    div(
+                        call("sin", vec![mul(Expr::Number(2.0), u.clone())]),
+                        Expr::Number(4.0),
+                    )
+                };
+                let result = match name.as_str() {
+                    "sec" => f("tan"),
+                    "csc" => neg(f("cot")),
+                    // sin(u)^2 = (1 - cos(2u))/2
+                    "sin" => sub(half(), sin_2u()),
+                    // cos(u)^2 = (1 + cos(2u))/2
+                    "cos" => add(half(), sin_2u()),
+                    _ => {
+                        return Some((
+                            div(pow(*lhs.clone(), number(3.0)), number(3.0)),
+                            *lhs.clone(),
+                        ))
+                    }
+                };
+                Some((result, u))
+            }
+            // u^-1 => ln|u|
+            (Some(-1.0), u) => Some((ln_abs(u.clone()), u.clone())),
+            // u^n => u^(n+1)/(n+1)
+            (Some(n), u) => Some((
+                div(pow(u.clone(), number(n + 1.0)), number(n + 1.0)),
+                u.clone(),
+            )),
+            // e^u => e^u
+            (None, Expr::Constant(Constant::E)) => Some((expr.clone(), *rhs.clone())),
+            // a^u => a^u/ln(a)
+            (None, base) if as_number(base).is_some() => Some((
+                div(expr.clone(), call("ln", vec![base.clone()])),
+                *rhs.clone(),
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Integrates `factor*f(g)` when `factor` is a constant multiple of `g'`, so
+/// the result is that multiple of `F(g)`.
+fn substitution(factor: &Expr, outer: &Expr, variable: &str) -> Option<Expr> {
+    let (antiderivative, inner) = outer_antiderivative(outer)?;
+    let derivative = differentiate(&inner, variable).ok()?;
+    let ratio = constant_ratio(factor, &derivative, variable)?;
+    Some(mul(number(ratio), antiderivative))
+}
+
+/// The constant `k` with `lhs = k*rhs`, found by comparing both at a few points.
+fn constant_ratio(lhs: &Expr, rhs: &Expr, variable: &str) -> Option<f64> {
+    if let (Some(lhs), Some(rhs)) = (as_number(lhs), as_number(rhs)) {
+        return (rhs != 0.0).then(|| lhs / rhs);
+    }
+    if lhs == rhs {
+        return Some(1.0);
+    }
+
+    let mut ratios = Vec::new();
+    for x in SAMPLES {
+        let ctx = Context::new().with_variable(variable, x);
+        let (Ok(lhs), Ok(rhs)) = (evaluate_with(lhs, &ctx), evaluate_with(rhs, &ctx)) else {
+            continue;
+        };
+        if rhs != 0.0 && lhs.is_finite() && rhs.is_finite() {
+            ratios.push(lhs / rhs);
+        }
+    }
+
+    let first = *ratios.first()?;
+    let constant = ratios.len() >= 3
+        && ratios
+            .iter()
+            .all(|ratio| (ratio - first).abs() <= 1e-9 * first.abs().max(1.0));
+    constant.then(|| round(first, 12))
+}
+
+/// Integrates `polynomial*other` by parts, differentiating the polynomial when
+/// `other` can be integrated repeatedly and integrating it otherwise.
+fn by_parts(polynomial: &Expr, other: &Expr, variable: &str, depth: usize) -> Option<Expr> {
+    let p = Polynomial::from_expr(polynomial, variable)?;
+
+    let repeats = match other {
+        Expr::Function { name, .. } => {
+            matches!(name.as_str(), "sin" | "cos" | "exp" | "sinh" | "cosh")
+        }
+        Expr::BinOp { op: Op::Power, .. } => true,
+        _ => false,
+    };
+
+    if repeats {
+        // Tabular integration: p*F1 - p'*F2 + p''*F3 - ...
+        let mut result = Expr::Number(0.0);
+        let mut derivative = polynomial.clone();
+        let mut integral = other.clone();
+        for k in 0..=p.degree() {
+            integral = antiderivative(&integral, variable, depth + 1)?;
+            let term = mul(derivative.clone(), integral.clone());
+            result = match k % 2 {
+                0 => add(result, term),
+                _ => sub(result, term),
+            };
+            derivative = differentiate(&derivative, variable).ok()?;
+        }
+        return Some(result);
+    }
+
+    // ∫p*f = P*f - ∫P*f'
+    let integral = p.antiderivative().to_expr(variable);
+    let rest = mul(integral.clone(), differentiate(other, variable).ok()?);
+    Some(sub(
+        mul(integral, other.clone()),
+        antiderivative(&rest.optimize_expression(), variable, depth + 1)?,
+    ))
+}
+
+/// Integrates `numerator/denominator` for polynomials with a denominator of
+/// degree one or two.
+fn rational(numerator: &Expr, denominator: &Expr, variable: &str) -> Option<Expr> {
+    let p = Polynomial::from_expr(numerator, variable)?;
+    let q = Polynomial::from_expr(denominator, variable)?;
+    let (quotient, remainder) = p.div_rem(&q);
+    let integral = quotient.antiderivative().to_expr(variable);
+
+    let rest = match q.degree() {
+        // r/(ax+b) => r/a*ln|ax+b|
+        1 => mul(
+            number(remainder.coefficient(0) / q.coefficient(1)),
+            ln_abs(q.to_expr(variable)),
+        ),
+        // (r1*x + r0)/(ax^2+bx+c) => r1/(2a)*ln|ax^2+bx+c| + (r0 - r1*b/(2a))*∫1/(ax^2+bx+c)
+        2 => {
+            let (a, b, c) = (q.coefficient(2), q.coefficient(1), q.coefficient(0));
+            let (r1, r0) = (remainder.coefficient(1), remainder.coefficient(0));
+            let log = mul(number(r1 / (2.0 * a)), ln_abs(q.to_expr(variable)));
+
+            let scale = r0 - r1 * b / (2.0 * a);
+            // 2ax+b
+            let linear = Polynomial::new(vec![b, 2.0 * a]);
+            let discriminant = b * b - 4.0 * a * c;
+            let reciprocal = match discriminant {
+                // -2/(2ax+b)
+                0.0 => div(number(-2.0), linear.to_expr(variable)),
+                // 2/k*atan((2ax+b)/k) with k = sqrt(4ac-b^2)
+                d if d < 0.0 => {
+                    let k = (-d).sqrt();
+                    let scaled = linear.scale(1.0 / k).to_expr(variable);
+                    mul(number(2.0 / k), call("atan", vec![scaled]))
+                }
+                // 1/k*ln|(2ax+b-k)/(2ax+b+k)| with k = sqrt(b^2-4ac)
+                d => {
+                    let k = d.sqrt();
+                    let ratio = div(
+                        linear.add(&Polynomial::new(vec![-k])).to_expr(variable),
+                        linear.add(&Polynomial::new(vec![k])).to_expr(variable),
+                    );
+                    mul(number(1.0 / k), ln_abs(ratio))
+                }
+            };
+            add(log, mul(number(scale), reciprocal))
+        }
+        _ => return None,
+    };
+    Some(add(integral, rest))
+}
+
+/// Whether `f` is infinite somewhere on `[a, b]`. `f` is sampled on a grid,
+/// and around every local maximum of `|f|` on the grid, the largest value
+/// within the neighbouring pieces is narrowed down by ternary search, which
+/// runs into a pole when there is one.
+fn has_pole(
+    f: &mut dyn FnMut(f64) -> Result<f64, EvaluatorError>,
+    a: f64,
+    b: f64,
+) -> Result<bool, EvaluatorError> {
+    let step = (b - a) / POLE_GRID as f64;
+    let values = (0..=POLE_GRID)
+        .map(|i| a + step * i as f64)
+        .map(|x| Ok((x, f(x)?.abs())))
+        .collect::<Result<Vec<(f64, f64)>, EvaluatorError>>()?;
+    if values.iter().any(|(_, y)| !y.is_finite()) {
+        return Ok(true);
+    }
+
+    let scale = values
+        .iter()
+        .fold(f64::MIN_POSITIVE, |max, (_, y)| max.max(*y));
+    let is_peak = |i: usize| {
+        let y = values[i].1;
+        (i == 0 || values[i - 1].1 <= y) && (i == POLE_GRID || values[i + 1].1 <= y)
+    };
+    for i in (0..=POLE_GRID).filter(|&i| is_peak(i)) {
+        for (lo, hi) in [(i.saturating_sub(1), i), (i, (i + 1).min(POLE_GRID))] {
+            let (mut lo, mut hi) = (values[lo].0, values[hi].0);
+            for _ in 0..POLE_STEPS {
+                let third = (hi - lo) / 3.0;
+                let (left, right) = (lo + third, hi - third);
+                let (y_left, y_right) = (f(left)?.abs(), f(right)?.abs());
+                if !(y_left.is_finite() && y_right.is_finite()) {
+                    return Ok(true);
+                }
+                if y_left < y_right {
+                    lo = left;
+                } else {
+                    hi = right;
+                }
+            }
+            if f((lo + hi) / 2.0)?.abs() > POLE_RATIO * scale {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Adaptive Simpson quadrature of `f` from `a` to `b`.
+fn simpson(
+    f: &mut dyn FnMut(f64) -> Result<f64, EvaluatorError>,
+    a: f64,
+    b: f64,
+) -> Result<f64, EvaluatorError> {
+    fn step(
+        f: &mut dyn FnMut(f64) -> Result<f64, EvaluatorError>,
+        (a, fa): (f64, f64),
+        (m, fm): (f64, f64),
+        (b, fb): (f64, f64),
+        whole: f64,
+        tolerance: f64,
+        depth: usize,
+    ) -> Result<f64, EvaluatorError> {
+        let (lm, rm) = ((a + m) / 2.0, (m + b) / 2.0);
+        let (flm, frm) = (f(lm)?, f(rm)?);
+        let left = (m - a) / 6.0 * (fa + 4.0 * flm + fm);
+        let right = (b - m) / 6.0 * (fm + 4.0 * frm + fb);
+        let error = left + right - whole;
+
+        if depth == 0 || error.abs() <= 15.0 * tolerance {
+            return Ok(left + right + error / 15.0);
+        }
+        Ok(step(
+            f,
+            (a, fa),
+            (lm, flm),
+            (m, fm),
+            left,
+            tolerance / 2.0,
+            depth - 1,
+        )? + step(
+            f,
+            (m, fm),
+            (rm, frm),
+            (b, fb),
+            right,
+            tolerance / 2.0,
+            depth - 1,
+        )?)
+    }
+
+    let m = (a + b) / 2.0;
+    let (fa, fm, fb) = (f(a)?, f(m)?, f(b)?);
+    let whole = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+    step(f, (a, fa), (m, fm), (b, fb), whole, 1e-12, 20)
+}
@@ -0,0 +1,9 @@
+//! Symbolic calculus on parsed expressions.
+
+pub(crate) mod build;
+mod derivative;
+mod integral;
+mod polynomial;
+
+pub use derivative::differentiate;
+pub use integral::{integrate, integrate_definite};
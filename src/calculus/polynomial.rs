@@ -0,0 +1,145 @@
+use crate::parser::{Expr, Op};
+
+use super::build::{add, as_number, number, sub};
+
+/// Highest power expanded when reading `(...)^n` as a polynomial.
+const MAX_POWER: f64 = 32.0;
+
+/// Polynomial in one variable with numeric coefficients, lowest degree first.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Polynomial(Vec<f64>);
+
+impl Polynomial {
+    pub(crate) fn new(mut coefficients: Vec<f64>) -> Self {
+        while coefficients.len() > 1 && coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        if coefficients.is_empty() {
+            coefficients.push(0.0);
+        }
+        Self(coefficients)
+    }
+
+    /// Reads `expr` as a polynomial in `variable`, `None` if it isn't one.
+    pub(crate) fn from_expr(expr: &Expr, variable: &str) -> Option<Self> {
+        match expr {
+            Expr::Number(n) => Some(Self::new(vec![*n])),
+            Expr::Rational(n) => Some(Self::new(vec![n.to_f64()])),
+            Expr::Spanned { expr, .. } => Self::from_expr(expr, variable),
+            Expr::UnaryMinus(inner) => Some(Self::from_expr(inner, variable)?.scale(-1.0)),
+            Expr::Monomial {
+                coefficient,
+                variable: name,
+                exponent,
+            } if name == variable && exponent.fract() == 0.0 && *exponent >= 0.0 => {
+                let mut coefficients = vec![0.0; *exponent as usize + 1];
+                coefficients[*exponent as usize] = *coefficient;
+                Some(Self::new(coefficients))
+            }
+            Expr::BinOp { lhs, op, rhs } => {
+                let lhs = Self::from_expr(lhs, variable)?;
+                match op {
+                    Op::Add => Some(lhs.add(&Self::from_expr(rhs, variable)?)),
+                    Op::Subtract => Some(lhs.add(&Self::from_expr(rhs, variable)?.scale(-1.0))),
+                    Op::Multiply => Some(lhs.mul(&Self::from_expr(rhs, variable)?)),
+                    Op::Divide => match as_number(rhs) {
+                        Some(n) if n != 0.0 => Some(lhs.scale(1.0 / n)),
+                        _ => None,
+                    },
+                    Op::Power => match as_number(rhs) {
+                        Some(n) if n.fract() == 0.0 && (0.0..=MAX_POWER).contains(&n) => {
+                            let mut result = Self::new(vec![1.0]);
+                            for _ in 0..n as usize {
+                                result = result.mul(&lhs);
+                            }
+                            Some(result)
+                        }
+                        _ => None,
+                    },
+                    Op::Modulo | Op::Equals | Op::Convert => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn degree(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    pub(crate) fn coefficient(&self, degree: usize) -> f64 {
+        self.0.get(degree).copied().unwrap_or(0.0)
+    }
+
+    pub(crate) fn scale(&self, factor: f64) -> Self {
+        Self::new(self.0.iter().map(|c| c * factor).collect())
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let len = self.0.len().max(other.0.len());
+        Self::new(
+            (0..len)
+                .map(|i| self.coefficient(i) + other.coefficient(i))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn mul(&self, other: &Self) -> Self {
+        let mut coefficients = vec![0.0; self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                coefficients[i + j] += a * b;
+            }
+        }
+        Self::new(coefficients)
+    }
+
+    /// Quotient and remainder of dividing by `divisor`, which must not be zero.
+    pub(crate) fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let lead = divisor.coefficient(divisor.degree());
+        let mut remainder = self.0.clone();
+        let mut quotient = vec![0.0; self.0.len().saturating_sub(divisor.degree()).max(1)];
+
+        for i in (0..quotient.len()).rev() {
+            if i + divisor.degree() >= remainder.len() {
+                continue;
+            }
+            let factor = remainder[i + divisor.degree()] / lead;
+            quotient[i] = factor;
+            for (j, c) in divisor.0.iter().enumerate() {
+                remainder[i + j] -= factor * c;
+            }
+        }
+
+        remainder.truncate(divisor.degree().max(1));
+        (Self::new(quotient), Self::new(remainder))
+    }
+
+    pub(crate) fn antiderivative(&self) -> Self {
+        let mut coefficients = vec![0.0];
+        for (i, c) in self.0.iter().enumerate() {
+            coefficients.push(c / (i + 1) as f64);
+        }
+        Self::new(coefficients)
+    }
+
+    pub(crate) fn to_expr(&self, variable: &str) -> Expr {
+        let mut result = Expr::Number(0.0);
+        for (degree, c) in self.0.iter().enumerate().rev() {
+            let term = |c: f64| match degree {
+                0 => number(c),
+                _ => Expr::Monomial {
+                    coefficient: c,
+                    variable: variable.to_string(),
+                    exponent: degree as f64,
+                },
+            };
+            result = match *c {
+                0.0 => continue,
+                c if c < 0.0 => sub(result, term(-c)),
+                c => add(result, term(c)),
+            };
+        }
+        result
+    }
+}
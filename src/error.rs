@@ -1,8 +1,15 @@
+use std::fmt;
+
 use thiserror::Error;
+use wasm_bindgen::prelude::*;
 
+use crate::numeric_evaluator::Arity;
+use crate::parser::Span;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 pub enum ParserError {
+    #[error("Syntax error: {message}")]
+    Syntax { message: String, span: Span },
     #[error("Syntax error: no name found for function (this should not happen)")]
     NoFunctionName,
     #[error("Syntax error: no equals sing found '='")]
@@ -13,17 +20,202 @@ pub enum ParserError {
     InvalidToken(String),
     #[error("Syntax error: invalid operator '{0}'")]
     InvalidOperator(String),
+    #[error("Syntax error: invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("Syntax error: no variable found in monomial (this should not happen)")]
+    NoVariable,
+    #[error("Syntax error: empty expression")]
+    EmptyExpression,
+    #[error("Syntax error: matrix rows must have the same length")]
+    RaggedMatrix,
+}
+
+impl ParserError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParserError::Syntax { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 pub enum EvaluatorError {
     #[error("Syntax error: can't find function with the name '{0}'")]
     UnknownFunction(String),
-    #[error("Error while parsing: {0}")]
-    ParseFailure(ParserError),
+    #[error("Can't find a value for the variable '{0}'")]
+    UnknownVariable(String),
+    #[error("Function '{name}' expects {expected} argument(s) but got {found}")]
+    ArityMismatch {
+        name: String,
+        expected: Arity,
+        found: usize,
+    },
+    #[error("Domain error in '{function}': {reason}")]
+    Domain { function: String, reason: String },
+    #[error("Invalid function definition: {0}")]
+    InvalidDefinition(String),
+    #[error("Function '{0}' calls itself")]
+    RecursiveFunction(String),
     #[error("Equality found in evaluator")]
-    EqualityInEval
+    EqualityInEval,
+    #[error("Function '{0}' can't be evaluated with decimals")]
+    NotDecimal(String),
+    #[error("Dimension mismatch in '{operation}': {reason}")]
+    DimensionMismatch { operation: String, reason: String },
+    /// `error` raised while evaluating the part of the input at `span`.
+    #[error("{error}")]
+    At {
+        span: Span,
+        error: Box<EvaluatorError>,
+    },
+}
+
+impl EvaluatorError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            EvaluatorError::At { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without the location it was raised at.
+    pub fn without_span(&self) -> &EvaluatorError {
+        match self {
+            EvaluatorError::At { error, .. } => error.without_span(),
+            error => error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CalculusError {
+    #[error("Can't differentiate unknown function '{name}' of {found} argument(s)")]
+    UnknownFunction { name: String, found: usize },
+    #[error("'{0}' is not differentiable")]
+    NotDifferentiable(String),
+    #[error("Can't integrate '{0}'")]
+    CannotIntegrate(String),
+    #[error("'{0}' is unbounded between the limits of integration")]
+    Unbounded(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SolverError {
+    #[error("Expected an equation")]
+    NotAnEquation,
+    #[error("Can't solve for '{variable}': {reason}")]
+    Unsupported { variable: String, reason: String },
+    #[error("Equation {0} of the system is not linear")]
+    NotLinear(usize),
+}
+
+/// Any error raised while parsing, evaluating or transforming an expression.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+    #[error(transparent)]
+    Evaluator(#[from] EvaluatorError),
+    #[error(transparent)]
+    Calculus(#[from] CalculusError),
+    #[error(transparent)]
+    Solver(#[from] SolverError),
+}
+
+impl Error {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::Parser(error) => error.span(),
+            Error::Evaluator(error) => error.span(),
+            Error::Calculus(_) | Error::Solver(_) => None,
+        }
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Renders the line of `source` containing `span` with carets underneath it.
+pub fn render_snippet(source: &str, span: Span) -> String {
+    // Spans that don't fit `source` point at its end instead
+    let fits = span.start <= span.end
+        && source.is_char_boundary(span.start)
+        && source.is_char_boundary(span.end);
+    let span = if fits {
+        span
+    } else {
+        Span::new(source.len(), source.len())
+    };
+
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[span.start..]
+        .find('\n')
+        .map_or(source.len(), |i| span.start + i);
+    let line = &source[line_start..line_end];
+
+    let offset = source[line_start..span.start].chars().count();
+    let width = source[span.start..span.end.min(line_end)].chars().count();
+
+    format!("{line}\n{}{}", " ".repeat(offset), "^".repeat(width.max(1)))
+}
+
+/// A human readable error with the location in the input it refers to.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    message: String,
+    span: Option<Span>,
+    snippet: Option<String>,
 }
 
+impl Diagnostic {
+    /// Builds a diagnostic for an error raised while handling `source`.
+    pub fn new(error: &Error, source: &str) -> Self {
+        let span = error.span().filter(|span| span.end <= source.len());
 
+        Self {
+            message: error.to_string(),
+            span,
+            snippet: span.map(|span| render_snippet(source, span)),
+        }
+    }
 
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+#[wasm_bindgen]
+impl Diagnostic {
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.to_owned()
+    }
+
+    /// Byte offset where the offending input starts, if known.
+    #[wasm_bindgen(getter)]
+    pub fn start(&self) -> Option<usize> {
+        self.span.map(|span| span.start)
+    }
+
+    /// Byte offset where the offending input ends, if known.
+    #[wasm_bindgen(getter)]
+    pub fn end(&self) -> Option<usize> {
+        self.span.map(|span| span.end)
+    }
+
+    /// The input line with carets under the offending part, if known.
+    #[wasm_bindgen(getter)]
+    pub fn snippet(&self) -> Option<String> {
+        self.snippet.to_owned()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.snippet {
+            Some(snippet) => write!(f, "{}\n{snippet}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -1,16 +1,243 @@
 use wasm_bindgen::prelude::*;
 
+pub mod algebra;
+pub mod calculus;
+pub mod error;
 mod math;
-mod error;
-mod parser;
-mod optimizer;
 pub mod numeric_evaluator;
+mod optimizer;
+pub mod parser;
+pub mod solver;
 
 #[cfg(test)]
 mod tests;
 
+use error::{Diagnostic, Error};
+use numeric_evaluator::{AngleMode, ComplexFormat, Context, Precision, Quantity, RoundingMode};
+use parser::ParserOptions;
+
+/// Evaluates `expression`, interpreting angles in `angle_mode` (radians when omitted).
+#[wasm_bindgen]
+pub fn evaluate(expression: &str, angle_mode: Option<AngleMode>) -> Result<f64, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(numeric_evaluator::evaluate_with(&expr, &ctx)?));
+    result.map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Evaluates `expression` with `digits` significant digits, rounding every
+/// result with `rounding` (half to even when omitted), and returns the decimal
+/// as a string.
+#[wasm_bindgen]
+pub fn evaluate_decimal(
+    expression: &str,
+    digits: u32,
+    rounding: Option<RoundingMode>,
+    angle_mode: Option<AngleMode>,
+) -> Result<String, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let precision = Precision::new(digits, rounding.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| {
+            Ok(numeric_evaluator::evaluate_decimal_with(
+                &expr, &ctx, precision,
+            )?)
+        });
+    result
+        .map(|value| value.to_string())
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Evaluates `expression` over the complex numbers, reading `i` as the
+/// imaginary unit, and writes the result in `format` (`a + bi` when omitted).
+#[wasm_bindgen]
+pub fn evaluate_complex(
+    expression: &str,
+    angle_mode: Option<AngleMode>,
+    format: Option<ComplexFormat>,
+) -> Result<String, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        complex: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(numeric_evaluator::evaluate_complex_with(&expr, &ctx)?));
+    result
+        .map(|z| numeric_evaluator::format_complex(z, format.unwrap_or_default()))
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Evaluates `expression` over the sedenions, reading `i`, `j`, `k` and
+/// `e1`..`e15` as basis units, and writes the result as `a + bi + cj + ...`.
+#[wasm_bindgen]
+pub fn evaluate_hypercomplex(
+    expression: &str,
+    angle_mode: Option<AngleMode>,
+) -> Result<String, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        hypercomplex: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(numeric_evaluator::evaluate_hypercomplex_with(&expr, &ctx)?));
+    result
+        .map(|z| z.to_string())
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Evaluates `expression`, which may contain matrix literals like
+/// `[1, 2; 3, 4]`, and writes the resulting scalar or matrix.
+#[wasm_bindgen]
+pub fn evaluate_matrix(
+    expression: &str,
+    angle_mode: Option<AngleMode>,
+) -> Result<String, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(numeric_evaluator::evaluate_matrix_with(&expr, &ctx)?));
+    result
+        .map(|value| value.to_string())
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Evaluates `expression` with physical units, like `3 m/s * 2 min` or
+/// `5 ft in cm`, returning the value along with its unit.
+#[wasm_bindgen]
+pub fn evaluate_units(
+    expression: &str,
+    angle_mode: Option<AngleMode>,
+) -> Result<Quantity, Diagnostic> {
+    let ctx = Context::new().with_angle_mode(angle_mode.unwrap_or_default());
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+
+    let result = parser::parse_with(expression, &options)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(numeric_evaluator::evaluate_units_with(&expr, &ctx)?));
+    result.map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Multiplies out `expression` into a canonical sum of terms.
+#[wasm_bindgen]
+pub fn expand(expression: &str) -> Result<String, Diagnostic> {
+    parser::parse(expression)
+        .map(|expr| algebra::expand(&expr).to_string())
+        .map_err(|error| Diagnostic::new(&Error::from(error), expression))
+}
+
+/// Factors `expression` over the rationals.
+#[wasm_bindgen]
+pub fn factor(expression: &str) -> Result<String, Diagnostic> {
+    parser::parse(expression)
+        .map(|expr| algebra::factor(&expr).to_string())
+        .map_err(|error| Diagnostic::new(&Error::from(error), expression))
+}
+
+/// Differentiates `expression` with respect to `variable`, returning the
+/// simplified derivative.
+#[wasm_bindgen]
+pub fn differentiate(expression: &str, variable: &str) -> Result<String, Diagnostic> {
+    let result = parser::parse(expression)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(calculus::differentiate(&expr, variable)?));
+    result
+        .map(|derivative| derivative.to_string())
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Integrates `expression` with respect to `variable`, returning an
+/// antiderivative without the constant of integration.
+#[wasm_bindgen]
+pub fn integrate(expression: &str, variable: &str) -> Result<String, Diagnostic> {
+    let result = parser::parse(expression)
+        .map_err(Error::from)
+        .and_then(|expr| Ok(calculus::integrate(&expr, variable)?));
+    result
+        .map(|integral| integral.to_string())
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Integrates `expression` over `variable` from `a` to `b`.
+#[wasm_bindgen]
+pub fn integrate_definite(
+    expression: &str,
+    variable: &str,
+    a: f64,
+    b: f64,
+) -> Result<f64, Diagnostic> {
+    parser::parse(expression)
+        .map_err(Error::from)
+        .and_then(|expr| calculus::integrate_definite(&expr, variable, a, b))
+        .map_err(|error| Diagnostic::new(&error, expression))
+}
+
+/// Solves the linear or quadratic `equation` for `variable`.
+#[wasm_bindgen]
+pub fn solve(equation: &str, variable: &str) -> Result<String, Diagnostic> {
+    let result = parser::parse_equation(equation)
+        .map_err(Error::from)
+        .and_then(|equation| Ok(solver::solve(&equation, variable)?));
+    result
+        .map(|solutions| solutions.to_string())
+        .map_err(|error| Diagnostic::new(&error, equation))
+}
+
+/// Solves the system of linear `equations`, separated by `;`, for all of their
+/// variables.
+#[wasm_bindgen]
+pub fn solve_system(equations: &str) -> Result<String, Diagnostic> {
+    let result = parser::parse_system(equations)
+        .map_err(Error::from)
+        .and_then(|system| Ok(solver::solve_system(&system)?));
+    result
+        .map(|solution| solution.to_string())
+        .map_err(|error| Diagnostic::new(&error, equations))
+}
 
+/// Finds the real roots of `equation` for `variable` between `start` and `end`.
 #[wasm_bindgen]
-pub fn evaluate(expression: &str) -> f64 {
-    numeric_evaluator::evaluate(expression).unwrap()
-}
\ No newline at end of file
+pub fn numeric_solve(
+    equation: &str,
+    variable: &str,
+    start: f64,
+    end: f64,
+) -> Result<Vec<f64>, Diagnostic> {
+    let options = solver::NumericSolveOptions {
+        start,
+        end,
+        ..Default::default()
+    };
+    parser::parse_equation(equation)
+        .map_err(Error::from)
+        .and_then(|equation| solver::numeric_solve(&equation, variable, &options))
+        .map(|roots| roots.iter().map(|root| root.value).collect())
+        .map_err(|error| Diagnostic::new(&error, equation))
+}
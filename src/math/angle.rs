@@ -1,5 +1,42 @@
 use std::f64::consts::PI;
 
+use wasm_bindgen::prelude::*;
+
+/// Unit used for the arguments of trigonometric functions and the results of
+/// their inverses.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleMode {
+    /// Converts `a`, given in this unit, to radians.
+    pub fn to_radians(self, a: f64) -> f64 {
+        match self {
+            AngleMode::Radians => a,
+            AngleMode::Degrees => deg_to_rad(a),
+            AngleMode::Gradians => a * (PI / 200.0),
+        }
+    }
+
+    /// Converts `a`, given in radians, to this unit.
+    pub fn from_radians(self, a: f64) -> f64 {
+        match self {
+            AngleMode::Radians => a,
+            AngleMode::Degrees => rad_to_deg(a),
+            AngleMode::Gradians => a * (200.0 / PI),
+        }
+    }
+}
+
 pub fn deg_to_rad(a: f64) -> f64 {
-    a*(PI/180.0)
-}
\ No newline at end of file
+    a * (PI / 180.0)
+}
+
+pub fn rad_to_deg(a: f64) -> f64 {
+    a * (180.0 / PI)
+}
@@ -0,0 +1,46 @@
+use num_complex::Complex64;
+use wasm_bindgen::prelude::*;
+
+/// How [`format_complex`] writes a complex number.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComplexFormat {
+    /// `a + bi`.
+    #[default]
+    Rectangular,
+    /// `r e^(θi)`, with the angle in radians between -π and π.
+    Polar,
+}
+
+/// `b` as the imaginary part `bi`, writing `i` and `-i` for `±1`.
+fn imaginary(b: f64) -> String {
+    match b {
+        1.0 => "i".to_string(),
+        -1.0 => "-i".to_string(),
+        b => format!("{b}i"),
+    }
+}
+
+/// Writes `z` in `format`. Both forms read back as the same number when
+/// parsed with [`ParserOptions::complex`](crate::parser::ParserOptions::complex)
+/// set.
+pub fn format_complex(z: Complex64, format: ComplexFormat) -> String {
+    // Adding zero turns negative zeros into positive ones
+    let (re, im) = (z.re + 0.0, z.im + 0.0);
+    match format {
+        ComplexFormat::Rectangular => match (re, im) {
+            (re, 0.0) => format!("{re}"),
+            (0.0, im) => imaginary(im),
+            (re, im) if im < 0.0 => format!("{re} - {}", imaginary(-im)),
+            (re, im) => format!("{re} + {}", imaginary(im)),
+        },
+        ComplexFormat::Polar => {
+            let (r, theta) = Complex64::new(re, im).to_polar();
+            if r == 0.0 || theta == 0.0 {
+                format!("{r}")
+            } else {
+                format!("{r}e^({})", imaginary(theta))
+            }
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::math::AngleMode;
+
+use super::{digit_count, pow10, Decimal, Precision, RoundingMode, GUARD_DIGITS};
+
+/// Largest magnitude of an argument to `exp`, beyond which the result doesn't
+/// fit in the exponent.
+const MAX_EXP_MAGNITUDE: i64 = 15;
+
+/// Largest integer exponent raised to by repeated multiplication.
+const MAX_INTEGER_POWER: u64 = 1 << 20;
+
+/// `sum(x^(2i+1)/(2i+1))` for `x = 1/k`, in fixed point with `scale` as one.
+/// The signs alternate for the inverse tangent and don't for the inverse
+/// hyperbolic tangent.
+fn inverse_series(k: u64, scale: &BigInt, alternating: bool) -> BigInt {
+    let k_squared = BigInt::from(k * k);
+    let mut power = scale / k;
+    let mut sum = BigInt::zero();
+    let mut i: u64 = 0;
+    while !power.is_zero() {
+        let term = &power / (2 * i + 1);
+        if alternating && i % 2 == 1 {
+            sum -= term;
+        } else {
+            sum += term;
+        }
+        power /= &k_squared;
+        i += 1;
+    }
+    sum
+}
+
+/// Whether `term` no longer changes `sum` at `precision`.
+fn negligible(term: &Decimal, sum: &Decimal, precision: Precision) -> bool {
+    term.is_zero() || term.magnitude() < sum.magnitude() - precision.digits as i64 - 1
+}
+
+/// `x - x^3/3! + x^5/5! - ...` when `first` is `x` and `start` is 1, and the
+/// series of the cosine when they are 1 and 0.
+fn alternating_series(x: &Decimal, first: Decimal, start: i64, working: Precision) -> Decimal {
+    let x_squared = (x * x).round(working);
+    let mut term = first.clone();
+    let mut sum = first;
+    let mut k = start;
+    loop {
+        let divisor = Decimal::from((k + 1) * (k + 2));
+        term = -(&term * &x_squared)
+            .checked_div(&divisor, working)
+            .expect("divisor is not zero");
+        if negligible(&term, &sum, working) {
+            return sum;
+        }
+        sum = (&sum + &term).round(working);
+        k += 2;
+    }
+}
+
+fn half() -> Decimal {
+    Decimal::new(5.into(), -1)
+}
+
+impl Decimal {
+    /// π rounded to `precision`.
+    pub fn pi(precision: Precision) -> Self {
+        // Machin's formula, π = 16 atan(1/5) - 4 atan(1/239)
+        let digits = precision.digits + GUARD_DIGITS;
+        let scale = pow10(digits as u64);
+        let pi = inverse_series(5, &scale, true) * 16 - inverse_series(239, &scale, true) * 4;
+        Self::new(pi, -(digits as i64)).round(precision)
+    }
+
+    /// ln(10) rounded to `precision`.
+    fn ln10(precision: Precision) -> Self {
+        // ln(10) = 3 ln(2) + ln(5/4), where ln(2) = 2 atanh(1/3) and
+        // ln(5/4) = 2 atanh(1/9)
+        let digits = precision.digits + GUARD_DIGITS;
+        let scale = pow10(digits as u64);
+        let ln10 = inverse_series(3, &scale, false) * 6 + inverse_series(9, &scale, false) * 2;
+        Self::new(ln10, -(digits as i64)).round(precision)
+    }
+
+    pub fn sqrt(&self, precision: Precision) -> Option<Self> {
+        self.nth_root(2, precision)
+    }
+
+    /// The real `n`th root, `None` for even roots of negative numbers and for
+    /// `n = 0`.
+    pub fn nth_root(&self, n: u32, precision: Precision) -> Option<Self> {
+        if n == 0 || (self.is_negative() && n.is_multiple_of(2)) {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(Self::zero());
+        }
+
+        // Shift the mantissa so its root has more digits than needed and the
+        // exponent divides evenly
+        let degree = n as i64;
+        let mut shift =
+            (degree * (precision.digits as i64 + 2) - digit_count(&self.mantissa)).max(0);
+        shift += (self.exponent - shift).rem_euclid(degree);
+        let radicand =
+            self.mantissa.magnitude() * num_bigint::BigUint::from(10u8).pow(shift as u32);
+        let root = radicand.nth_root(n);
+        let sticky = u8::from(root.pow(n) != radicand);
+
+        let mut mantissa = BigInt::from(root * 10u8 + sticky);
+        if self.is_negative() {
+            mantissa = -mantissa;
+        }
+        Some(Self::new(mantissa, (self.exponent - shift) / degree - 1).round(precision))
+    }
+
+    /// `e^self`, `None` if the result is too large or too small to represent.
+    pub fn exp(&self, precision: Precision) -> Option<Self> {
+        if self.is_zero() {
+            return Some(Self::from(1));
+        }
+        if self.magnitude() > MAX_EXP_MAGNITUDE {
+            return None;
+        }
+        let working = precision.working(GUARD_DIGITS + self.magnitude().max(0) as u32 + 1);
+
+        // e^x = 10^n e^r with |r| <= ln(10)/2
+        let ln10 = Self::ln10(working);
+        let n = self
+            .checked_div(&ln10, working)?
+            .round_at(0, RoundingMode::HalfEven);
+        let r = (self - &(&n * &ln10)).round(working);
+
+        // e^r = (e^(r/256))^256, and the series of e^(r/256) converges quickly
+        let r = (&r * &Self::new(390625.into(), -8)).round(working);
+        let mut term = Self::from(1);
+        let mut sum = term.clone();
+        let mut k = 1;
+        loop {
+            term = (&term * &r).checked_div(&Self::from(k), working)?;
+            if negligible(&term, &sum, working) {
+                break;
+            }
+            sum = (&sum + &term).round(working);
+            k += 1;
+        }
+        for _ in 0..8 {
+            sum = (&sum * &sum).round(working);
+        }
+        let result = sum.shift(n.to_integer()?.to_i64()?).round(precision);
+        result.is_in_range().then_some(result)
+    }
+
+    /// The natural logarithm, `None` unless the number is positive.
+    pub fn ln(&self, precision: Precision) -> Option<Self> {
+        if self.is_zero() || self.is_negative() {
+            return None;
+        }
+        let one = Self::from(1);
+        let distance = self - &one;
+        if distance.is_zero() {
+            return Some(Self::zero());
+        }
+
+        // Close to 1 the result is about x - 1, so as many more digits are
+        // needed as it has leading zeros. Further away, x = m 10^e and
+        // ln(x) = ln(m) + e ln(10).
+        let (mut m, exponent) = if distance.abs() < half() {
+            (self.clone(), 0)
+        } else {
+            let exponent = self.magnitude();
+            (self.shift(-exponent), exponent)
+        };
+        let extra = (-distance.magnitude()).max(0) + digit_count(&exponent.into());
+        let working = precision.working(GUARD_DIGITS + extra as u32);
+
+        // ln(m) = 2^k ln(m^(1/2^k)), taking square roots until m is close to 1
+        let close = Self::new(1.into(), -2);
+        let mut halvings = 0;
+        while (&m - &one).abs() > close {
+            m = m.sqrt(working)?;
+            halvings += 1;
+        }
+
+        // ln(m) = 2 atanh(z) with z = (m - 1)/(m + 1)
+        let z = (&m - &one).checked_div(&(&m + &one), working)?;
+        let z_squared = (&z * &z).round(working);
+        let mut power = z.clone();
+        let mut sum = z;
+        let mut i = 1;
+        loop {
+            power = (&power * &z_squared).round(working);
+            let term = power.checked_div(&Self::from(2 * i + 1), working)?;
+            if negligible(&term, &sum, working) {
+                break;
+            }
+            sum = (&sum + &term).round(working);
+            i += 1;
+        }
+
+        let ln_m = &sum * &Self::from(1 << (halvings + 1));
+        let result = &ln_m + &(&Self::from(exponent) * &Self::ln10(working));
+        Some(result.round(precision))
+    }
+
+    /// `self^exponent`, `None` if the result isn't a real number or is too
+    /// large to represent.
+    pub fn pow(&self, exponent: &Self, precision: Precision) -> Option<Self> {
+        let integer = exponent.to_integer();
+        if let Some(n) = integer.as_ref().and_then(|n| n.to_i64()) {
+            if n.unsigned_abs() <= MAX_INTEGER_POWER {
+                return self.powi(n, precision);
+            }
+        }
+        if self.is_zero() {
+            return (!exponent.is_negative()).then(Self::zero);
+        }
+        if self.is_negative() && integer.is_none() {
+            return None;
+        }
+
+        // x^y = e^(y ln|x|), where the logarithm needs as many more digits as
+        // the product has in its integer part
+        let estimate = exponent * &self.abs().ln(precision.working(GUARD_DIGITS))?;
+        let working = precision.working(GUARD_DIGITS + estimate.magnitude().max(0) as u32);
+        let ln = self.abs().ln(working)?;
+        let result = (exponent * &ln).round(working).exp(working)?;
+        let negative = self.is_negative() && integer.is_some_and(|n| n.is_odd());
+        Some(if negative { -result } else { result }.round(precision))
+    }
+
+    /// `self^n` by repeated squaring, `None` for negative powers of zero.
+    fn powi(&self, n: i64, precision: Precision) -> Option<Self> {
+        let working = precision.working(GUARD_DIGITS + digit_count(&n.into()) as u32);
+        let mut result = Self::from(1);
+        let mut base = self.clone();
+        let mut remaining = n.unsigned_abs();
+        while remaining > 0 {
+            if remaining % 2 == 1 {
+                result = (&result * &base).round(working);
+            }
+            base = (&base * &base).round(working);
+            remaining /= 2;
+        }
+
+        if n < 0 {
+            Self::from(1).checked_div(&result, precision)
+        } else {
+            Some(result.round(precision))
+        }
+    }
+
+    /// The remainder in radians after subtracting the nearest multiple `k` of
+    /// a quarter turn, with `k mod 4` and the precision the remainder was
+    /// computed with. Quarter turns of 90° and 100 gradians are subtracted
+    /// exactly, and π/2 carries as many more digits as the argument has in its
+    /// integer part, so the remainder keeps all of its digits.
+    fn quarter_turns(&self, angle_mode: AngleMode, precision: Precision) -> (Self, u32, Precision) {
+        let working = precision.working(GUARD_DIGITS + self.magnitude().max(0) as u32);
+        let (quarter, half_turn) = match angle_mode {
+            AngleMode::Radians => (&Self::pi(working) * &half(), None),
+            AngleMode::Degrees => (Self::from(90), Some(Self::from(180))),
+            AngleMode::Gradians => (Self::from(100), Some(Self::from(200))),
+        };
+        let k = self
+            .checked_div(&quarter, working)
+            .expect("a quarter turn is not zero")
+            .round_at(0, RoundingMode::HalfEven);
+        let remainder = self - &(&k * &quarter);
+        let remainder = match half_turn {
+            None => remainder.round(working),
+            Some(half_turn) => (&remainder * &Self::pi(working))
+                .checked_div(&half_turn, working)
+                .expect("half a turn is not zero"),
+        };
+        let quadrant = k.to_integer().unwrap_or_default().mod_floor(&4.into());
+        (remainder, quadrant.to_u32().unwrap_or_default(), working)
+    }
+
+    /// `sin(r + quadrant π/2)` for a remainder `r` from
+    /// [`Decimal::quarter_turns`].
+    fn sine_in_quadrant(r: &Self, quadrant: u32, working: Precision) -> Self {
+        let sin = || alternating_series(r, r.clone(), 1, working);
+        let cos = || alternating_series(r, Self::from(1), 0, working);
+        match quadrant {
+            0 => sin(),
+            1 => cos(),
+            2 => -sin(),
+            _ => -cos(),
+        }
+    }
+
+    /// The sine of an angle in `angle_mode`.
+    pub fn sin(&self, angle_mode: AngleMode, precision: Precision) -> Self {
+        let (r, quadrant, working) = self.quarter_turns(angle_mode, precision);
+        Self::sine_in_quadrant(&r, quadrant, working).round(precision)
+    }
+
+    /// The cosine of an angle in `angle_mode`.
+    pub fn cos(&self, angle_mode: AngleMode, precision: Precision) -> Self {
+        let (r, quadrant, working) = self.quarter_turns(angle_mode, precision);
+        Self::sine_in_quadrant(&r, (quadrant + 1) % 4, working).round(precision)
+    }
+
+    /// The tangent of an angle in `angle_mode`, `None` where the cosine is
+    /// zero.
+    pub fn tan(&self, angle_mode: AngleMode, precision: Precision) -> Option<Self> {
+        let (r, quadrant, working) = self.quarter_turns(angle_mode, precision);
+        let sin = Self::sine_in_quadrant(&r, quadrant, working);
+        let cos = Self::sine_in_quadrant(&r, (quadrant + 1) % 4, working);
+        sin.checked_div(&cos, precision)
+    }
+
+    /// The inverse tangent in radians.
+    pub fn atan(&self, precision: Precision) -> Self {
+        let working = precision.working(GUARD_DIGITS);
+        let one = Self::from(1);
+        if self.abs() <= one {
+            return Self::atan_reduced(self, working).round(precision);
+        }
+
+        // atan(x) = ±π/2 - atan(1/x)
+        let half_pi = &Self::pi(working) * &half();
+        let inverse = one.checked_div(self, working).expect("self is not zero");
+        let result = if self.is_negative() {
+            -&half_pi
+        } else {
+            half_pi
+        };
+        (&result - &Self::atan_reduced(&inverse, working)).round(precision)
+    }
+
+    /// The inverse tangent of `x` with `|x| <= 1`.
+    fn atan_reduced(x: &Self, working: Precision) -> Self {
+        // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))) halves the argument each
+        // time
+        let one = Self::from(1);
+        let mut x = x.clone();
+        for _ in 0..4 {
+            let root = (&one + &(&x * &x))
+                .sqrt(working)
+                .expect("argument is positive");
+            x = x
+                .checked_div(&(&one + &root), working)
+                .expect("divisor is positive");
+        }
+
+        let x_squared = (&x * &x).round(working);
+        let mut power = x.clone();
+        let mut sum = x;
+        let mut i = 1;
+        loop {
+            power = -(&power * &x_squared).round(working);
+            let term = power
+                .checked_div(&Self::from(2 * i + 1), working)
+                .expect("divisor is not zero");
+            if negligible(&term, &sum, working) {
+                break;
+            }
+            sum = (&sum + &term).round(working);
+            i += 1;
+        }
+        &sum * &Self::from(16)
+    }
+
+    /// The inverse sine in radians, `None` outside of `[-1, 1]`.
+    pub fn asin(&self, precision: Precision) -> Option<Self> {
+        let one = Self::from(1);
+        if self.abs() > one {
+            return None;
+        }
+        let working = precision.working(GUARD_DIGITS);
+        let cos = (&one - &(self * self)).sqrt(working)?;
+        if cos.is_zero() {
+            let half_pi = &Self::pi(working) * &half();
+            let result = if self.is_negative() {
+                -half_pi
+            } else {
+                half_pi
+            };
+            return Some(result.round(precision));
+        }
+        Some(self.checked_div(&cos, working)?.atan(precision))
+    }
+
+    /// The inverse cosine in radians, `None` outside of `[-1, 1]`.
+    pub fn acos(&self, precision: Precision) -> Option<Self> {
+        let one = Self::from(1);
+        if self.abs() > one {
+            return None;
+        }
+        let working = precision.working(GUARD_DIGITS);
+        if self == &-&one {
+            return Some(Self::pi(precision));
+        }
+        // acos(x) = 2 atan(sqrt((1 - x)/(1 + x))) doesn't cancel near 1
+        let ratio = (&one - self).checked_div(&(&one + self), working)?;
+        let atan = ratio.sqrt(working)?.atan(working);
+        Some((&atan * &Self::from(2)).round(precision))
+    }
+
+    /// The angle of the point `(x, y)` in radians, between -π and π.
+    pub fn atan2(y: &Self, x: &Self, precision: Precision) -> Self {
+        let working = precision.working(GUARD_DIGITS);
+        if x.is_zero() {
+            let half_pi = &Self::pi(working) * &half();
+            let result = match y {
+                y if y.is_zero() => Self::zero(),
+                y if y.is_negative() => -half_pi,
+                _ => half_pi,
+            };
+            return result.round(precision);
+        }
+
+        let atan = y
+            .checked_div(x, working)
+            .expect("x is not zero")
+            .atan(working);
+        if !x.is_negative() {
+            return atan.round(precision);
+        }
+        let pi = Self::pi(working);
+        let result = if y.is_negative() {
+            &atan - &pi
+        } else {
+            &atan + &pi
+        };
+        result.round(precision)
+    }
+
+    /// Converts an angle given in `angle_mode` to radians.
+    pub fn to_radians(&self, angle_mode: AngleMode, precision: Precision) -> Self {
+        let working = precision.working(GUARD_DIGITS);
+        let turn = match angle_mode {
+            AngleMode::Radians => return self.round(precision),
+            AngleMode::Degrees => Self::from(180),
+            AngleMode::Gradians => Self::from(200),
+        };
+        (self * &Self::pi(working))
+            .checked_div(&turn, precision)
+            .expect("half a turn is not zero")
+    }
+
+    /// Converts an angle in radians to `angle_mode`.
+    pub fn from_radians(&self, angle_mode: AngleMode, precision: Precision) -> Self {
+        let working = precision.working(GUARD_DIGITS);
+        let turn = match angle_mode {
+            AngleMode::Radians => return self.round(precision),
+            AngleMode::Degrees => Self::from(180),
+            AngleMode::Gradians => Self::from(200),
+        };
+        (self * &turn)
+            .checked_div(&Self::pi(working), precision)
+            .expect("π is not zero")
+    }
+}
@@ -0,0 +1,412 @@
+mod elementary;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Pow, Signed, Zero};
+use wasm_bindgen::prelude::*;
+
+use super::Rational;
+
+/// Extra digits carried through intermediate results so that the final
+/// rounding is the only one that shows.
+pub(crate) const GUARD_DIGITS: u32 = 10;
+
+/// Largest magnitude of the exponent of the leading digit of a result.
+/// Adding numbers further apart than this would build integers with as many
+/// digits.
+const MAX_MAGNITUDE: i64 = 100_000;
+
+/// How a result is rounded when it has more digits than its [`Precision`].
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// To the nearest value, ties to an even last digit.
+    #[default]
+    HalfEven,
+    /// To the nearest value, ties away from zero.
+    HalfUp,
+    /// To the nearest value, ties towards zero.
+    HalfDown,
+    /// Away from zero.
+    Up,
+    /// Towards zero.
+    Down,
+    /// Towards positive infinity.
+    Ceiling,
+    /// Towards negative infinity.
+    Floor,
+}
+
+impl RoundingMode {
+    /// Whether a value that was cut off after `quotient` should move one unit
+    /// away from zero, given how the cut off part compares to half a unit.
+    fn rounds_away(self, quotient: &BigInt, negative: bool, half: Ordering) -> bool {
+        match self {
+            RoundingMode::HalfEven => {
+                half == Ordering::Greater || (half == Ordering::Equal && quotient.is_odd())
+            }
+            RoundingMode::HalfUp => half != Ordering::Less,
+            RoundingMode::HalfDown => half == Ordering::Greater,
+            RoundingMode::Up => true,
+            RoundingMode::Down => false,
+            RoundingMode::Ceiling => !negative,
+            RoundingMode::Floor => negative,
+        }
+    }
+}
+
+/// Number of significant digits every result is rounded to, and how.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precision {
+    pub digits: u32,
+    pub rounding: RoundingMode,
+}
+
+impl Default for Precision {
+    /// 34 digits rounded half to even, like IEEE 754 `decimal128`.
+    fn default() -> Self {
+        Self {
+            digits: 34,
+            rounding: RoundingMode::HalfEven,
+        }
+    }
+}
+
+impl Precision {
+    /// `digits` significant digits, at least one.
+    pub fn new(digits: u32, rounding: RoundingMode) -> Self {
+        Self {
+            digits: digits.max(1),
+            rounding,
+        }
+    }
+
+    /// A precision with `extra` more digits rounded half to even, for
+    /// intermediate results that are rounded again at the end.
+    pub(crate) fn working(self, extra: u32) -> Self {
+        Self::new(self.digits.saturating_add(extra), RoundingMode::HalfEven)
+    }
+}
+
+/// A decimal number `mantissa * 10^exponent` with any number of digits.
+///
+/// Adding, subtracting and multiplying with the operators is exact. Everything
+/// else takes a [`Precision`] to round its result to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    /// Never ends in a zero digit, so every value has one representation.
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+fn pow10(n: u64) -> BigInt {
+    Pow::pow(BigInt::from(10), n)
+}
+
+/// Number of decimal digits in `n`, ignoring its sign.
+fn digit_count(n: &BigInt) -> i64 {
+    n.magnitude().to_string().len() as i64
+}
+
+impl Decimal {
+    pub(crate) fn new(mut mantissa: BigInt, mut exponent: i64) -> Self {
+        if mantissa.is_zero() {
+            return Self::zero();
+        }
+        let ten = BigInt::from(10);
+        loop {
+            let (quotient, remainder) = mantissa.div_rem(&ten);
+            if !remainder.is_zero() {
+                break;
+            }
+            mantissa = quotient;
+            exponent += 1;
+        }
+        Self { mantissa, exponent }
+    }
+
+    pub fn zero() -> Self {
+        Self {
+            mantissa: BigInt::zero(),
+            exponent: 0,
+        }
+    }
+
+    pub fn from_integer(n: i64) -> Self {
+        Self::new(n.into(), 0)
+    }
+
+    /// `rational` rounded to `precision`.
+    pub fn from_rational(rational: &Rational, precision: Precision) -> Self {
+        Self::from_ratio(rational.numerator(), rational.denominator(), precision)
+    }
+
+    /// `numerator/denominator` rounded to `precision`. The denominator must
+    /// not be zero.
+    fn from_ratio(numerator: &BigInt, denominator: &BigInt, precision: Precision) -> Self {
+        if numerator.is_zero() {
+            return Self::zero();
+        }
+        let (numerator, denominator) = if denominator.is_negative() {
+            (-numerator, -denominator)
+        } else {
+            (numerator.clone(), denominator.clone())
+        };
+
+        // Scale so the quotient has at least one digit more than needed
+        let magnitude = digit_count(&numerator) - digit_count(&denominator);
+        let exponent = magnitude - precision.digits as i64 - 2;
+        let (numerator, denominator) = if exponent < 0 {
+            (numerator * pow10(exponent.unsigned_abs()), denominator)
+        } else {
+            (numerator, denominator * pow10(exponent as u64))
+        };
+        let (quotient, remainder) = numerator.div_rem(&denominator);
+
+        // A trailing sticky digit keeps an inexact quotient from looking like
+        // an exact tie when rounding
+        let sticky = if remainder.is_zero() {
+            BigInt::zero()
+        } else {
+            numerator.signum()
+        };
+        Self::new(quotient * 10 + sticky, exponent - 1).round(precision)
+    }
+
+    /// The exact value as a fraction.
+    pub fn to_rational(&self) -> Rational {
+        let value = if self.exponent < 0 {
+            num_rational::BigRational::new(
+                self.mantissa.clone(),
+                pow10(self.exponent.unsigned_abs()),
+            )
+        } else {
+            num_rational::BigRational::from_integer(&self.mantissa * pow10(self.exponent as u64))
+        };
+        Rational(value)
+    }
+
+    /// The nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// The value if it is an integer.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        (self.exponent >= 0).then(|| &self.mantissa * pow10(self.exponent as u64))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            mantissa: self.mantissa.abs(),
+            exponent: self.exponent,
+        }
+    }
+
+    /// The exponent of the leading digit, `floor(log10(|self|))`, and zero for
+    /// zero.
+    pub fn magnitude(&self) -> i64 {
+        if self.is_zero() {
+            return 0;
+        }
+        self.exponent + digit_count(&self.mantissa) - 1
+    }
+
+    /// Whether the leading digit is within [`MAX_MAGNITUDE`] places of the
+    /// decimal point, as every result should be.
+    pub fn is_in_range(&self) -> bool {
+        self.magnitude().abs() <= MAX_MAGNITUDE
+    }
+
+    /// `self * 10^n`.
+    pub(crate) fn shift(&self, n: i64) -> Self {
+        if self.is_zero() {
+            return Self::zero();
+        }
+        Self {
+            mantissa: self.mantissa.clone(),
+            exponent: self.exponent + n,
+        }
+    }
+
+    /// Rounds to the number of significant digits of `precision`.
+    pub fn round(&self, precision: Precision) -> Self {
+        if self.is_zero() {
+            return Self::zero();
+        }
+        let exponent = self.magnitude() - precision.digits as i64 + 1;
+        self.round_at(exponent, precision.rounding)
+    }
+
+    /// Rounds to a multiple of `10^exponent`, e.g. to an integer when the
+    /// exponent is zero.
+    pub fn round_at(&self, exponent: i64, rounding: RoundingMode) -> Self {
+        if self.exponent >= exponent {
+            return self.clone();
+        }
+
+        let shift = (exponent - self.exponent) as u64;
+        let (quotient, half) = if shift > digit_count(&self.mantissa) as u64 {
+            // Everything is cut off, and it is less than half a unit
+            (BigInt::zero(), Ordering::Less)
+        } else {
+            let divisor = pow10(shift);
+            let (quotient, remainder) = self.mantissa.div_rem(&divisor);
+            (
+                quotient,
+                (remainder.magnitude() * 2u8).cmp(divisor.magnitude()),
+            )
+        };
+
+        // The mantissa never ends in zero, so something nonzero was cut off
+        let negative = self.is_negative();
+        let quotient = if rounding.rounds_away(&quotient, negative, half) {
+            if negative {
+                quotient - 1
+            } else {
+                quotient + 1
+            }
+        } else {
+            quotient
+        };
+        Self::new(quotient, exponent)
+    }
+
+    /// `self/other` rounded to `precision`, `None` when dividing by zero.
+    pub fn checked_div(&self, other: &Self, precision: Precision) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        let quotient = Self::from_ratio(&self.mantissa, &other.mantissa, precision);
+        Some(quotient.shift(self.exponent - other.exponent))
+    }
+
+    /// The remainder of dividing by `other`, truncating the quotient towards
+    /// zero, `None` when dividing by zero.
+    pub fn checked_rem(&self, other: &Self, precision: Precision) -> Option<Self> {
+        let remainder = self.to_rational().checked_rem(&other.to_rational())?;
+        Some(Self::from_rational(&remainder, precision))
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(n: i64) -> Self {
+        Self::from_integer(n)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self - other)
+            .mantissa
+            .sign()
+            .cmp(&num_bigint::Sign::NoSign)
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Writes the number positionally, or in scientific notation like `1.5e-12`
+    /// when that would need many zeros around its digits.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let digits = self.mantissa.magnitude().to_string();
+        let magnitude = self.magnitude();
+
+        if self.exponent > 20 || magnitude < -7 {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            return write!(f, "{sign}{first}{point}{rest}e{magnitude}");
+        }
+
+        if self.exponent >= 0 {
+            let zeros = "0".repeat(self.exponent as usize);
+            return write!(f, "{sign}{digits}{zeros}");
+        }
+        let places = self.exponent.unsigned_abs() as usize;
+        let digits = format!("{digits:0>width$}", width = places + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - places);
+        write!(f, "{sign}{whole}.{fraction}")
+    }
+}
+
+/// Both mantissas scaled to the smaller exponent, and that exponent.
+fn align(a: &Decimal, b: &Decimal) -> (BigInt, BigInt, i64) {
+    let exponent = a.exponent.min(b.exponent);
+    let scale = |d: &Decimal| &d.mantissa * pow10((d.exponent - exponent) as u64);
+    (scale(a), scale(b), exponent)
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal {
+            mantissa: -self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        -self.clone()
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        if self.is_zero() {
+            return other.clone();
+        }
+        if other.is_zero() {
+            return self.clone();
+        }
+        let (a, b, exponent) = align(self, other);
+        Decimal::new(a + b, exponent)
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        self + &-other
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal::new(
+            &self.mantissa * &other.mantissa,
+            self.exponent + other.exponent,
+        )
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A Cayley–Dickson number with `N` real components, `N` being a power of
+/// two. Component 0 is the real part and component `n` the coefficient of the
+/// basis unit `e{n}`.
+///
+/// Each doubling gives up a property: quaternions don't commute, octonions
+/// don't associate and sedenions have zero divisors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hypercomplex<const N: usize> {
+    components: [f64; N],
+}
+
+pub type Quaternion = Hypercomplex<4>;
+pub type Octonion = Hypercomplex<8>;
+pub type Sedenion = Hypercomplex<16>;
+
+/// Conjugates `a` in place, negating every imaginary component.
+fn conjugate(a: &mut [f64]) {
+    a.iter_mut().skip(1).for_each(|x| *x = -*x);
+}
+
+/// The Cayley–Dickson product of `a` and `b`, both of length `n`:
+/// `(a, b)(c, d) = (ac - d*b, da + bc*)`.
+fn multiply(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let n = a.len();
+    if n == 1 {
+        return vec![a[0] * b[0]];
+    }
+
+    let (a1, a2) = a.split_at(n / 2);
+    let (b1, b2) = b.split_at(n / 2);
+    let (mut b1_conj, mut b2_conj) = (b1.to_vec(), b2.to_vec());
+    conjugate(&mut b1_conj);
+    conjugate(&mut b2_conj);
+
+    let lhs = multiply(a1, b1);
+    let rhs = multiply(&b2_conj, a2);
+    let mut result: Vec<f64> = lhs.iter().zip(&rhs).map(|(x, y)| x - y).collect();
+    let lhs = multiply(b2, a1);
+    let rhs = multiply(a2, &b1_conj);
+    result.extend(lhs.iter().zip(&rhs).map(|(x, y)| x + y));
+    result
+}
+
+impl<const N: usize> Hypercomplex<N> {
+    pub fn new(components: [f64; N]) -> Self {
+        assert!(
+            N.is_power_of_two() && N <= 16,
+            "Cayley–Dickson algebras up to the sedenions have 1, 2, 4, 8 or 16 components"
+        );
+        Self { components }
+    }
+
+    pub fn from_real(x: f64) -> Self {
+        let mut components = [0.0; N];
+        components[0] = x;
+        Self::new(components)
+    }
+
+    /// The basis unit `e{index}`, if the algebra has one.
+    pub fn unit(index: usize) -> Option<Self> {
+        let mut components = [0.0; N];
+        *components.get_mut(index)? = 1.0;
+        Some(Self::new(components))
+    }
+
+    pub fn components(&self) -> &[f64; N] {
+        &self.components
+    }
+
+    /// The real part.
+    pub fn re(&self) -> f64 {
+        self.components[0]
+    }
+
+    /// The real value, if every imaginary component is zero.
+    pub fn real(&self) -> Option<f64> {
+        self.components[1..]
+            .iter()
+            .all(|&x| x == 0.0)
+            .then_some(self.components[0])
+    }
+
+    pub fn conj(&self) -> Self {
+        let mut components = self.components;
+        conjugate(&mut components);
+        Self::new(components)
+    }
+
+    /// The square of the Euclidean norm, the real part of `z z*`.
+    pub fn norm_sqr(&self) -> f64 {
+        self.components.iter().map(|x| x * x).sum()
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.norm_sqr().sqrt()
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new(self.components.map(|x| x * factor))
+    }
+
+    /// The multiplicative inverse `z* / |z|^2`, which is two-sided even where
+    /// multiplication doesn't associate. Zero has none.
+    pub fn inv(&self) -> Option<Self> {
+        let norm_sqr = self.norm_sqr();
+        (norm_sqr != 0.0).then(|| self.conj().scale(1.0 / norm_sqr))
+    }
+
+    /// `z^n` by repeated squaring. Every Cayley–Dickson algebra is power
+    /// associative, so the order of the products doesn't matter. Negative
+    /// powers of zero are undefined.
+    pub fn powi(&self, n: i32) -> Option<Self> {
+        let mut base = if n < 0 { self.inv()? } else { *self };
+        let mut result = Self::from_real(1.0);
+        let mut exponent = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent /= 2;
+        }
+        Some(result)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.components.iter().all(|x| x.is_finite())
+    }
+
+    /// Applies `f` to every component.
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(self.components.map(f))
+    }
+}
+
+impl<const N: usize> Add for Hypercomplex<N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let mut components = self.components;
+        components
+            .iter_mut()
+            .zip(other.components)
+            .for_each(|(x, y)| *x += y);
+        Self::new(components)
+    }
+}
+
+impl<const N: usize> Sub for Hypercomplex<N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<const N: usize> Neg for Hypercomplex<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(self.components.map(|x| -x))
+    }
+}
+
+/// The Cayley–Dickson product, which doesn't commute from the quaternions on:
+/// `i*j` is `k` but `j*i` is `-k`.
+impl<const N: usize> Mul for Hypercomplex<N> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let product = multiply(&self.components, &other.components);
+        Self::new(product.try_into().expect("products keep the dimension"))
+    }
+}
+
+/// Writes the nonzero components as `a + bi + cj + dk + fe4 + ...`.
+impl<const N: usize> fmt::Display for Hypercomplex<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 16] = [
+            "", "i", "j", "k", "e4", "e5", "e6", "e7", "e8", "e9", "e10", "e11", "e12", "e13",
+            "e14", "e15",
+        ];
+
+        let mut terms = self
+            .components
+            .iter()
+            .enumerate()
+            // Adding zero turns negative zeros into positive ones
+            .map(|(index, x)| (index, x + 0.0))
+            .filter(|(_, x)| *x != 0.0);
+        let Some((index, x)) = terms.next() else {
+            return write!(f, "0");
+        };
+
+        let term = |index: usize, x: f64| match (index, x) {
+            (0, x) => format!("{x}"),
+            (index, 1.0) => UNITS[index].to_string(),
+            (index, x) => format!("{x}{}", UNITS[index]),
+        };
+        match (index, x) {
+            (index, -1.0) if index != 0 => write!(f, "-{}", UNITS[index])?,
+            (index, x) => write!(f, "{}", term(index, x))?,
+        }
+        for (index, x) in terms {
+            if x < 0.0 {
+                write!(f, " - {}", term(index, -x))?;
+            } else {
+                write!(f, " + {}", term(index, x))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::f64::consts::PI;
+
+/// Returns `x` as an integer if it has no fractional part and fits in an `i64`.
+pub fn as_integer(x: f64) -> Option<i64> {
+    if x.fract() == 0.0 && x.abs() <= i64::MAX as f64 {
+        Some(x as i64)
+    } else {
+        None
+    }
+}
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The least common multiple of `a` and `b`, or `None` if it doesn't fit in
+/// a `u64`.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        Some(0)
+    } else {
+        (a / gcd(a, b)).checked_mul(b)
+    }
+}
+
+/// Largest `n` whose factorial fits in an `f64`.
+const MAX_FACTORIAL: u64 = 170;
+
+/// Number of factors up to which `binomial` multiplies them out, rather than
+/// going through the logarithm of the gamma function.
+const MAX_BINOMIAL_PRODUCT: u64 = 100;
+
+/// `n!`, or `None` once it no longer fits in an `f64`.
+pub fn factorial(n: u64) -> Option<f64> {
+    (n <= MAX_FACTORIAL).then(|| (2..=n).fold(1.0, |acc, i| acc * i as f64))
+}
+
+/// The number of ways to choose `k` items out of `n`, or `None` once it no
+/// longer fits in an `f64`.
+pub fn binomial(n: u64, k: u64) -> Option<f64> {
+    if k > n {
+        return Some(0.0);
+    }
+
+    // C(n, k) = C(n, n - k), iterate over the smaller of the two.
+    let k = k.min(n - k);
+    let result = if k <= MAX_BINOMIAL_PRODUCT {
+        (1..=k).fold(1.0, |acc, i| acc * (n - k + i) as f64 / i as f64)
+    } else {
+        ln_binomial(n, k).exp().round()
+    };
+    result.is_finite().then_some(result)
+}
+
+/// `ln(C(n, k))`, which stays finite long after `C(n, k)` overflows.
+pub(super) fn ln_binomial(n: u64, k: u64) -> f64 {
+    ln_gamma(n as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((n - k) as f64 + 1.0)
+}
+
+/// `ln(Γ(x))` for `x > 0`, by the Lanczos approximation with `g = 7`, which is
+/// accurate to about 15 significant digits.
+pub(super) fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection, Γ(x)Γ(1 - x) = π/sin(πx)
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    let t = x + G + 0.5;
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
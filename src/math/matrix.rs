@@ -0,0 +1,306 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Entries this much smaller than the largest one count as zero during
+/// elimination, so rounding errors don't hide a singular matrix.
+const EPSILON: f64 = 1e-12;
+
+/// Every `(row, col)` position of a `rows`×`cols` matrix, row by row.
+fn indices(rows: usize, cols: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+}
+
+/// A dense matrix of `f64`, stored row by row. Vectors are matrices with a
+/// single row or column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    entries: Vec<f64>,
+}
+
+impl Matrix {
+    /// A `rows`×`cols` matrix from its entries, row by row.
+    pub fn new(rows: usize, cols: usize, entries: Vec<f64>) -> Self {
+        assert_eq!(
+            rows * cols,
+            entries.len(),
+            "matrix entries don't fit its shape"
+        );
+        Self {
+            rows,
+            cols,
+            entries,
+        }
+    }
+
+    /// A matrix from its rows, if they all have the same length.
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Option<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        Some(Self::new(rows.len(), cols, rows.concat()))
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut identity = Self::new(n, n, vec![0.0; n * n]);
+        (0..n).for_each(|i| identity[(i, i)] = 1.0);
+        identity
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn entries(&self) -> &[f64] {
+        &self.entries
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn is_vector(&self) -> bool {
+        self.rows == 1 || self.cols == 1
+    }
+
+    /// Applies `f` to every entry.
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(
+            self.rows,
+            self.cols,
+            self.entries.iter().map(|&x| f(x)).collect(),
+        )
+    }
+
+    /// Combines the entries of two matrices of the same shape with `f`.
+    pub fn zip_with(&self, other: &Matrix, f: impl Fn(f64, f64) -> f64) -> Option<Self> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return None;
+        }
+        let entries = self.entries.iter().zip(&other.entries);
+        Some(Self::new(
+            self.rows,
+            self.cols,
+            entries.map(|(&a, &b)| f(a, b)).collect(),
+        ))
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transpose = Self::new(self.cols, self.rows, vec![0.0; self.entries.len()]);
+        for (row, col) in indices(self.rows, self.cols) {
+            transpose[(col, row)] = self[(row, col)];
+        }
+        transpose
+    }
+
+    /// The matrix product, if the columns of `self` match the rows of `other`.
+    pub fn mul(&self, other: &Matrix) -> Option<Self> {
+        if self.cols != other.rows {
+            return None;
+        }
+        let mut product = Self::new(self.rows, other.cols, vec![0.0; self.rows * other.cols]);
+        for (row, col) in indices(self.rows, other.cols) {
+            product[(row, col)] = (0..self.cols)
+                .map(|k| self[(row, k)] * other[(k, col)])
+                .sum();
+        }
+        Some(product)
+    }
+
+    /// `self^n` for square matrices, by repeated squaring. Negative powers
+    /// need an invertible matrix.
+    pub fn powi(&self, n: i32) -> Option<Self> {
+        if !self.is_square() {
+            return None;
+        }
+        let mut base = if n < 0 { self.inv()? } else { self.clone() };
+        let mut result = Self::identity(self.rows);
+        let mut exponent = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.mul(&base)?;
+            }
+            base = base.mul(&base)?;
+            exponent /= 2;
+        }
+        Some(result)
+    }
+
+    /// The sum of the diagonal of a square matrix.
+    pub fn trace(&self) -> Option<f64> {
+        self.is_square()
+            .then(|| (0..self.rows).map(|i| self[(i, i)]).sum())
+    }
+
+    /// The determinant of a square matrix.
+    pub fn det(&self) -> Option<f64> {
+        if !self.is_square() {
+            return None;
+        }
+        let (pivots, det) = self.clone().reduce(self.cols);
+        Some(if pivots.len() < self.rows { 0.0 } else { det })
+    }
+
+    /// The inverse of a square matrix, if it isn't singular.
+    pub fn inv(&self) -> Option<Self> {
+        self.solve(&Self::identity(self.rows))
+    }
+
+    /// The number of linearly independent rows.
+    pub fn rank(&self) -> usize {
+        self.clone().reduce(self.cols).0.len()
+    }
+
+    /// The `x` with `self x = b`, for a square non-singular `self` and a `b`
+    /// with as many rows.
+    pub fn solve(&self, b: &Matrix) -> Option<Self> {
+        if !self.is_square() || self.rows != b.rows {
+            return None;
+        }
+
+        // Eliminating on [A | b] leaves [I | x]
+        let cols = self.cols + b.cols;
+        let mut augmented = Self::new(self.rows, cols, vec![0.0; self.rows * cols]);
+        for (row, col) in indices(self.rows, cols) {
+            augmented[(row, col)] = match col.checked_sub(self.cols) {
+                None => self[(row, col)],
+                Some(col) => b[(row, col)],
+            };
+        }
+        let (pivots, _) = augmented.reduce(self.cols);
+        if pivots.len() < self.rows {
+            return None;
+        }
+
+        let mut x = Self::new(b.rows, b.cols, vec![0.0; b.entries.len()]);
+        for (row, col) in indices(b.rows, b.cols) {
+            x[(row, col)] = augmented[(row, self.cols + col)];
+        }
+        Some(x)
+    }
+
+    /// The Euclidean norm of a vector, or the Frobenius norm of a matrix.
+    pub fn norm(&self) -> f64 {
+        self.entries.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+
+    /// The dot product of two vectors of the same length, whether they are
+    /// rows or columns.
+    pub fn dot(&self, other: &Matrix) -> Option<f64> {
+        if !self.is_vector() || !other.is_vector() || self.entries.len() != other.entries.len() {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .zip(&other.entries)
+                .map(|(a, b)| a * b)
+                .sum(),
+        )
+    }
+
+    /// The cross product of two vectors of length 3, shaped like `self`.
+    pub fn cross(&self, other: &Matrix) -> Option<Self> {
+        let ([a1, a2, a3], [b1, b2, b3]) = (
+            <[f64; 3]>::try_from(self.entries.as_slice()).ok()?,
+            <[f64; 3]>::try_from(other.entries.as_slice()).ok()?,
+        );
+        if !self.is_vector() || !other.is_vector() {
+            return None;
+        }
+        let entries = vec![a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1];
+        Some(Self::new(self.rows, self.cols, entries))
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.entries.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+
+    /// Gauss–Jordan elimination with partial pivoting, looking for pivots in
+    /// the first `columns` columns only. Leaves the matrix in reduced row
+    /// echelon form and returns the pivot columns along with the product of
+    /// the pivots, which is the determinant when every column has one.
+    fn reduce(&mut self, columns: usize) -> (Vec<usize>, f64) {
+        let largest = indices(self.rows, self.cols)
+            .filter(|&(_, col)| col < columns)
+            .map(|index| self[index].abs())
+            .fold(0.0, f64::max);
+        let tolerance = largest * EPSILON;
+
+        let mut pivots = Vec::new();
+        let mut det = 1.0;
+        for col in 0..columns {
+            let row = pivots.len();
+            if row == self.rows {
+                break;
+            }
+            let Some(pivot_row) = (row..self.rows)
+                .max_by(|&a, &b| self[(a, col)].abs().total_cmp(&self[(b, col)].abs()))
+                .filter(|&pivot_row| self[(pivot_row, col)].abs() > tolerance)
+            else {
+                continue;
+            };
+            if pivot_row != row {
+                self.swap_rows(row, pivot_row);
+                det = -det;
+            }
+
+            let pivot = self[(row, col)];
+            det *= pivot;
+            for c in 0..self.cols {
+                self[(row, c)] /= pivot;
+            }
+            for other in (0..self.rows).filter(|&other| other != row) {
+                let factor = self[(other, col)];
+                if factor != 0.0 {
+                    for c in 0..self.cols {
+                        self[(other, c)] -= factor * self[(row, c)];
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        (pivots, det)
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        &self.entries[row * self.cols + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        &mut self.entries[row * self.cols + col]
+    }
+}
+
+/// Writes the matrix like its literal, `[1, 2; 3, 4]`.
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self
+            .entries
+            .chunks(self.cols.max(1))
+            .map(|row| {
+                row.iter()
+                    // Adding zero turns negative zeros into positive ones
+                    .map(|x| (x + 0.0).to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            })
+            .collect::<Vec<String>>()
+            .join("; ");
+        write!(f, "[{rows}]")
+    }
+}
@@ -1,5 +1,25 @@
-mod round;
 mod angle;
+mod complex;
+mod decimal;
+mod hypercomplex;
+mod integer;
+mod matrix;
+mod rational;
+mod round;
+mod statistics;
+mod units;
 
+pub use angle::AngleMode;
+pub use complex::{format_complex, ComplexFormat};
+pub(crate) use decimal::GUARD_DIGITS;
+pub use decimal::{Decimal, Precision, RoundingMode};
+pub use hypercomplex::{Hypercomplex, Octonion, Quaternion, Sedenion};
+pub use integer::{as_integer, binomial, factorial, gcd, lcm};
+pub use matrix::Matrix;
+pub use rational::{ParseRationalError, Rational};
 pub use round::round;
-pub use angle::deg_to_rad;
+pub use statistics::{
+    binomial_pmf, erf, erfc, mean, median, mode, normal_cdf, normal_inverse_cdf, normal_pdf,
+    poisson_cdf, quantile, variance,
+};
+pub use units::{Dimension, Quantity, Unit};
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// An exact fraction with arbitrary precision numerator and denominator.
+///
+/// Converting to [`f64`] is always explicit, through [`Rational::to_f64`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rational(pub(crate) BigRational);
+
+impl Rational {
+    /// `numerator/denominator`, `None` if the denominator is zero.
+    pub fn new(numerator: i64, denominator: i64) -> Option<Self> {
+        (denominator != 0).then(|| {
+            Self(BigRational::new(
+                BigInt::from(numerator),
+                BigInt::from(denominator),
+            ))
+        })
+    }
+
+    pub fn from_integer(n: i64) -> Self {
+        Self(BigRational::from_integer(n.into()))
+    }
+
+    /// The exact value of `x` as it is written in decimal, so `0.1` becomes
+    /// `1/10` rather than the nearest binary fraction. `None` for infinities
+    /// and NaN.
+    pub fn from_f64(x: f64) -> Option<Self> {
+        if !x.is_finite() {
+            return None;
+        }
+        // `f64` prints the shortest decimal that reads back as the same value
+        let value: Self = x.abs().to_string().parse().ok()?;
+        Some(if x < 0.0 { -value } else { value })
+    }
+
+    /// The nearest `f64`, or an infinity if the value is out of range.
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// The value as an `f64` if it converts without rounding.
+    pub fn to_exact_f64(&self) -> Option<f64> {
+        let value = self.to_f64();
+        (Self::from_f64(value).as_ref() == Some(self)).then_some(value)
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    /// The value if it is an integer that fits in an `i32`.
+    pub fn to_i32(&self) -> Option<i32> {
+        self.0
+            .is_integer()
+            .then(|| self.numerator().to_i32())
+            .flatten()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.0.is_integer()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    pub fn abs(&self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// `self/other`, `None` when dividing by zero.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        (!other.is_zero()).then(|| Self(&self.0 / &other.0))
+    }
+
+    /// The remainder of dividing by `other`, truncating the quotient towards
+    /// zero, `None` when dividing by zero.
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        let quotient = self.checked_div(other)?.0.trunc();
+        Some(Self(&self.0 - &other.0 * quotient))
+    }
+
+    /// `self^exponent`, `None` for negative powers of zero.
+    pub fn checked_pow(&self, exponent: i32) -> Option<Self> {
+        (exponent >= 0 || !self.is_zero()).then(|| Self(self.0.pow(exponent)))
+    }
+
+    /// Number of bits in the larger of the numerator and denominator.
+    pub fn bits(&self) -> u64 {
+        self.numerator().bits().max(self.denominator().bits())
+    }
+
+    /// The digits after the decimal point needed to write the value exactly,
+    /// `None` if its decimal expansion doesn't terminate.
+    fn decimal_places(&self) -> Option<u32> {
+        let mut denominator = self.denominator().clone();
+        let (two, five) = (BigInt::from(2), BigInt::from(5));
+        let (mut twos, mut fives) = (0, 0);
+        while denominator.is_even() {
+            denominator /= &two;
+            twos += 1;
+        }
+        while (&denominator % &five).is_zero() {
+            denominator /= &five;
+            fives += 1;
+        }
+        denominator.is_one().then_some(u32::max(twos, fives))
+    }
+}
+
+impl fmt::Display for Rational {
+    /// Writes terminating decimals like `3.25` and other fractions like `1/3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(places) = self.decimal_places() else {
+            return write!(f, "{}/{}", self.numerator(), self.denominator());
+        };
+
+        let scaled =
+            (&self.0 * BigRational::from_integer(BigInt::from(10).pow(places))).to_integer();
+        let sign = if scaled.is_negative() { "-" } else { "" };
+        let digits = scaled.abs().to_string();
+        if places == 0 {
+            return write!(f, "{sign}{digits}");
+        }
+
+        let digits = format!("{digits:0>width$}", width = places as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - places as usize);
+        write!(f, "{sign}{whole}.{fraction}")
+    }
+}
+
+/// Error returned when a string isn't a decimal number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRationalError;
+
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    /// Reads decimals like `12`, `3.25` or `-0.5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) {
+            return Err(ParseRationalError);
+        }
+
+        let numerator: BigInt = format!("{whole}{fraction}")
+            .parse()
+            .map_err(|_| ParseRationalError)?;
+        let denominator = BigInt::from(10).pow(fraction.len() as u32);
+        let value = Self(BigRational::new(numerator, denominator));
+        Ok(if negative { -value } else { value })
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational(-self.0)
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational(&self.0 + &other.0)
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        Rational(&self.0 - &other.0)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational(&self.0 * &other.0)
+    }
+}
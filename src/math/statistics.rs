@@ -0,0 +1,247 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use super::integer::{ln_binomial, ln_gamma};
+
+/// The arithmetic mean of a non-empty sample.
+pub fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// The middle value of a non-empty sample, or the mean of the two middle
+/// values when its length is even.
+pub fn median(xs: &[f64]) -> f64 {
+    quantile(xs, 0.5)
+}
+
+/// The most frequent value of a non-empty sample. Ties go to the smallest
+/// value.
+pub fn mode(xs: &[f64]) -> f64 {
+    let sorted = sorted(xs);
+    let mut best = (sorted[0], 0);
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > best.1 {
+            best = (run[0], run.len());
+        }
+    }
+    best.0
+}
+
+/// The variance of a sample, dividing by `n - 1`, or of a whole population,
+/// dividing by `n`. A sample needs at least two values.
+pub fn variance(xs: &[f64], sample: bool) -> f64 {
+    let mean = mean(xs);
+    let squares: f64 = xs.iter().map(|x| (x - mean) * (x - mean)).sum();
+    squares / (xs.len() - usize::from(sample)) as f64
+}
+
+/// The `q`-quantile of a non-empty sample for `q` in `[0, 1]`, interpolating
+/// linearly between the closest ranks.
+pub fn quantile(xs: &[f64], q: f64) -> f64 {
+    let sorted = sorted(xs);
+    let rank = q * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[below] + (rank - below as f64) * (sorted[above] - sorted[below])
+}
+
+/// Evaluates the polynomial with `coefficients`, highest degree first, at `x`.
+fn horner(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+fn sorted(xs: &[f64]) -> Vec<f64> {
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// The error function. A series with only positive terms is used for small
+/// arguments and a continued fraction of `erfc` in the tails, both accurate to
+/// about machine precision.
+pub fn erf(x: f64) -> f64 {
+    if x.abs() >= 3.0 {
+        return x.signum() * (1.0 - erfc_tail(x.abs()));
+    }
+    // erf(x) = 2/√π e^(-x²) Σ 2^n x^(2n+1) / (1·3·5···(2n+1))
+    let mut term = x;
+    let mut sum = x;
+    for n in 1..200 {
+        term *= 2.0 * x * x / (2 * n + 1) as f64;
+        sum += term;
+        if term.abs() <= sum.abs() * f64::EPSILON {
+            break;
+        }
+    }
+    2.0 / PI.sqrt() * (-x * x).exp() * sum
+}
+
+/// The complementary error function `1 - erf(x)`, without losing precision in
+/// the upper tail.
+pub fn erfc(x: f64) -> f64 {
+    if x >= 3.0 {
+        erfc_tail(x)
+    } else {
+        1.0 - erf(x)
+    }
+}
+
+/// `erfc(x)` for `x >= 3`, from its continued fraction
+/// `e^(-x²)/√π / (x + (1/2)/(x + 1/(x + (3/2)/(x + ...))))`.
+fn erfc_tail(x: f64) -> f64 {
+    let fraction = (1..=60).rev().fold(x, |tail, n| x + n as f64 / 2.0 / tail);
+    (-x * x).exp() / PI.sqrt() / fraction
+}
+
+/// The density of the normal distribution with mean `mu` and standard
+/// deviation `sigma` at `x`.
+pub fn normal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (x - mu) / sigma;
+    (-z * z / 2.0).exp() / (sigma * (2.0 * PI).sqrt())
+}
+
+/// The probability that a normally distributed value is at most `x`.
+pub fn normal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    erfc(-(x - mu) / (sigma * SQRT_2)) / 2.0
+}
+
+/// The `x` with `normal_cdf(x, mu, sigma) = p`, for `p` strictly between 0
+/// and 1. Acklam's rational approximation is refined with a Halley step.
+pub fn normal_inverse_cdf(p: f64, mu: f64, sigma: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        let r = (-2.0 * q.ln()).sqrt();
+        horner(&C, r) / (horner(&D, r) * r + 1.0)
+    };
+    let z = if p < LOW {
+        tail(p)
+    } else if p > 1.0 - LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        horner(&A, r) * q / (horner(&B, r) * r + 1.0)
+    };
+
+    let error = normal_cdf(z, 0.0, 1.0) - p;
+    let u = error * (2.0 * PI).sqrt() * (z * z / 2.0).exp();
+    let z = z - u / (1.0 + z * u / 2.0);
+    mu + sigma * z
+}
+
+/// The probability of exactly `k` successes in `n` trials that each succeed
+/// with probability `p`. It is computed in log space, since the binomial
+/// coefficient and the powers of `p` overflow and underflow long before
+/// their product does.
+pub fn binomial_pmf(n: u64, p: f64, k: u64) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    if p == 0.0 || p == 1.0 {
+        let certain = if p == 0.0 { 0 } else { n };
+        return if k == certain { 1.0 } else { 0.0 };
+    }
+    (ln_binomial(n, k) + k as f64 * p.ln() + (n - k) as f64 * (-p).ln_1p()).exp()
+}
+
+/// The probability of at most `k` events of a Poisson process with mean
+/// `lambda`, which is the regularized upper incomplete gamma function
+/// `Q(k + 1, lambda)`.
+pub fn poisson_cdf(lambda: f64, k: u64) -> f64 {
+    if lambda == 0.0 {
+        return 1.0;
+    }
+    upper_gamma(k as f64 + 1.0, lambda)
+}
+
+/// `ln(x^a e^(-x) / Γ(a))`. For large `a` the terms are each far larger than
+/// their sum, so Stirling's series is used to cancel them exactly, leaving
+/// `a ln(1 + (x - a)/a) - (x - a)` and small corrections.
+fn ln_gamma_factor(a: f64, x: f64) -> f64 {
+    if a < 100.0 {
+        return a * x.ln() - x - ln_gamma(a);
+    }
+    let d = x - a;
+    let stirling = 1.0 / (12.0 * a) - 1.0 / (360.0 * a.powi(3)) + 1.0 / (1260.0 * a.powi(5));
+    a * (d / a).ln_1p() - d + 0.5 * (a / (2.0 * PI)).ln() - stirling
+}
+
+/// The regularized upper incomplete gamma function `Q(a, x)` for `a > 0` and
+/// `x > 0`. Below `x = a + 1` it is `1 - P(a, x)` from the series of the
+/// lower function, above that its continued fraction converges quickly.
+/// Either way the terms only need about `√x` iterations and the common
+/// factor `x^a e^(-x) / Γ(a)` is taken in log space, so large means neither
+/// underflow nor take long.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: u32 = 100_000_000;
+    const TINY: f64 = 1e-300;
+
+    let factor = ln_gamma_factor(a, x).exp();
+    if x < a + 1.0 {
+        // P(a, x) = x^a e^(-x) / Γ(a) Σ x^n / (a (a + 1) ··· (a + n))
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITERATIONS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term <= sum * f64::EPSILON {
+                break;
+            }
+        }
+        return (1.0 - factor * sum).clamp(0.0, 1.0);
+    }
+
+    // Q(a, x) = x^a e^(-x) / Γ(a) / (x + 1 - a - 1 (1 - a) / (x + 3 - a - ...)),
+    // evaluated with the modified Lentz method
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut fraction = d;
+    for i in 1..MAX_ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        fraction *= delta;
+        if (delta - 1.0).abs() <= f64::EPSILON {
+            break;
+        }
+    }
+    (factor * fraction).clamp(0.0, 1.0)
+}
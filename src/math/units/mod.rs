@@ -0,0 +1,275 @@
+mod table;
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+/// Symbols of the SI base units, in the order of [`Dimension`]'s exponents.
+const BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+/// Exponents of the seven SI base dimensions: length, mass, time, electric
+/// current, temperature, amount of substance and luminous intensity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Dimension([i32; 7]);
+
+impl Dimension {
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.iter().all(|&exponent| exponent == 0)
+    }
+
+    fn mul(self, other: Dimension) -> Dimension {
+        let mut exponents = self.0;
+        exponents.iter_mut().zip(other.0).for_each(|(a, b)| *a += b);
+        Dimension(exponents)
+    }
+
+    fn powi(self, n: i32) -> Dimension {
+        Dimension(self.0.map(|exponent| exponent * n))
+    }
+}
+
+/// Writes `name^exponent` factors as `kg*m/s^2`, or with negative exponents
+/// like `s^-1` when nothing is left above the fraction bar.
+fn write_factors<'a>(
+    f: &mut fmt::Formatter,
+    factors: impl Iterator<Item = (&'a str, i32)> + Clone,
+) -> fmt::Result {
+    let power = |name: &str, exponent: i32| match exponent {
+        1 => name.to_string(),
+        exponent => format!("{name}^{exponent}"),
+    };
+    let numerator: Vec<String> = factors
+        .clone()
+        .filter(|(_, exponent)| *exponent > 0)
+        .map(|(name, exponent)| power(name, exponent))
+        .collect();
+    let denominator = factors.filter(|(_, exponent)| *exponent < 0);
+
+    if numerator.is_empty() {
+        let factors: Vec<String> = denominator
+            .map(|(name, exponent)| power(name, exponent))
+            .collect();
+        return write!(f, "{}", factors.join("*"));
+    }
+    write!(f, "{}", numerator.join("*"))?;
+    for (name, exponent) in denominator {
+        write!(f, "/{}", power(name, -exponent))?;
+    }
+    Ok(())
+}
+
+/// Writes the dimension in SI base units, like `kg*m/s^2`.
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_dimensionless() {
+            return write!(f, "1");
+        }
+        write_factors(f, BASE_UNITS.iter().copied().zip(self.0))
+    }
+}
+
+/// A product of named units raised to integer powers, like `m/s^2`. The
+/// empty product is a plain number.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Unit {
+    factors: Vec<(String, i32)>,
+}
+
+impl Unit {
+    /// The unit called `name`, like `m`, `km`, `min` or `lb`.
+    pub fn named(name: &str) -> Option<Unit> {
+        table::lookup(name)?;
+        Some(Unit {
+            factors: vec![(name.to_string(), 1)],
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// The size of the unit in SI base units, 1000 for `km`.
+    pub fn factor(&self) -> f64 {
+        self.factors
+            .iter()
+            .map(|(name, exponent)| definition(name).0.powi(*exponent))
+            .product()
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.factors
+            .iter()
+            .fold(Dimension::default(), |dimension, (name, exponent)| {
+                dimension.mul(definition(name).1.powi(*exponent))
+            })
+    }
+
+    fn mul(&self, other: &Unit) -> Unit {
+        let mut factors = self.factors.clone();
+        for (name, exponent) in &other.factors {
+            match factors.iter_mut().find(|(other, _)| other == name) {
+                Some((_, total)) => *total += exponent,
+                None => factors.push((name.to_owned(), *exponent)),
+            }
+        }
+        factors.retain(|(_, exponent)| *exponent != 0);
+        Unit { factors }
+    }
+
+    /// The unit raised to `exponent`, if every power stays an integer.
+    fn powf(&self, exponent: f64) -> Option<Unit> {
+        let factors = self
+            .factors
+            .iter()
+            .map(|(name, power)| {
+                let power = *power as f64 * exponent;
+                (power.fract() == 0.0).then(|| (name.to_owned(), power as i32))
+            })
+            .collect::<Option<Vec<(String, i32)>>>()?;
+        Some(Unit { factors })
+    }
+
+    /// Rewrites units of the same dimension in terms of the first of them, so
+    /// `m/s*min` becomes `m` with a scale of 60. Returns the scale along with
+    /// the simplified unit.
+    fn simplify(self) -> (f64, Unit) {
+        let mut scale = 1.0;
+        let mut factors: Vec<(String, i32)> = Vec::new();
+        for (name, exponent) in self.factors {
+            let (factor, dimension) = definition(&name);
+            let same = factors
+                .iter_mut()
+                .find(|(other, _)| definition(other).1 == dimension);
+            match same {
+                Some((other, total)) => {
+                    scale *= (factor / definition(other).0).powi(exponent);
+                    *total += exponent;
+                }
+                None => factors.push((name, exponent)),
+            }
+        }
+        factors.retain(|(_, exponent)| *exponent != 0);
+        (scale, Unit { factors })
+    }
+}
+
+/// The factor and dimension of a unit name already known to exist.
+fn definition(name: &str) -> (f64, Dimension) {
+    table::lookup(name).expect("units are looked up when they are created")
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let factors = self
+            .factors
+            .iter()
+            .map(|(name, exponent)| (name.as_str(), *exponent));
+        write_factors(f, factors)
+    }
+}
+
+/// A number with a unit, like `3 m/s`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    value: f64,
+    unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+
+    /// The value in SI base units.
+    pub fn si_value(&self) -> f64 {
+        self.value * self.unit.factor()
+    }
+
+    /// The value as a plain number, if the units cancel out, like in
+    /// `30 deg` or `1 m/km`.
+    pub fn dimensionless_value(&self) -> Option<f64> {
+        self.dimension().is_dimensionless().then(|| self.si_value())
+    }
+
+    /// The same quantity expressed in `unit`, if it has the same dimension.
+    pub fn convert(&self, unit: &Unit) -> Option<Quantity> {
+        (self.dimension() == unit.dimension())
+            .then(|| Quantity::new(self.si_value() / unit.factor(), unit.clone()))
+    }
+
+    /// `self op other` for `+`, `-` and `%`, in the unit of `self`. Both
+    /// sides must have the same dimension.
+    pub fn combine(&self, other: &Quantity, op: impl Fn(f64, f64) -> f64) -> Option<Quantity> {
+        let other = other.convert(&self.unit)?;
+        Some(Quantity::new(
+            op(self.value, other.value),
+            self.unit.clone(),
+        ))
+    }
+
+    pub fn mul(&self, other: &Quantity) -> Quantity {
+        let (scale, unit) = self.unit.mul(&other.unit).simplify();
+        Quantity::new(self.value * other.value * scale, unit)
+    }
+
+    pub fn div(&self, other: &Quantity) -> Quantity {
+        let inverse = other.unit.powf(-1.0).expect("integer powers stay integers");
+        self.mul(&Quantity::new(1.0 / other.value, inverse))
+    }
+
+    /// `self^exponent`, if the powers of its units stay integers.
+    pub fn powf(&self, exponent: f64) -> Option<Quantity> {
+        let unit = self.unit.powf(exponent)?;
+        Some(Quantity::new(self.value.powf(exponent), unit))
+    }
+
+    /// Applies `f` to the value, keeping the unit.
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Quantity {
+        Quantity::new(f(self.value), self.unit.clone())
+    }
+}
+
+#[wasm_bindgen]
+impl Quantity {
+    /// The number in front of the unit.
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The unit, like `m/s`, or an empty string for plain numbers.
+    #[wasm_bindgen(getter, js_name = unit)]
+    pub fn unit_name(&self) -> String {
+        self.unit.to_string()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_display_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::new(value, Unit::default())
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.unit.is_empty() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} {}", self.value, self.unit)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::f64::consts::PI;
+
+use super::Dimension;
+
+/// A unit that can be written in an expression, defined by its size in SI
+/// base units.
+struct Definition {
+    names: &'static [&'static str],
+    factor: f64,
+    dimension: Dimension,
+    /// Whether SI prefixes apply, as in `km` or `mA`.
+    prefixable: bool,
+}
+
+const fn unit(
+    names: &'static [&'static str],
+    factor: f64,
+    dimension: [i32; 7],
+    prefixable: bool,
+) -> Definition {
+    Definition {
+        names,
+        factor,
+        dimension: Dimension(dimension),
+        prefixable,
+    }
+}
+
+// Exponents of length, mass, time, current, temperature, amount and luminous
+// intensity
+const DIMENSIONLESS: [i32; 7] = [0, 0, 0, 0, 0, 0, 0];
+const LENGTH: [i32; 7] = [1, 0, 0, 0, 0, 0, 0];
+const MASS: [i32; 7] = [0, 1, 0, 0, 0, 0, 0];
+const TIME: [i32; 7] = [0, 0, 1, 0, 0, 0, 0];
+const AREA: [i32; 7] = [2, 0, 0, 0, 0, 0, 0];
+const VOLUME: [i32; 7] = [3, 0, 0, 0, 0, 0, 0];
+const SPEED: [i32; 7] = [1, 0, -1, 0, 0, 0, 0];
+const FORCE: [i32; 7] = [1, 1, -2, 0, 0, 0, 0];
+const PRESSURE: [i32; 7] = [-1, 1, -2, 0, 0, 0, 0];
+const ENERGY: [i32; 7] = [2, 1, -2, 0, 0, 0, 0];
+const POWER: [i32; 7] = [2, 1, -3, 0, 0, 0, 0];
+
+const UNITS: &[Definition] = &[
+    // SI base units, with the gram standing in for the kilogram so prefixes
+    // apply to it
+    unit(&["m"], 1.0, LENGTH, true),
+    unit(&["g"], 1e-3, MASS, true),
+    unit(&["s"], 1.0, TIME, true),
+    unit(&["A"], 1.0, [0, 0, 0, 1, 0, 0, 0], true),
+    unit(&["K"], 1.0, [0, 0, 0, 0, 1, 0, 0], true),
+    unit(&["mol"], 1.0, [0, 0, 0, 0, 0, 1, 0], true),
+    unit(&["cd"], 1.0, [0, 0, 0, 0, 0, 0, 1], true),
+    // Derived SI units
+    unit(&["Hz"], 1.0, [0, 0, -1, 0, 0, 0, 0], true),
+    unit(&["N"], 1.0, FORCE, true),
+    unit(&["Pa"], 1.0, PRESSURE, true),
+    unit(&["J"], 1.0, ENERGY, true),
+    unit(&["W"], 1.0, POWER, true),
+    unit(&["C"], 1.0, [0, 0, 1, 1, 0, 0, 0], true),
+    unit(&["V"], 1.0, [2, 1, -3, -1, 0, 0, 0], true),
+    unit(&["ohm", "Ω"], 1.0, [2, 1, -3, -2, 0, 0, 0], true),
+    unit(&["L", "l"], 1e-3, VOLUME, true),
+    unit(&["eV"], 1.602176634e-19, ENERGY, true),
+    unit(&["bar"], 1e5, PRESSURE, true),
+    unit(&["cal"], 4.184, ENERGY, true),
+    // Time
+    unit(&["min"], 60.0, TIME, false),
+    unit(&["h"], 3600.0, TIME, false),
+    unit(&["day"], 86400.0, TIME, false),
+    unit(&["week"], 604800.0, TIME, false),
+    unit(&["year"], 31557600.0, TIME, false),
+    // Imperial and US customary units
+    unit(&["in"], 0.0254, LENGTH, false),
+    unit(&["ft"], 0.3048, LENGTH, false),
+    unit(&["yd"], 0.9144, LENGTH, false),
+    unit(&["mi"], 1609.344, LENGTH, false),
+    unit(&["acre"], 4046.8564224, AREA, false),
+    unit(&["gal"], 3.785411784e-3, VOLUME, false),
+    unit(&["oz"], 0.028349523125, MASS, false),
+    unit(&["lb"], 0.45359237, MASS, false),
+    unit(&["mph"], 0.44704, SPEED, false),
+    unit(&["psi"], 6894.757293168361, PRESSURE, false),
+    unit(&["atm"], 101325.0, PRESSURE, false),
+    unit(&["hp"], 745.6998715822702, POWER, false),
+    // Angles are dimensionless, they only scale the number
+    unit(&["rad"], 1.0, DIMENSIONLESS, false),
+    unit(&["deg"], PI / 180.0, DIMENSIONLESS, false),
+];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("da", 1e1),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+];
+
+/// The size in SI base units and the dimension of the unit called `name`.
+/// Exact names win over prefixed ones, so `min` is a minute and `cd` a
+/// candela.
+pub(super) fn lookup(name: &str) -> Option<(f64, Dimension)> {
+    let exact = |name: &str| {
+        UNITS
+            .iter()
+            .find(|definition| definition.names.contains(&name))
+    };
+    if let Some(definition) = exact(name) {
+        return Some((definition.factor, definition.dimension));
+    }
+
+    PREFIXES.iter().find_map(|(prefix, scale)| {
+        let definition = exact(name.strip_prefix(prefix)?)?;
+        definition
+            .prefixable
+            .then(|| (scale * definition.factor, definition.dimension))
+    })
+}
@@ -0,0 +1,331 @@
+use crate::error::{EvaluatorError, Result};
+use crate::math::{
+    as_integer, binomial, binomial_pmf, erf, erfc, factorial, gcd, lcm, mean, median, mode,
+    normal_cdf, normal_inverse_cdf, normal_pdf, poisson_cdf, quantile, variance,
+};
+
+use super::{AngleConversion, Arity, FunctionRegistry};
+
+pub(super) fn domain_error(function: &str, reason: &str) -> EvaluatorError {
+    EvaluatorError::Domain {
+        function: function.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn integer_arg(function: &str, x: f64) -> Result<i64, EvaluatorError> {
+    as_integer(x).ok_or_else(|| domain_error(function, "arguments must be integers"))
+}
+
+fn natural_arg(function: &str, x: f64) -> Result<u64, EvaluatorError> {
+    match integer_arg(function, x)? {
+        n if n >= 0 => Ok(n as u64),
+        _ => Err(domain_error(
+            function,
+            "arguments must be non-negative integers",
+        )),
+    }
+}
+
+pub(super) fn register(registry: &mut FunctionRegistry) {
+    register_trigonometric(registry);
+    register_hyperbolic(registry);
+    register_logarithmic(registry);
+    register_rounding(registry);
+    register_algebraic(registry);
+    register_integer(registry);
+    register_statistics(registry);
+    register_distributions(registry);
+}
+
+/// Whether `value`, the sine or cosine of `x`, is zero up to the rounding
+/// error of `x` itself. `cos(pi/2)` is `6e-17` rather than `0` since `pi/2`
+/// isn't exact, and the error grows with the size of `x`.
+fn vanishes(value: f64, x: f64) -> bool {
+    value.abs() <= f64::EPSILON * x.abs().max(1.0)
+}
+
+fn register_trigonometric(registry: &mut FunctionRegistry) {
+    use AngleConversion::{Input, Output};
+
+    registry.register_angular("cos", Arity::Fixed(1), Input, |args| Ok(args[0].cos()));
+    registry.register_angular("sin", Arity::Fixed(1), Input, |args| Ok(args[0].sin()));
+    registry.register_angular("tan", Arity::Fixed(1), Input, |args| Ok(args[0].tan()));
+    registry.register_angular("sec", Arity::Fixed(1), Input, |args| match args[0].cos() {
+        cos if vanishes(cos, args[0]) => Err(domain_error("sec", "cosine of the argument is zero")),
+        cos => Ok(1.0 / cos),
+    });
+    registry.register_angular("csc", Arity::Fixed(1), Input, |args| match args[0].sin() {
+        sin if vanishes(sin, args[0]) => Err(domain_error("csc", "sine of the argument is zero")),
+        sin => Ok(1.0 / sin),
+    });
+    registry.register_angular("cot", Arity::Fixed(1), Input, |args| {
+        if vanishes(args[0].sin(), args[0]) {
+            return Err(domain_error("cot", "tangent of the argument is zero"));
+        }
+        Ok(1.0 / args[0].tan())
+    });
+
+    registry.register_angular("acos", Arity::Fixed(1), Output, |args| {
+        if !(-1.0..=1.0).contains(&args[0]) {
+            return Err(domain_error("acos", "argument must be between -1 and 1"));
+        }
+        Ok(args[0].acos())
+    });
+    registry.register_angular("asin", Arity::Fixed(1), Output, |args| {
+        if !(-1.0..=1.0).contains(&args[0]) {
+            return Err(domain_error("asin", "argument must be between -1 and 1"));
+        }
+        Ok(args[0].asin())
+    });
+    registry.register_angular("atan", Arity::Fixed(1), Output, |args| Ok(args[0].atan()));
+    registry.register_angular("atan2", Arity::Fixed(2), Output, |args| {
+        Ok(args[0].atan2(args[1]))
+    });
+}
+
+fn register_hyperbolic(registry: &mut FunctionRegistry) {
+    registry.register("sinh", Arity::Fixed(1), |args| Ok(args[0].sinh()));
+    registry.register("cosh", Arity::Fixed(1), |args| Ok(args[0].cosh()));
+    registry.register("tanh", Arity::Fixed(1), |args| Ok(args[0].tanh()));
+    registry.register("asinh", Arity::Fixed(1), |args| Ok(args[0].asinh()));
+    registry.register("acosh", Arity::Fixed(1), |args| {
+        if args[0] < 1.0 {
+            return Err(domain_error("acosh", "argument must be at least 1"));
+        }
+        Ok(args[0].acosh())
+    });
+    registry.register("atanh", Arity::Fixed(1), |args| {
+        if args[0] <= -1.0 || args[0] >= 1.0 {
+            return Err(domain_error(
+                "atanh",
+                "argument must be between -1 and 1 exclusive",
+            ));
+        }
+        Ok(args[0].atanh())
+    });
+}
+
+fn register_logarithmic(registry: &mut FunctionRegistry) {
+    fn positive(function: &str, x: f64) -> Result<f64, EvaluatorError> {
+        if x <= 0.0 {
+            return Err(domain_error(function, "argument must be positive"));
+        }
+        Ok(x)
+    }
+
+    registry.register("exp", Arity::Fixed(1), |args| Ok(args[0].exp()));
+    registry.register("ln", Arity::Fixed(1), |args| {
+        Ok(positive("ln", args[0])?.ln())
+    });
+    registry.register("log10", Arity::Fixed(1), |args| {
+        Ok(positive("log10", args[0])?.log10())
+    });
+    registry.register("log2", Arity::Fixed(1), |args| {
+        Ok(positive("log2", args[0])?.log2())
+    });
+    // log(x) is the common logarithm, log(b, x) the logarithm of x in base b.
+    registry.register("log", Arity::Range(1, 2), |args| match args {
+        [x] => Ok(positive("log", *x)?.log10()),
+        [base, x] => {
+            if *base <= 0.0 || *base == 1.0 {
+                return Err(domain_error(
+                    "log",
+                    "base must be positive and not equal to 1",
+                ));
+            }
+            Ok(positive("log", *x)?.log(*base))
+        }
+        _ => Err(EvaluatorError::ArityMismatch {
+            name: "log".to_string(),
+            expected: Arity::Range(1, 2),
+            found: args.len(),
+        }),
+    });
+}
+
+fn register_rounding(registry: &mut FunctionRegistry) {
+    registry.register("floor", Arity::Fixed(1), |args| Ok(args[0].floor()));
+    registry.register("ceil", Arity::Fixed(1), |args| Ok(args[0].ceil()));
+    registry.register("round", Arity::Fixed(1), |args| Ok(args[0].round()));
+    registry.register("trunc", Arity::Fixed(1), |args| Ok(args[0].trunc()));
+    registry.register("fract", Arity::Fixed(1), |args| Ok(args[0].fract()));
+    registry.register("abs", Arity::Fixed(1), |args| Ok(args[0].abs()));
+    registry.register("sign", Arity::Fixed(1), |args| match args[0] {
+        0.0 => Ok(0.0),
+        x => Ok(x.signum()),
+    });
+}
+
+fn register_algebraic(registry: &mut FunctionRegistry) {
+    registry.register("sqrt", Arity::Fixed(1), |args| {
+        if args[0] < 0.0 {
+            return Err(domain_error("sqrt", "argument must not be negative"));
+        }
+        Ok(args[0].sqrt())
+    });
+    registry.register("cbrt", Arity::Fixed(1), |args| Ok(args[0].cbrt()));
+    registry.register("nthroot", Arity::Fixed(2), |args| {
+        let (x, n) = (args[0], integer_arg("nthroot", args[1])?);
+        match n {
+            0 => Err(domain_error("nthroot", "root degree must not be zero")),
+            n if x < 0.0 && n % 2 == 0 => {
+                Err(domain_error("nthroot", "even root of a negative number"))
+            }
+            n if x < 0.0 => Ok(-(-x).powf(1.0 / n as f64)),
+            n => Ok(x.powf(1.0 / n as f64)),
+        }
+    });
+    registry.register("hypot", Arity::Variadic(1), |args| {
+        Ok(args.iter().fold(0.0, |acc: f64, x| acc.hypot(*x)))
+    });
+    registry.register("pow", Arity::Fixed(2), |args| Ok(args[0].powf(args[1])));
+}
+
+fn register_integer(registry: &mut FunctionRegistry) {
+    registry.register("gcd", Arity::Variadic(2), |args| {
+        let mut result = 0;
+        for x in args {
+            result = gcd(result, integer_arg("gcd", *x)?.unsigned_abs());
+        }
+        Ok(result as f64)
+    });
+    registry.register("lcm", Arity::Variadic(2), |args| {
+        let mut result = 1;
+        for x in args {
+            result = lcm(result, integer_arg("lcm", *x)?.unsigned_abs())
+                .ok_or_else(|| domain_error("lcm", "result is too large"))?;
+        }
+        Ok(result as f64)
+    });
+    registry.register("factorial", Arity::Fixed(1), |args| {
+        factorial(natural_arg("factorial", args[0])?)
+            .ok_or_else(|| domain_error("factorial", "result is too large"))
+    });
+    registry.register("binomial", Arity::Fixed(2), |args| {
+        let n = natural_arg("binomial", args[0])?;
+        let k = natural_arg("binomial", args[1])?;
+        binomial(n, k).ok_or_else(|| domain_error("binomial", "result is too large"))
+    });
+}
+
+fn register_statistics(registry: &mut FunctionRegistry) {
+    /// A probability or fraction from `function`'s first argument, which
+    /// must lie between 0 and `max`.
+    fn fraction(function: &str, x: f64, max: f64) -> Result<f64, EvaluatorError> {
+        if !(0.0..=max).contains(&x) {
+            return Err(domain_error(
+                function,
+                &format!("first argument must be between 0 and {max}"),
+            ));
+        }
+        Ok(x / max)
+    }
+
+    registry.register("min", Arity::Variadic(1), |args| {
+        Ok(args.iter().copied().fold(f64::INFINITY, f64::min))
+    });
+    registry.register("max", Arity::Variadic(1), |args| {
+        Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+    });
+    registry.register("sum", Arity::Variadic(1), |args| Ok(args.iter().sum()));
+    registry.register("product", Arity::Variadic(1), |args| {
+        Ok(args.iter().product())
+    });
+    registry.register("mean", Arity::Variadic(1), |args| Ok(mean(args)));
+    registry.register("median", Arity::Variadic(1), |args| Ok(median(args)));
+    registry.register("mode", Arity::Variadic(1), |args| Ok(mode(args)));
+    // Sample statistics divide by n - 1, the p-prefixed population ones by n.
+    registry.register("variance", Arity::Variadic(2), |args| {
+        Ok(variance(args, true))
+    });
+    registry.register("stdev", Arity::Variadic(2), |args| {
+        Ok(variance(args, true).sqrt())
+    });
+    registry.register("pvariance", Arity::Variadic(1), |args| {
+        Ok(variance(args, false))
+    });
+    registry.register("pstdev", Arity::Variadic(1), |args| {
+        Ok(variance(args, false).sqrt())
+    });
+    // percentile(p, x1, ..., xn) with p in [0, 100], quantile(q, x1, ..., xn)
+    // with q in [0, 1].
+    registry.register("percentile", Arity::Variadic(2), |args| {
+        let p = fraction("percentile", args[0], 100.0)?;
+        Ok(quantile(&args[1..], p))
+    });
+    registry.register("quantile", Arity::Variadic(2), |args| {
+        let q = fraction("quantile", args[0], 1.0)?;
+        Ok(quantile(&args[1..], q))
+    });
+}
+
+fn register_distributions(registry: &mut FunctionRegistry) {
+    /// The mean and standard deviation following `x`, defaulting to the
+    /// standard normal distribution.
+    fn normal_args(function: &str, args: &[f64]) -> Result<(f64, f64), EvaluatorError> {
+        let (mu, sigma) = match args {
+            [_] => (0.0, 1.0),
+            [_, mu] => (*mu, 1.0),
+            [_, mu, sigma] => (*mu, *sigma),
+            _ => {
+                return Err(EvaluatorError::ArityMismatch {
+                    name: function.to_string(),
+                    expected: Arity::Range(1, 3),
+                    found: args.len(),
+                })
+            }
+        };
+        if sigma <= 0.0 {
+            return Err(domain_error(
+                function,
+                "standard deviation must be positive",
+            ));
+        }
+        Ok((mu, sigma))
+    }
+
+    fn probability(function: &str, p: f64) -> Result<f64, EvaluatorError> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(domain_error(
+                function,
+                "probability must be between 0 and 1",
+            ));
+        }
+        Ok(p)
+    }
+
+    registry.register("erf", Arity::Fixed(1), |args| Ok(erf(args[0])));
+    registry.register("erfc", Arity::Fixed(1), |args| Ok(erfc(args[0])));
+    // normpdf(x), normpdf(x, mu) or normpdf(x, mu, sigma), likewise normcdf
+    // and invnorm.
+    registry.register("normpdf", Arity::Range(1, 3), |args| {
+        let (mu, sigma) = normal_args("normpdf", args)?;
+        Ok(normal_pdf(args[0], mu, sigma))
+    });
+    registry.register("normcdf", Arity::Range(1, 3), |args| {
+        let (mu, sigma) = normal_args("normcdf", args)?;
+        Ok(normal_cdf(args[0], mu, sigma))
+    });
+    registry.register("invnorm", Arity::Range(1, 3), |args| {
+        let (mu, sigma) = normal_args("invnorm", args)?;
+        match probability("invnorm", args[0])? {
+            0.0 => Ok(f64::NEG_INFINITY),
+            1.0 => Ok(f64::INFINITY),
+            p => Ok(normal_inverse_cdf(p, mu, sigma)),
+        }
+    });
+    // binompdf(n, p, k) and poissoncdf(lambda, k)
+    registry.register("binompdf", Arity::Fixed(3), |args| {
+        let n = natural_arg("binompdf", args[0])?;
+        let p = probability("binompdf", args[1])?;
+        let k = natural_arg("binompdf", args[2])?;
+        Ok(binomial_pmf(n, p, k))
+    });
+    registry.register("poissoncdf", Arity::Fixed(2), |args| {
+        if args[0] < 0.0 {
+            return Err(domain_error("poissoncdf", "mean must not be negative"));
+        }
+        Ok(poisson_cdf(args[0], natural_arg("poissoncdf", args[1])?))
+    });
+}
@@ -0,0 +1,289 @@
+use num_complex::Complex64;
+
+use crate::error::{EvaluatorError, Result};
+use crate::math::{as_integer, round};
+use crate::parser::{parse_with, Constant, Expr, Op, ParserOptions, SeriesKind};
+
+use super::builtins::domain_error;
+use super::evaluator::locate;
+use super::frames::{Frames, UserFunctions};
+use super::{Arity, Context};
+
+/// The result, or a domain error for `function` if it isn't a finite number.
+fn finite(function: &str, z: Complex64) -> Result<Complex64, EvaluatorError> {
+    if z.is_finite() {
+        Ok(z)
+    } else {
+        Err(domain_error(function, "result is not a finite number"))
+    }
+}
+
+/// The real value of `z`, if it has no imaginary part.
+fn real(z: Complex64) -> Option<f64> {
+    (z.im == 0.0).then_some(z.re)
+}
+
+/// The principal value of `z^w`. Integer powers are multiplied out and real
+/// powers of non-negative reals use `f64::powf`, so they come out exact
+/// where the real result would.
+fn power(z: Complex64, w: Complex64) -> Complex64 {
+    match (real(z), real(w)) {
+        (_, Some(n)) if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 => z.powi(n as i32),
+        (Some(x), Some(y)) if x >= 0.0 => Complex64::from(x.powf(y)),
+        _ if z == Complex64::from(0.0) && w.re > 0.0 => Complex64::from(0.0),
+        _ => z.powc(w),
+    }
+}
+
+/// `-z` without negative zeros, so negating a real number keeps it on the
+/// upper side of the branch cuts and `sqrt(-1)` is `i` rather than `-i`.
+fn negate(z: Complex64) -> Complex64 {
+    Complex64::new(-z.re + 0.0, -z.im + 0.0)
+}
+
+/// Number of arguments of the functions with a complex implementation.
+fn builtin_arity(name: &str) -> Option<Arity> {
+    match name {
+        "re" | "im" | "arg" | "conj" | "abs" | "sqrt" | "cbrt" | "exp" | "ln" | "log10"
+        | "log2" | "sin" | "cos" | "tan" | "sec" | "csc" | "cot" | "asin" | "acos" | "atan"
+        | "sinh" | "cosh" | "tanh" | "asinh" | "acosh" | "atanh" => Some(Arity::Fixed(1)),
+        "nthroot" | "pow" => Some(Arity::Fixed(2)),
+        "log" => Some(Arity::Range(1, 2)),
+        _ => None,
+    }
+}
+
+/// Walks an expression tree like the `f64` evaluator, but with complex
+/// numbers.
+struct ComplexEvaluator<'a> {
+    ctx: &'a Context,
+    frames: Frames<Complex64>,
+}
+
+impl<'a> UserFunctions<'a> for ComplexEvaluator<'a> {
+    type Value = Complex64;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<Complex64> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Complex64, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Complex64>) -> Result<Complex64, EvaluatorError> {
+        self.call_builtin(name, &args)
+    }
+
+    fn integer(&self, n: i64) -> Complex64 {
+        Complex64::from(n as f64)
+    }
+
+    fn as_integer(&self, value: &Complex64) -> Option<i64> {
+        real(*value).and_then(as_integer)
+    }
+
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Complex64,
+        term: Complex64,
+    ) -> Result<Complex64, EvaluatorError> {
+        Ok(match kind {
+            SeriesKind::Sum => total + term,
+            SeriesKind::Product => total * term,
+        })
+    }
+}
+
+impl<'a> ComplexEvaluator<'a> {
+    fn new(ctx: &'a Context) -> Self {
+        Self {
+            ctx,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<Complex64> {
+        self.frames
+            .get(name)
+            .copied()
+            .or_else(|| self.ctx.get_variable(name).map(Complex64::from))
+    }
+
+    /// Calls a built-in function with its principal branch. Functions without
+    /// a complex implementation, and host functions, accept real arguments
+    /// only.
+    fn call_builtin(&self, name: &str, args: &[Complex64]) -> Result<Complex64, EvaluatorError> {
+        let Some(arity) = builtin_arity(name) else {
+            let Some(args) = args
+                .iter()
+                .map(|&arg| real(arg))
+                .collect::<Option<Vec<f64>>>()
+            else {
+                return Err(domain_error(name, "arguments must be real"));
+            };
+            let result = self
+                .ctx
+                .functions()
+                .call(name, &args, self.ctx.angle_mode())?;
+            return Ok(Complex64::from(result));
+        };
+        if !arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            });
+        }
+
+        let angle_mode = self.ctx.angle_mode();
+        // Converting angles is a scaling, which extends to complex angles
+        let to_radians = |z: Complex64| z * angle_mode.to_radians(1.0);
+        let from_radians = |z: Complex64| z * angle_mode.from_radians(1.0);
+        let one = Complex64::from(1.0);
+
+        let result = match (name, args) {
+            ("re", [z]) => Complex64::from(z.re),
+            ("im", [z]) => Complex64::from(z.im),
+            ("arg", [z]) => Complex64::from(angle_mode.from_radians(z.arg())),
+            ("conj", [z]) => Complex64::new(z.re, -z.im + 0.0),
+            ("abs", [z]) => Complex64::from(z.norm()),
+
+            ("sqrt", [z]) => match real(*z) {
+                Some(x) if x >= 0.0 => Complex64::from(x.sqrt()),
+                _ => z.sqrt(),
+            },
+            // The real root of a real number, like the real function
+            ("cbrt", [z]) => match real(*z) {
+                Some(x) => Complex64::from(x.cbrt()),
+                None => z.cbrt(),
+            },
+            ("nthroot", [z, n]) => {
+                let n = real(*n)
+                    .filter(|n| n.fract() == 0.0)
+                    .ok_or_else(|| domain_error("nthroot", "root degree must be an integer"))?;
+                if n == 0.0 {
+                    return Err(domain_error("nthroot", "root degree must not be zero"));
+                }
+                match real(*z) {
+                    Some(x) if x < 0.0 && n % 2.0 != 0.0 => Complex64::from(-(-x).powf(1.0 / n)),
+                    _ => power(*z, Complex64::from(1.0 / n)),
+                }
+            }
+            ("pow", [z, w]) => power(*z, *w),
+
+            ("exp", [z]) => z.exp(),
+            ("ln", [z]) => z.ln(),
+            ("log10", [z]) => z.log10(),
+            ("log2", [z]) => z.log2(),
+            // log(z) is the common logarithm, log(b, z) the logarithm of z in base b.
+            ("log", [z]) => z.log10(),
+            ("log", [base, z]) => z.ln() / base.ln(),
+
+            ("sin", [z]) => to_radians(*z).sin(),
+            ("cos", [z]) => to_radians(*z).cos(),
+            ("tan", [z]) => to_radians(*z).tan(),
+            ("sec", [z]) => one / to_radians(*z).cos(),
+            ("csc", [z]) => one / to_radians(*z).sin(),
+            ("cot", [z]) => one / to_radians(*z).tan(),
+            ("asin", [z]) => from_radians(z.asin()),
+            ("acos", [z]) => from_radians(z.acos()),
+            ("atan", [z]) => from_radians(z.atan()),
+
+            ("sinh", [z]) => z.sinh(),
+            ("cosh", [z]) => z.cosh(),
+            ("tanh", [z]) => z.tanh(),
+            ("asinh", [z]) => z.asinh(),
+            ("acosh", [z]) => z.acosh(),
+            ("atanh", [z]) => z.atanh(),
+            _ => return Err(EvaluatorError::UnknownFunction(name.to_string())),
+        };
+        finite(name, result)
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Complex64, EvaluatorError> {
+        match expr {
+            Expr::BinOp { op: Op::Equals, .. } => Err(EvaluatorError::EqualityInEval),
+            Expr::BinOp { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.evaluate_expr(lhs)?, self.evaluate_expr(rhs)?);
+                match op {
+                    Op::Add => Ok(lhs + rhs),
+                    Op::Subtract => Ok(lhs - rhs),
+                    Op::Multiply => Ok(lhs * rhs),
+                    Op::Divide if rhs == Complex64::from(0.0) => {
+                        Err(domain_error("/", "division by zero"))
+                    }
+                    Op::Divide => Ok(lhs / rhs),
+                    Op::Modulo => match (real(lhs), real(rhs)) {
+                        (Some(a), Some(b)) => Ok(Complex64::from((a % b).abs())),
+                        _ => Err(domain_error("%", "arguments must be real")),
+                    },
+                    Op::Power => finite("^", power(lhs, rhs)),
+                    Op::Equals => Err(EvaluatorError::EqualityInEval),
+                    Op::Convert => Err(domain_error(
+                        "to",
+                        "only quantities with units can be converted",
+                    )),
+                }
+            }
+            Expr::Number(val) => Ok(Complex64::from(*val)),
+            Expr::Rational(val) => Ok(Complex64::from(val.to_f64())),
+            Expr::Constant(Constant::ImaginaryUnit) => Ok(Complex64::i()),
+            Expr::Constant(constant @ Constant::Unit(_)) => Err(domain_error(
+                constant.name(),
+                "hypercomplex numbers aren't complex",
+            )),
+            Expr::Constant(constant) => Ok(Complex64::from(constant.value())),
+            Expr::Matrix(_) => Err(domain_error("[]", "matrices have no scalar value")),
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(negate(self.evaluate_expr(op)?)),
+            Expr::Function { name, args } => match expr.as_series() {
+                Some(series) => self.evaluate_series(series),
+                None => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.evaluate_expr(arg))
+                        .collect::<Result<Vec<Complex64>, EvaluatorError>>()?;
+                    self.call(name, args)
+                }
+            },
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => match self.get_variable(variable) {
+                Some(value) => Ok(*coefficient * power(value, Complex64::from(*exponent))),
+                None => Err(EvaluatorError::UnknownVariable(variable.to_owned())),
+            },
+        }
+    }
+}
+
+/// Evaluates `expression` over the complex numbers, reading `i` as the
+/// imaginary unit.
+pub fn evaluate_complex(expression: &str) -> Result<Complex64> {
+    let options = ParserOptions {
+        spans: true,
+        complex: true,
+        ..Default::default()
+    };
+    Ok(evaluate_complex_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+    )?)
+}
+
+/// Evaluates an already parsed expression over the complex numbers, resolving
+/// its variables from `ctx`. Functions use their principal branch, so
+/// `sqrt(-1)` is `i` and `ln(-1)` is `πi`.
+pub fn evaluate_complex_with(expr: &Expr, ctx: &Context) -> Result<Complex64, EvaluatorError> {
+    let z = ComplexEvaluator::new(ctx).evaluate_expr(expr)?;
+    Ok(Complex64::new(round(z.re, 15), round(z.im, 15)))
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::error::{EvaluatorError, Result};
+use crate::math::AngleMode;
+use crate::parser::{parse_equation_with, ParserOptions};
+
+use super::user_function::check_recursion;
+use super::{Arity, FunctionRegistry, UserFunction};
+
+/// Variable bindings and functions used while evaluating an expression.
+#[derive(Debug, Clone)]
+pub struct Context {
+    variables: HashMap<String, f64>,
+    functions: FunctionRegistry,
+    user_functions: HashMap<String, UserFunction>,
+    angle_mode: AngleMode,
+    parser_options: ParserOptions,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            variables: HashMap::new(),
+            functions: FunctionRegistry::with_builtins(),
+            user_functions: HashMap::new(),
+            angle_mode: AngleMode::default(),
+            parser_options: ParserOptions::default(),
+        }
+    }
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `name` to `value`, replacing any previous binding.
+    pub fn set_variable(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    /// Builder style variant of [`Context::set_variable`].
+    pub fn with_variable(mut self, name: &str, value: f64) -> Self {
+        self.set_variable(name, value);
+        self
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<f64> {
+        self.variables.get(name).copied()
+    }
+
+    pub fn remove_variable(&mut self, name: &str) -> Option<f64> {
+        self.variables.remove(name)
+    }
+
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    /// Sets the unit used by trigonometric functions, radians by default.
+    pub fn set_angle_mode(&mut self, angle_mode: AngleMode) {
+        self.angle_mode = angle_mode;
+    }
+
+    /// Builder style variant of [`Context::set_angle_mode`].
+    pub fn with_angle_mode(mut self, angle_mode: AngleMode) -> Self {
+        self.set_angle_mode(angle_mode);
+        self
+    }
+
+    pub fn parser_options(&self) -> &ParserOptions {
+        &self.parser_options
+    }
+
+    /// Sets the options used to parse definitions and expressions in this context.
+    pub fn set_parser_options(&mut self, parser_options: ParserOptions) {
+        self.parser_options = parser_options;
+    }
+
+    /// Registers a host function callable from expressions evaluated in this context.
+    pub fn register_function<F>(&mut self, name: &str, arity: Arity, body: F)
+    where
+        F: Fn(&[f64]) -> Result<f64, EvaluatorError> + 'static,
+    {
+        self.functions.register(name, arity, body);
+    }
+
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    pub fn functions_mut(&mut self) -> &mut FunctionRegistry {
+        &mut self.functions
+    }
+
+    /// Defines a function from a statement such as `f(X, Y) = X^2 + Y`.
+    ///
+    /// User functions take precedence over registered functions with the same
+    /// name. Definitions that would make a function call itself are rejected.
+    pub fn define(&mut self, definition: &str) -> Result<()> {
+        let function =
+            UserFunction::from_equation(parse_equation_with(definition, &self.parser_options)?)?;
+        Ok(self.define_function(function)?)
+    }
+
+    pub fn define_function(&mut self, function: UserFunction) -> Result<(), EvaluatorError> {
+        check_recursion(&function, &self.user_functions)?;
+        self.user_functions
+            .insert(function.name.to_owned(), function);
+        Ok(())
+    }
+
+    pub fn get_user_function(&self, name: &str) -> Option<&UserFunction> {
+        self.user_functions.get(name)
+    }
+
+    pub fn remove_user_function(&mut self, name: &str) -> Option<UserFunction> {
+        self.user_functions.remove(name)
+    }
+}
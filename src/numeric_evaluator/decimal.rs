@@ -0,0 +1,448 @@
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::error::{EvaluatorError, Result};
+use crate::math::{Decimal, Precision, Rational, RoundingMode, GUARD_DIGITS};
+use crate::parser::{parse_with, Constant, Expr, Op, ParserOptions, SeriesKind};
+
+use super::builtins::domain_error;
+use super::evaluator::locate;
+use super::frames::{Frames, UserFunctions};
+use super::{AngleMode, Arity, Context};
+
+/// Number of arguments of the built-in functions that can be evaluated with
+/// decimals.
+fn builtin_arity(name: &str) -> Option<Arity> {
+    match name {
+        "sin" | "cos" | "tan" | "sec" | "csc" | "cot" | "asin" | "acos" | "atan" | "exp" | "ln"
+        | "log10" | "log2" | "floor" | "ceil" | "round" | "trunc" | "fract" | "abs" | "sign"
+        | "sqrt" | "cbrt" => Some(Arity::Fixed(1)),
+        "atan2" | "nthroot" | "pow" => Some(Arity::Fixed(2)),
+        "min" | "max" | "sum" | "product" => Some(Arity::Variadic(1)),
+        "log" => Some(Arity::Range(1, 2)),
+        _ => None,
+    }
+}
+
+/// The exact value of a number that was stored as an `f64`.
+fn from_f64(name: &str, x: f64, precision: Precision) -> Result<Decimal, EvaluatorError> {
+    Rational::from_f64(x)
+        .map(|x| Decimal::from_rational(&x, precision))
+        .ok_or_else(|| domain_error(name, "value is not a finite number"))
+}
+
+/// The exact value of a number literal, possibly negated.
+fn number(expr: &Expr) -> Option<BigRational> {
+    match expr.unspanned() {
+        Expr::Number(n) => Rational::from_f64(*n).map(|n| n.0),
+        Expr::Rational(n) => Some(n.0.clone()),
+        Expr::UnaryMinus(inner) => number(inner).map(|n| -n),
+        _ => None,
+    }
+}
+
+/// `value`, or a domain error for `function` if its exponent is too large to
+/// keep computing with.
+fn bounded(function: &str, value: Decimal) -> Result<Decimal, EvaluatorError> {
+    match value.is_in_range() {
+        true => Ok(value),
+        false => Err(domain_error(function, "result is out of range")),
+    }
+}
+
+/// `base^exponent` for `function`. Positive bases always have a real power,
+/// so there it can only have been out of range.
+fn power(
+    function: &str,
+    base: &Decimal,
+    exponent: &Decimal,
+    precision: Precision,
+) -> Result<Decimal, EvaluatorError> {
+    match base.pow(exponent, precision) {
+        Some(power) => bounded(function, power),
+        None if base.is_zero() || base.is_negative() => {
+            Err(domain_error(function, "result is not a real number"))
+        }
+        None => Err(domain_error(function, "result is out of range")),
+    }
+}
+
+/// `k` if `expr` is a rational multiple `k*pi` written with numbers and
+/// `pi`/`tau` only.
+fn pi_multiple(expr: &Expr) -> Option<BigRational> {
+    match expr.unspanned() {
+        Expr::Constant(Constant::Pi) => Some(BigRational::one()),
+        Expr::Constant(Constant::Tau) => Some(BigRational::from_integer(2.into())),
+        Expr::UnaryMinus(inner) => pi_multiple(inner).map(|k| -k),
+        Expr::BinOp {
+            lhs,
+            op: Op::Multiply,
+            rhs,
+        } => match (number(lhs), number(rhs)) {
+            (Some(n), None) => pi_multiple(rhs).map(|k| k * n),
+            (None, Some(n)) => pi_multiple(lhs).map(|k| k * n),
+            _ => None,
+        },
+        Expr::BinOp {
+            lhs,
+            op: Op::Divide,
+            rhs,
+        } => {
+            let n = number(rhs).filter(|n| !n.is_zero())?;
+            pi_multiple(lhs).map(|k| k / n)
+        }
+        _ => None,
+    }
+}
+
+/// Walks an expression tree like the `f64` evaluator, rounding every
+/// intermediate result to `precision`.
+struct DecimalEvaluator<'a> {
+    ctx: &'a Context,
+    precision: Precision,
+    frames: Frames<Decimal>,
+}
+
+impl<'a> UserFunctions<'a> for DecimalEvaluator<'a> {
+    type Value = Decimal;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<Decimal> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Decimal, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Decimal>) -> Result<Decimal, EvaluatorError> {
+        self.call_builtin(name, &args, self.ctx.angle_mode())
+            .and_then(|value| bounded(name, value))
+    }
+
+    fn integer(&self, n: i64) -> Decimal {
+        Decimal::from(n)
+    }
+
+    fn as_integer(&self, value: &Decimal) -> Option<i64> {
+        value.to_integer()?.to_i64()
+    }
+
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Decimal,
+        term: Decimal,
+    ) -> Result<Decimal, EvaluatorError> {
+        match kind {
+            SeriesKind::Sum => Ok((&total + &term).round(self.precision)),
+            SeriesKind::Product => bounded(kind.name(), (&total * &term).round(self.precision)),
+        }
+    }
+}
+
+impl<'a> DecimalEvaluator<'a> {
+    fn new(ctx: &'a Context, precision: Precision) -> Self {
+        Self {
+            ctx,
+            precision,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Result<Decimal, EvaluatorError> {
+        if let Some(value) = self.frames.get(name) {
+            return Ok(value.clone());
+        }
+        match self.ctx.get_variable(name) {
+            Some(value) => from_f64(name, value, self.precision),
+            None => Err(EvaluatorError::UnknownVariable(name.to_owned())),
+        }
+    }
+
+    fn constant(&self, constant: Constant) -> Result<Decimal, EvaluatorError> {
+        let precision = self.precision;
+        let working = precision.working(GUARD_DIGITS);
+        match constant {
+            Constant::Pi => Ok(Decimal::pi(precision)),
+            Constant::Tau => Ok((&Decimal::pi(working) * &Decimal::from(2)).round(precision)),
+            Constant::E => Ok(Decimal::from(1).exp(precision).expect("e is finite")),
+            Constant::Phi => {
+                let root = Decimal::from(5).sqrt(working).expect("5 is positive");
+                let phi = (&root + &Decimal::from(1))
+                    .checked_div(&Decimal::from(2), precision)
+                    .expect("2 is not zero");
+                Ok(phi)
+            }
+            Constant::Infinity => Err(domain_error("inf", "infinity has no decimal value")),
+            Constant::ImaginaryUnit => Err(domain_error("i", "complex numbers have no real value")),
+            Constant::Unit(_) => Err(domain_error(
+                constant.name(),
+                "hypercomplex numbers have no real value",
+            )),
+        }
+    }
+
+    /// The argument in whole degrees of a trigonometric function of a
+    /// multiple of π, like `sin(pi)` or `tan(3pi/4)`, so it can be reduced
+    /// exactly rather than with π rounded to the precision.
+    fn exact_angle(&self, name: &str, args: &[Expr]) -> Option<Decimal> {
+        let [arg] = args else {
+            return None;
+        };
+        let trigonometric = matches!(name, "sin" | "cos" | "tan" | "sec" | "csc" | "cot");
+        if !trigonometric
+            || self.ctx.angle_mode() != AngleMode::Radians
+            || self.ctx.get_user_function(name).is_some()
+        {
+            return None;
+        }
+        let degrees = pi_multiple(arg)? * BigRational::from_integer(180.into());
+        degrees
+            .is_integer()
+            .then(|| Decimal::new(degrees.to_integer(), 0))
+    }
+
+    fn call_builtin(
+        &self,
+        name: &str,
+        args: &[Decimal],
+        angle_mode: AngleMode,
+    ) -> Result<Decimal, EvaluatorError> {
+        let Some(arity) = builtin_arity(name) else {
+            if self.ctx.functions().contains(name) {
+                return Err(EvaluatorError::NotDecimal(name.to_string()));
+            }
+            return Err(EvaluatorError::UnknownFunction(name.to_string()));
+        };
+        if !arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            });
+        }
+
+        let precision = self.precision;
+        let working = precision.working(GUARD_DIGITS);
+        let positive = |function: &str, x: &Decimal| {
+            if x.is_zero() || x.is_negative() {
+                return Err(domain_error(function, "argument must be positive"));
+            }
+            Ok(x.ln(working).expect("argument is positive"))
+        };
+        let logarithm = |ln: Decimal, base: i64| {
+            let base = Decimal::from(base).ln(working).expect("base is positive");
+            ln.checked_div(&base, precision)
+                .expect("logarithm of the base is not zero")
+        };
+        let nonzero = |function: &str, x: &Decimal, reason: &str| {
+            if x.is_zero() {
+                return Err(domain_error(function, reason));
+            }
+            Ok(())
+        };
+        let reciprocal = |x: &Decimal| {
+            Decimal::from(1)
+                .checked_div(x, precision)
+                .expect("argument is not zero")
+        };
+
+        match (name, args) {
+            ("sin", [x]) => Ok(x.sin(angle_mode, precision)),
+            ("cos", [x]) => Ok(x.cos(angle_mode, precision)),
+            ("tan", [x]) => x
+                .tan(angle_mode, precision)
+                .ok_or_else(|| domain_error("tan", "cosine of the argument is zero")),
+            // Only exact multiples of a quarter turn have an exact zero sine
+            // or cosine
+            ("sec", [x]) => {
+                let cos = x.cos(angle_mode, working);
+                nonzero("sec", &cos, "cosine of the argument is zero")?;
+                Ok(reciprocal(&cos))
+            }
+            ("csc", [x]) => {
+                let sin = x.sin(angle_mode, working);
+                nonzero("csc", &sin, "sine of the argument is zero")?;
+                Ok(reciprocal(&sin))
+            }
+            ("cot", [x]) => {
+                let sin = x.sin(angle_mode, working);
+                nonzero("cot", &sin, "tangent of the argument is zero")?;
+                Ok(x.cos(angle_mode, working)
+                    .checked_div(&sin, precision)
+                    .expect("sine is not zero"))
+            }
+            ("asin", [x]) => x
+                .asin(working)
+                .map(|a| a.from_radians(angle_mode, precision))
+                .ok_or_else(|| domain_error("asin", "argument must be between -1 and 1")),
+            ("acos", [x]) => x
+                .acos(working)
+                .map(|a| a.from_radians(angle_mode, precision))
+                .ok_or_else(|| domain_error("acos", "argument must be between -1 and 1")),
+            ("atan", [x]) => Ok(x.atan(working).from_radians(angle_mode, precision)),
+            ("atan2", [y, x]) => {
+                Ok(Decimal::atan2(y, x, working).from_radians(angle_mode, precision))
+            }
+
+            ("exp", [x]) => x
+                .exp(precision)
+                .ok_or_else(|| domain_error("exp", "result is out of range")),
+            ("ln", [x]) => Ok(positive("ln", x)?.round(precision)),
+            ("log10", [x]) => Ok(logarithm(positive("log10", x)?, 10)),
+            ("log2", [x]) => Ok(logarithm(positive("log2", x)?, 2)),
+            // log(x) is the common logarithm, log(b, x) the logarithm of x in base b.
+            ("log", [x]) => Ok(logarithm(positive("log", x)?, 10)),
+            ("log", [base, x]) => {
+                if base.is_zero() || base.is_negative() || *base == Decimal::from(1) {
+                    return Err(domain_error(
+                        "log",
+                        "base must be positive and not equal to 1",
+                    ));
+                }
+                let base = base.ln(working).expect("base is positive");
+                Ok(positive("log", x)?
+                    .checked_div(&base, precision)
+                    .expect("logarithm of the base is not zero"))
+            }
+
+            ("floor", [x]) => Ok(x.round_at(0, RoundingMode::Floor)),
+            ("ceil", [x]) => Ok(x.round_at(0, RoundingMode::Ceiling)),
+            ("round", [x]) => Ok(x.round_at(0, RoundingMode::HalfUp)),
+            ("trunc", [x]) => Ok(x.round_at(0, RoundingMode::Down)),
+            ("fract", [x]) => Ok(x - &x.round_at(0, RoundingMode::Down)),
+            ("abs", [x]) => Ok(x.abs()),
+            ("sign", [x]) => Ok(Decimal::from(match x {
+                x if x.is_zero() => 0,
+                x if x.is_negative() => -1,
+                _ => 1,
+            })),
+
+            ("sqrt", [x]) => x
+                .sqrt(precision)
+                .ok_or_else(|| domain_error("sqrt", "argument must not be negative")),
+            ("cbrt", [x]) => Ok(x.nth_root(3, precision).expect("odd roots are real")),
+            ("nthroot", [x, n]) => {
+                let n = n
+                    .to_integer()
+                    .and_then(|n| n.to_i32())
+                    .ok_or_else(|| domain_error("nthroot", "arguments must be integers"))?;
+                if n == 0 {
+                    return Err(domain_error("nthroot", "root degree must not be zero"));
+                }
+                let root = x
+                    .nth_root(n.unsigned_abs(), working)
+                    .ok_or_else(|| domain_error("nthroot", "even root of a negative number"))?;
+                if n > 0 {
+                    Ok(root.round(precision))
+                } else {
+                    nonzero("nthroot", &root, "negative root of zero")?;
+                    Ok(reciprocal(&root))
+                }
+            }
+            ("pow", [x, y]) => power("pow", x, y, precision),
+            ("min", [first, rest @ ..]) => Ok(rest.iter().fold(first, std::cmp::min).clone()),
+            ("max", [first, rest @ ..]) => Ok(rest.iter().fold(first, std::cmp::max).clone()),
+            ("sum", args) => Ok(args
+                .iter()
+                .fold(Decimal::zero(), |sum, x| (&sum + x).round(precision))),
+            ("product", args) => Ok(args.iter().fold(Decimal::from(1), |product, x| {
+                (&product * x).round(precision)
+            })),
+            _ => Err(EvaluatorError::UnknownFunction(name.to_string())),
+        }
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Decimal, EvaluatorError> {
+        let precision = self.precision;
+        match expr {
+            Expr::BinOp { op: Op::Equals, .. } => Err(EvaluatorError::EqualityInEval),
+            Expr::BinOp { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.evaluate_expr(lhs)?, self.evaluate_expr(rhs)?);
+                match op {
+                    Op::Add => Ok((&lhs + &rhs).round(precision)),
+                    Op::Subtract => Ok((&lhs - &rhs).round(precision)),
+                    Op::Multiply => bounded("*", (&lhs * &rhs).round(precision)),
+                    Op::Divide => lhs
+                        .checked_div(&rhs, precision)
+                        .ok_or_else(|| domain_error("/", "division by zero"))
+                        .and_then(|quotient| bounded("/", quotient)),
+                    Op::Modulo => lhs
+                        .checked_rem(&rhs, precision)
+                        .map(|remainder| remainder.abs())
+                        .ok_or_else(|| domain_error("%", "division by zero")),
+                    Op::Power => power("^", &lhs, &rhs, precision),
+                    Op::Equals => Err(EvaluatorError::EqualityInEval),
+                    Op::Convert => Err(domain_error(
+                        "to",
+                        "only quantities with units can be converted",
+                    )),
+                }
+            }
+            Expr::Number(val) => from_f64("number", *val, precision),
+            Expr::Rational(val) => Ok(Decimal::from_rational(val, precision)),
+            Expr::Constant(constant) => self.constant(*constant),
+            Expr::Matrix(_) => Err(domain_error("[]", "matrices have no scalar value")),
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(-self.evaluate_expr(op)?),
+            Expr::Function { name, args } => {
+                if let Some(series) = expr.as_series() {
+                    return self.evaluate_series(series);
+                }
+                if let Some(degrees) = self.exact_angle(name, args) {
+                    return self.call_builtin(name, &[degrees], AngleMode::Degrees);
+                }
+                let args = args
+                    .iter()
+                    .map(|arg| self.evaluate_expr(arg))
+                    .collect::<Result<Vec<Decimal>, EvaluatorError>>()?;
+                self.call(name, args)
+            }
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => {
+                let value = self.get_variable(variable)?;
+                let coefficient = from_f64(variable, *coefficient, precision)?;
+                let exponent = from_f64(variable, *exponent, precision)?;
+                let power = power("^", &value, &exponent, precision)?;
+                bounded("^", (&coefficient * &power).round(precision))
+            }
+        }
+    }
+}
+
+/// Evaluates `expression` with decimals of the given precision instead of
+/// `f64`, for results that need more than 15 significant digits or
+/// predictable decimal rounding.
+pub fn evaluate_decimal(expression: &str, precision: Precision) -> Result<Decimal> {
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+    Ok(evaluate_decimal_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+        precision,
+    )?)
+}
+
+/// Evaluates an already parsed expression with decimals, resolving its
+/// variables from `ctx`. Every intermediate result is rounded to `precision`.
+///
+/// Functions registered on the context only work with `f64` and can't be
+/// called, but user functions can.
+pub fn evaluate_decimal_with(
+    expr: &Expr,
+    ctx: &Context,
+    precision: Precision,
+) -> Result<Decimal, EvaluatorError> {
+    DecimalEvaluator::new(ctx, precision).evaluate_expr(expr)
+}
@@ -1,95 +1,150 @@
-use anyhow::{bail, Result};
-
-use crate::error::EvaluatorError;
-use crate::math::{deg_to_rad, round};
-use crate::parser::{parse, Expr, Op};
-
-fn evaluate_expr(expr: Expr) -> Result<f64> {
-    match expr {
-        Expr::BinOp { lhs, op, rhs } => match op {
-            Op::Add => Ok(evaluate_expr(*lhs)? + evaluate_expr(*rhs)?),
-            Op::Subtract => Ok(evaluate_expr(*lhs)? - evaluate_expr(*rhs)?),
-            Op::Multiply => Ok(evaluate_expr(*lhs)? * evaluate_expr(*rhs)?),
-            Op::Divide => Ok(evaluate_expr(*lhs)? / evaluate_expr(*rhs)?),
-            Op::Modulo => Ok((evaluate_expr(*lhs)? % evaluate_expr(*rhs)?).abs()),
-            Op::Power => Ok(evaluate_expr(*lhs)?.powf(evaluate_expr(*rhs)?)),
-            Op::Equals => bail!(EvaluatorError::EqualityInEval),
-        },
-        Expr::Number(val) => Ok(val),
-        Expr::UnaryMinus(op) => Ok(-1.0 * evaluate_expr(*op)?),
-        Expr::Function { name, args } => match name.as_str() {
-            "cos" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).cos())
-            }
-            "sin" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).sin())
-            }
-            "tan" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).tan())
-            }
-            "floor" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.floor())
-            }
-            "ceil" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.ceil())
-            }
-            "round" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.round())
-            }
-            "trunc" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.trunc())
-            }
-            "fract" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.fract())
-            }
-            "sqrt" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.sqrt())
-            }
-            "pow" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.powf(evaluate_expr(*arg2)?))
-            }
-            "min" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.min(evaluate_expr(*arg2)?))
-            }
-            "max" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.max(evaluate_expr(*arg2)?))
-            }
-            name => bail!("Syntax error: no function name found"),
+use crate::error::{EvaluatorError, Result};
+use crate::math::{as_integer, round};
+use crate::parser::{parse_with, Constant, Expr, Op, ParserOptions, SeriesKind, Span};
+
+use super::frames::{Frames, UserFunctions};
+use super::Context;
+
+/// Attaches `span` to evaluator errors that don't know their location yet.
+pub(super) fn locate(error: EvaluatorError, span: Span) -> EvaluatorError {
+    match error {
+        EvaluatorError::At { .. } => error,
+        error => EvaluatorError::At {
+            span,
+            error: Box::new(error),
         },
-        _ => todo!(),
+    }
+}
+
+/// Walks an expression tree, keeping track of user function calls in progress.
+struct Evaluator<'a> {
+    ctx: &'a Context,
+    frames: Frames<f64>,
+}
+
+impl<'a> UserFunctions<'a> for Evaluator<'a> {
+    type Value = f64;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<f64> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<f64, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<f64>) -> Result<f64, EvaluatorError> {
+        self.ctx
+            .functions()
+            .call(name, &args, self.ctx.angle_mode())
+    }
+
+    fn integer(&self, n: i64) -> f64 {
+        n as f64
+    }
+
+    fn as_integer(&self, value: &f64) -> Option<i64> {
+        as_integer(*value)
+    }
+
+    fn accumulate(&self, kind: SeriesKind, total: f64, term: f64) -> Result<f64, EvaluatorError> {
+        Ok(match kind {
+            SeriesKind::Sum => total + term,
+            SeriesKind::Product => total * term,
+        })
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(ctx: &'a Context) -> Self {
+        Self {
+            ctx,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<f64> {
+        self.frames
+            .get(name)
+            .copied()
+            .or_else(|| self.ctx.get_variable(name))
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<f64, EvaluatorError> {
+        match expr {
+            Expr::BinOp { lhs, op, rhs } => match op {
+                Op::Add => Ok(self.evaluate_expr(lhs)? + self.evaluate_expr(rhs)?),
+                Op::Subtract => Ok(self.evaluate_expr(lhs)? - self.evaluate_expr(rhs)?),
+                Op::Multiply => Ok(self.evaluate_expr(lhs)? * self.evaluate_expr(rhs)?),
+                Op::Divide => Ok(self.evaluate_expr(lhs)? / self.evaluate_expr(rhs)?),
+                Op::Modulo => Ok((self.evaluate_expr(lhs)? % self.evaluate_expr(rhs)?).abs()),
+                Op::Power => Ok(self.evaluate_expr(lhs)?.powf(self.evaluate_expr(rhs)?)),
+                Op::Equals => Err(EvaluatorError::EqualityInEval),
+                Op::Convert => Err(EvaluatorError::Domain {
+                    function: "to".to_string(),
+                    reason: "only quantities with units can be converted".to_string(),
+                }),
+            },
+            Expr::Number(val) => Ok(*val),
+            Expr::Rational(val) => Ok(val.to_f64()),
+            Expr::Constant(Constant::ImaginaryUnit) => Err(EvaluatorError::Domain {
+                function: "i".to_string(),
+                reason: "complex numbers have no real value".to_string(),
+            }),
+            Expr::Constant(constant @ Constant::Unit(_)) => Err(EvaluatorError::Domain {
+                function: constant.name().to_string(),
+                reason: "hypercomplex numbers have no real value".to_string(),
+            }),
+            Expr::Constant(constant) => Ok(constant.value()),
+            Expr::Matrix(_) => Err(EvaluatorError::Domain {
+                function: "[]".to_string(),
+                reason: "matrices have no scalar value".to_string(),
+            }),
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(-self.evaluate_expr(op)?),
+            Expr::Function { name, args } => match expr.as_series() {
+                Some(series) => self.evaluate_series(series),
+                None => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.evaluate_expr(arg))
+                        .collect::<Result<Vec<f64>, EvaluatorError>>()?;
+                    self.call(name, args)
+                }
+            },
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => match self.get_variable(variable) {
+                Some(value) => Ok(coefficient * value.powf(*exponent)),
+                None => Err(EvaluatorError::UnknownVariable(variable.to_owned())),
+            },
+        }
     }
 }
 
 pub fn evaluate(expression: &str) -> Result<f64> {
-    Ok(round(evaluate_expr(parse(expression)?)?, 15))
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+    Ok(evaluate_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+    )?)
+}
+
+/// Evaluates an already parsed expression, resolving its variables from `ctx`.
+///
+/// Parsing once and calling this repeatedly with different contexts avoids
+/// re-parsing the same formula for every set of inputs.
+pub fn evaluate_with(expr: &Expr, ctx: &Context) -> Result<f64, EvaluatorError> {
+    Ok(round(Evaluator::new(ctx).evaluate_expr(expr)?, 15))
 }
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::error::EvaluatorError;
+use crate::parser::{Expr, Series, SeriesKind};
+
+use super::builtins::domain_error;
+use super::{Arity, Context};
+
+/// Largest number of terms a series may have.
+const MAX_SERIES_TERMS: i64 = 1_000_000;
+
+/// Parameter bindings of the user function calls in progress, innermost last.
+pub(super) struct Frames<T> {
+    bindings: Vec<HashMap<String, T>>,
+    call_stack: Vec<String>,
+}
+
+impl<T> Frames<T> {
+    pub(super) fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            call_stack: Vec::new(),
+        }
+    }
+
+    /// The value of the parameter `name` of the innermost call. Function
+    /// bodies only see their own parameters and the global variables, never
+    /// the parameters of their caller.
+    pub(super) fn get(&self, name: &str) -> Option<&T> {
+        self.bindings.last().and_then(|frame| frame.get(name))
+    }
+
+    /// Pushes a copy of the innermost frame, so bindings made with `bind`
+    /// shadow variables and parameters only until `leave_scope`.
+    pub(super) fn enter_scope(&mut self)
+    where
+        T: Clone,
+    {
+        let frame = self.bindings.last().cloned().unwrap_or_default();
+        self.bindings.push(frame);
+    }
+
+    pub(super) fn bind(&mut self, name: &str, value: T) {
+        self.bindings
+            .last_mut()
+            .expect("a scope was entered")
+            .insert(name.to_string(), value);
+    }
+
+    pub(super) fn leave_scope(&mut self) {
+        self.bindings.pop();
+    }
+}
+
+/// An evaluator over values of type `Value` that can call user functions and
+/// bind the index of a series.
+pub(super) trait UserFunctions<'a> {
+    type Value: Clone;
+
+    fn ctx(&self) -> &'a Context;
+
+    fn frames(&mut self) -> &mut Frames<Self::Value>;
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Self::Value, EvaluatorError>;
+
+    /// Calls the built-in or host function `name`.
+    fn builtin(
+        &mut self,
+        name: &str,
+        args: Vec<Self::Value>,
+    ) -> Result<Self::Value, EvaluatorError>;
+
+    /// The integer `n` as a value.
+    fn integer(&self, n: i64) -> Self::Value;
+
+    /// The integer `value` is, if it is one.
+    fn as_integer(&self, value: &Self::Value) -> Option<i64>;
+
+    /// Adds `term` to the sum or multiplies it into the product `total`.
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Self::Value,
+        term: Self::Value,
+    ) -> Result<Self::Value, EvaluatorError>;
+
+    /// Calls the user function `name` with its parameters bound to `args`,
+    /// or the built-in function of that name if there is no user function.
+    fn call(&mut self, name: &str, args: Vec<Self::Value>) -> Result<Self::Value, EvaluatorError> {
+        let Some(function) = self.ctx().get_user_function(name) else {
+            return self.builtin(name, args);
+        };
+
+        if function.params.len() != args.len() {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: Arity::Fixed(function.params.len()),
+                found: args.len(),
+            });
+        }
+
+        let frames = self.frames();
+        if frames.call_stack.iter().any(|caller| caller == name) {
+            return Err(EvaluatorError::RecursiveFunction(name.to_string()));
+        }
+
+        let frame = function.params.iter().cloned().zip(args).collect();
+        frames.bindings.push(frame);
+        frames.call_stack.push(name.to_string());
+
+        let result = self.evaluate(&function.body);
+
+        let frames = self.frames();
+        frames.call_stack.pop();
+        frames.bindings.pop();
+        result
+    }
+
+    /// Adds or multiplies the body of `series` with its index bound to each
+    /// integer between the bounds in turn. The first term starts the total,
+    /// so sums of quantities with units don't start from a plain zero.
+    fn evaluate_series(&mut self, series: Series) -> Result<Self::Value, EvaluatorError> {
+        let name = series.kind.name();
+        let from = self.evaluate(series.from)?;
+        let to = self.evaluate(series.to)?;
+        let (Some(from), Some(to)) = (self.as_integer(&from), self.as_integer(&to)) else {
+            return Err(domain_error(name, "bounds must be integers"));
+        };
+        if to.saturating_sub(from) >= MAX_SERIES_TERMS {
+            return Err(domain_error(
+                name,
+                &format!("more than {MAX_SERIES_TERMS} terms"),
+            ));
+        }
+
+        self.frames().enter_scope();
+        let result = (from..=to).try_fold(None, |total, k| {
+            let index = self.integer(k);
+            self.frames().bind(series.index, index);
+            let term = self.evaluate(series.body)?;
+            match total {
+                Some(total) => self.accumulate(series.kind, total, term).map(Some),
+                None => Ok(Some(term)),
+            }
+        });
+        self.frames().leave_scope();
+
+        let empty = match series.kind {
+            SeriesKind::Sum => 0,
+            SeriesKind::Product => 1,
+        };
+        Ok(result?.unwrap_or_else(|| self.integer(empty)))
+    }
+}
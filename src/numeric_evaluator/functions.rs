@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::error::{EvaluatorError, Result};
+use crate::math::AngleMode;
+
+/// Signature shared by every function callable from an expression.
+pub type NativeFunction = dyn Fn(&[f64]) -> Result<f64, EvaluatorError>;
+
+/// Number of arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly `n` arguments.
+    Fixed(usize),
+    /// Between `min` and `max` arguments, both inclusive.
+    Range(usize, usize),
+    /// At least `min` arguments.
+    Variadic(usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Fixed(n) => count == n,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+            Arity::Variadic(min) => count >= min,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Fixed(n) => write!(f, "{n}"),
+            Arity::Range(min, max) => write!(f, "{min} to {max}"),
+            Arity::Variadic(min) => write!(f, "at least {min}"),
+        }
+    }
+}
+
+/// Which side of a function, if any, is an angle that follows the [`AngleMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleConversion {
+    #[default]
+    None,
+    /// The arguments are angles, e.g. `sin`. They are converted to radians
+    /// before the body is called.
+    Input,
+    /// The result is an angle, e.g. `asin`. It is converted from radians
+    /// after the body is called.
+    Output,
+}
+
+#[derive(Clone)]
+pub struct Function {
+    arity: Arity,
+    angle: AngleConversion,
+    body: Rc<NativeFunction>,
+}
+
+impl Function {
+    pub fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    pub fn angle_conversion(&self) -> AngleConversion {
+        self.angle
+    }
+}
+
+/// Lookup table from function names to their implementations.
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, Function>,
+}
+
+impl FunctionRegistry {
+    /// Creates a registry without any functions in it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry containing every built-in function.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        super::builtins::register(&mut registry);
+        registry
+    }
+
+    /// Registers `body` under `name`, replacing any function with the same name.
+    pub fn register<F>(&mut self, name: &str, arity: Arity, body: F)
+    where
+        F: Fn(&[f64]) -> Result<f64, EvaluatorError> + 'static,
+    {
+        self.register_angular(name, arity, AngleConversion::None, body);
+    }
+
+    /// Like [`FunctionRegistry::register`], but for functions whose arguments
+    /// or result are angles. `body` always works in radians.
+    pub fn register_angular<F>(&mut self, name: &str, arity: Arity, angle: AngleConversion, body: F)
+    where
+        F: Fn(&[f64]) -> Result<f64, EvaluatorError> + 'static,
+    {
+        self.functions.insert(
+            name.to_string(),
+            Function {
+                arity,
+                angle,
+                body: Rc::new(body),
+            },
+        );
+    }
+
+    pub fn unregister(&mut self, name: &str) -> Option<Function> {
+        self.functions.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Calls the function `name` after checking that `args` matches its arity.
+    /// Angles are interpreted and returned in the unit given by `angle_mode`.
+    pub fn call(
+        &self,
+        name: &str,
+        args: &[f64],
+        angle_mode: AngleMode,
+    ) -> Result<f64, EvaluatorError> {
+        let Some(function) = self.get(name) else {
+            return Err(EvaluatorError::UnknownFunction(name.to_string()));
+        };
+
+        if !function.arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: function.arity,
+                found: args.len(),
+            });
+        }
+
+        let result = match function.angle {
+            AngleConversion::None => (function.body)(args)?,
+            AngleConversion::Input => {
+                let args: Vec<f64> = args.iter().map(|&a| angle_mode.to_radians(a)).collect();
+                (function.body)(&args)?
+            }
+            AngleConversion::Output => angle_mode.from_radians((function.body)(args)?),
+        };
+
+        // A NaN that wasn't passed in means the arguments were outside of the
+        // function's domain.
+        if result.is_nan() && !args.iter().any(|arg| arg.is_nan()) {
+            return Err(EvaluatorError::Domain {
+                function: name.to_string(),
+                reason: "result is not a real number".to_string(),
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+impl fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        f.debug_struct("FunctionRegistry")
+            .field("functions", &names)
+            .finish()
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::{EvaluatorError, Result};
+use crate::math::{as_integer, round, Sedenion};
+use crate::parser::{parse_with, Expr, Op, ParserOptions, SeriesKind};
+
+use super::builtins::domain_error;
+use super::evaluator::locate;
+use super::frames::{Frames, UserFunctions};
+use super::{Arity, Context};
+
+/// The result, or a domain error for `function` if it isn't a finite number.
+fn finite(function: &str, z: Sedenion) -> Result<Sedenion, EvaluatorError> {
+    if z.is_finite() {
+        Ok(z)
+    } else {
+        Err(domain_error(function, "result is not a finite number"))
+    }
+}
+
+/// `z^w`. Hypercomplex numbers only have integer powers, real numbers also
+/// have real powers of non-negative bases.
+fn power(z: Sedenion, w: Sedenion) -> Result<Sedenion, EvaluatorError> {
+    let Some(y) = w.real() else {
+        return Err(domain_error("^", "exponent must be real"));
+    };
+    match z.real() {
+        _ if y.fract() == 0.0 && y.abs() <= i32::MAX as f64 => z
+            .powi(y as i32)
+            .ok_or_else(|| domain_error("^", "division by zero")),
+        Some(x) if x >= 0.0 => Ok(Sedenion::from_real(x.powf(y))),
+        _ => Err(domain_error("^", "exponent must be an integer")),
+    }
+}
+
+/// Number of arguments of the functions with a hypercomplex implementation.
+fn builtin_arity(name: &str) -> Option<Arity> {
+    match name {
+        "re" | "abs" | "norm" | "conj" | "inv" => Some(Arity::Fixed(1)),
+        _ => None,
+    }
+}
+
+/// Walks an expression tree like the `f64` evaluator, but with sedenions.
+/// Complex numbers, quaternions and octonions are their subalgebras, so they
+/// evaluate the same here.
+struct HypercomplexEvaluator<'a> {
+    ctx: &'a Context,
+    frames: Frames<Sedenion>,
+}
+
+impl<'a> UserFunctions<'a> for HypercomplexEvaluator<'a> {
+    type Value = Sedenion;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<Sedenion> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Sedenion, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Sedenion>) -> Result<Sedenion, EvaluatorError> {
+        self.call_builtin(name, &args)
+    }
+
+    fn integer(&self, n: i64) -> Sedenion {
+        Sedenion::from_real(n as f64)
+    }
+
+    fn as_integer(&self, value: &Sedenion) -> Option<i64> {
+        value.real().and_then(as_integer)
+    }
+
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Sedenion,
+        term: Sedenion,
+    ) -> Result<Sedenion, EvaluatorError> {
+        Ok(match kind {
+            SeriesKind::Sum => total + term,
+            SeriesKind::Product => total * term,
+        })
+    }
+}
+
+impl<'a> HypercomplexEvaluator<'a> {
+    fn new(ctx: &'a Context) -> Self {
+        Self {
+            ctx,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<Sedenion> {
+        self.frames
+            .get(name)
+            .copied()
+            .or_else(|| self.ctx.get_variable(name).map(Sedenion::from_real))
+    }
+
+    /// Calls a built-in function. Functions without a hypercomplex
+    /// implementation, and host functions, accept real arguments only.
+    fn call_builtin(&self, name: &str, args: &[Sedenion]) -> Result<Sedenion, EvaluatorError> {
+        let Some(arity) = builtin_arity(name) else {
+            let Some(args) = args
+                .iter()
+                .map(Sedenion::real)
+                .collect::<Option<Vec<f64>>>()
+            else {
+                return Err(domain_error(name, "arguments must be real"));
+            };
+            let result = self
+                .ctx
+                .functions()
+                .call(name, &args, self.ctx.angle_mode())?;
+            return Ok(Sedenion::from_real(result));
+        };
+        if !arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            });
+        }
+
+        let result = match (name, args) {
+            ("re", [z]) => Sedenion::from_real(z.re()),
+            ("abs" | "norm", [z]) => Sedenion::from_real(z.norm()),
+            ("conj", [z]) => z.conj(),
+            ("inv", [z]) => z
+                .inv()
+                .ok_or_else(|| domain_error("inv", "zero has no inverse"))?,
+            _ => return Err(EvaluatorError::UnknownFunction(name.to_string())),
+        };
+        finite(name, result)
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Sedenion, EvaluatorError> {
+        match expr {
+            Expr::BinOp { op: Op::Equals, .. } => Err(EvaluatorError::EqualityInEval),
+            Expr::BinOp { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.evaluate_expr(lhs)?, self.evaluate_expr(rhs)?);
+                match op {
+                    Op::Add => Ok(lhs + rhs),
+                    Op::Subtract => Ok(lhs - rhs),
+                    Op::Multiply => Ok(lhs * rhs),
+                    // Right division, a/b is a*inv(b)
+                    Op::Divide => match rhs.inv() {
+                        Some(inverse) => finite("/", lhs * inverse),
+                        None => Err(domain_error("/", "division by zero")),
+                    },
+                    Op::Modulo => match (lhs.real(), rhs.real()) {
+                        (Some(a), Some(b)) => Ok(Sedenion::from_real((a % b).abs())),
+                        _ => Err(domain_error("%", "arguments must be real")),
+                    },
+                    Op::Power => finite("^", power(lhs, rhs)?),
+                    Op::Equals => Err(EvaluatorError::EqualityInEval),
+                    Op::Convert => Err(domain_error(
+                        "to",
+                        "only quantities with units can be converted",
+                    )),
+                }
+            }
+            Expr::Number(val) => Ok(Sedenion::from_real(*val)),
+            Expr::Rational(val) => Ok(Sedenion::from_real(val.to_f64())),
+            Expr::Constant(constant) => match constant.unit_index() {
+                Some(index) => Ok(Sedenion::unit(index).expect("sedenions have 15 units")),
+                None => Ok(Sedenion::from_real(constant.value())),
+            },
+            Expr::Matrix(_) => Err(domain_error("[]", "matrices have no scalar value")),
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(-self.evaluate_expr(op)?),
+            Expr::Function { name, args } => match expr.as_series() {
+                Some(series) => self.evaluate_series(series),
+                None => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.evaluate_expr(arg))
+                        .collect::<Result<Vec<Sedenion>, EvaluatorError>>()?;
+                    self.call(name, args)
+                }
+            },
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => match self.get_variable(variable) {
+                Some(value) => {
+                    let power = power(value, Sedenion::from_real(*exponent))?;
+                    Ok(power.scale(*coefficient))
+                }
+                None => Err(EvaluatorError::UnknownVariable(variable.to_owned())),
+            },
+        }
+    }
+}
+
+/// Evaluates `expression` over the sedenions, reading `i`, `j`, `k` and
+/// `e1`..`e15` as basis units.
+pub fn evaluate_hypercomplex(expression: &str) -> Result<Sedenion> {
+    let options = ParserOptions {
+        spans: true,
+        hypercomplex: true,
+        ..Default::default()
+    };
+    Ok(evaluate_hypercomplex_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+    )?)
+}
+
+/// Evaluates an already parsed expression over the sedenions, resolving its
+/// variables from `ctx`. Products keep their order, so `j*i` is `-k`.
+pub fn evaluate_hypercomplex_with(expr: &Expr, ctx: &Context) -> Result<Sedenion, EvaluatorError> {
+    let z = HypercomplexEvaluator::new(ctx).evaluate_expr(expr)?;
+    Ok(z.map(|x| round(x, 15)))
+}
@@ -0,0 +1,447 @@
+use std::fmt;
+
+use crate::error::{EvaluatorError, Result};
+use crate::math::{as_integer, round, Matrix};
+use crate::parser::{parse_with, Expr, Op, ParserOptions, SeriesKind};
+
+use super::builtins::domain_error;
+use super::evaluator::locate;
+use super::frames::{Frames, UserFunctions};
+use super::{Arity, Context};
+
+/// A scalar or a matrix. Matrices with a single entry are scalars.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(f64),
+    Matrix(Matrix),
+}
+
+impl Value {
+    /// The value as a matrix, scalars being 1×1.
+    pub fn into_matrix(self) -> Matrix {
+        match self {
+            Value::Scalar(x) => Matrix::new(1, 1, vec![x]),
+            Value::Matrix(matrix) => matrix,
+        }
+    }
+
+    fn map(self, f: impl Fn(f64) -> f64) -> Value {
+        match self {
+            Value::Scalar(x) => Value::Scalar(f(x)),
+            Value::Matrix(matrix) => Value::Matrix(matrix.map(f)),
+        }
+    }
+}
+
+impl From<Matrix> for Value {
+    fn from(matrix: Matrix) -> Self {
+        match matrix.entries() {
+            [x] => Value::Scalar(*x),
+            _ => Value::Matrix(matrix),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Scalar(x) => write!(f, "{x}"),
+            Value::Matrix(matrix) => write!(f, "{matrix}"),
+        }
+    }
+}
+
+/// `rows`×`cols`, for error messages.
+fn shape(matrix: &Matrix) -> String {
+    format!("{}x{}", matrix.rows(), matrix.cols())
+}
+
+fn mismatch(operation: &str, reason: String) -> EvaluatorError {
+    EvaluatorError::DimensionMismatch {
+        operation: operation.to_string(),
+        reason,
+    }
+}
+
+/// Error for functions that need a square matrix.
+fn not_square(operation: &str, matrix: &Matrix) -> EvaluatorError {
+    mismatch(
+        operation,
+        format!("expected a square matrix, got {}", shape(matrix)),
+    )
+}
+
+/// Error for functions that need vectors.
+fn not_vectors(operation: &str, a: &Matrix, b: &Matrix) -> EvaluatorError {
+    mismatch(
+        operation,
+        format!(
+            "expected two vectors of the same length, got {} and {}",
+            shape(a),
+            shape(b)
+        ),
+    )
+}
+
+/// `a op b` on scalars, like the `f64` evaluator.
+fn scalar_op(op: Op, a: f64, b: f64) -> f64 {
+    match op {
+        Op::Add => a + b,
+        Op::Subtract => a - b,
+        Op::Multiply => a * b,
+        Op::Divide => a / b,
+        Op::Modulo => (a % b).abs(),
+        Op::Power => a.powf(b),
+        Op::Equals | Op::Convert => f64::NAN,
+    }
+}
+
+fn symbol(op: Op) -> &'static str {
+    match op {
+        Op::Add => "+",
+        Op::Subtract => "-",
+        Op::Multiply => "*",
+        Op::Divide => "/",
+        Op::Modulo => "%",
+        Op::Power => "^",
+        Op::Equals => "=",
+        Op::Convert => "to",
+    }
+}
+
+/// `a * inv(b)`, dividing by a square matrix from the right. It is solved as
+/// `transpose(b) transpose(x) = transpose(a)` rather than inverting `b`.
+fn right_divide(a: &Matrix, b: &Matrix) -> Result<Matrix, EvaluatorError> {
+    if !b.is_square() || a.cols() != b.rows() {
+        return Err(mismatch(
+            "/",
+            format!("can't divide {} by {}", shape(a), shape(b)),
+        ));
+    }
+    let x = b
+        .transpose()
+        .solve(&a.transpose())
+        .ok_or_else(|| domain_error("/", "matrix is singular"))?;
+    Ok(x.transpose())
+}
+
+/// `lhs op rhs`. Matrices add and subtract entry by entry and multiply as
+/// matrices, `A/B` is `A*inv(B)` and `A^n` is a matrix power. Other
+/// operations with a scalar apply to every entry. The entry by entry product
+/// of two matrices is the `hadamard` function.
+fn binary(op: Op, lhs: Value, rhs: Value) -> Result<Value, EvaluatorError> {
+    let operation = symbol(op);
+    if op == Op::Convert {
+        return Err(domain_error(
+            operation,
+            "only quantities with units can be converted",
+        ));
+    }
+    match (lhs, rhs) {
+        (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(scalar_op(op, a, b))),
+        (Value::Matrix(a), Value::Matrix(b)) => match op {
+            Op::Add | Op::Subtract => a.zip_with(&b, |x, y| scalar_op(op, x, y)).ok_or_else(|| {
+                mismatch(
+                    operation,
+                    format!(
+                        "expected matrices of the same shape, got {} and {}",
+                        shape(&a),
+                        shape(&b)
+                    ),
+                )
+            }),
+            Op::Multiply => a.mul(&b).ok_or_else(|| {
+                mismatch(
+                    operation,
+                    format!("can't multiply {} by {}", shape(&a), shape(&b)),
+                )
+            }),
+            Op::Divide => right_divide(&a, &b),
+            Op::Modulo | Op::Power | Op::Equals | Op::Convert => {
+                Err(domain_error(operation, "not defined between two matrices"))
+            }
+        }
+        .map(Value::from),
+        (Value::Matrix(a), Value::Scalar(n)) if op == Op::Power => {
+            if !a.is_square() {
+                return Err(not_square(operation, &a));
+            }
+            if n.fract() != 0.0 {
+                return Err(domain_error(operation, "matrix powers must be integers"));
+            }
+            if n < i32::MIN as f64 || n > i32::MAX as f64 {
+                return Err(domain_error(operation, "exponent is out of range"));
+            }
+            let power = a
+                .powi(n as i32)
+                .ok_or_else(|| domain_error(operation, "matrix is singular"))?;
+            // Entries that overflow turn into infinities and NaNs on the way
+            if a.entries().iter().all(|x| x.is_finite())
+                && !power.entries().iter().all(|x| x.is_finite())
+            {
+                return Err(domain_error(operation, "result is not a finite number"));
+            }
+            Ok(Value::from(power))
+        }
+        (Value::Matrix(a), Value::Scalar(b)) => Ok(Value::from(a.map(|x| scalar_op(op, x, b)))),
+        (Value::Scalar(_), Value::Matrix(_)) if op == Op::Power => {
+            Err(domain_error(operation, "exponent must be a scalar"))
+        }
+        (Value::Scalar(a), Value::Matrix(b)) if op == Op::Divide => Ok(Value::from(
+            right_divide(&Matrix::identity(b.rows()), &b)?.map(|x| a * x),
+        )),
+        (Value::Scalar(a), Value::Matrix(b)) => Ok(Value::from(b.map(|x| scalar_op(op, a, x)))),
+    }
+}
+
+/// Number of arguments of the linear algebra functions.
+fn builtin_arity(name: &str) -> Option<Arity> {
+    match name {
+        "det" | "inv" | "transpose" | "rank" | "trace" | "norm" => Some(Arity::Fixed(1)),
+        "dot" | "cross" | "hadamard" | "solve" => Some(Arity::Fixed(2)),
+        _ => None,
+    }
+}
+
+/// Walks an expression tree like the `f64` evaluator, but with matrices.
+struct MatrixEvaluator<'a> {
+    ctx: &'a Context,
+    frames: Frames<Value>,
+}
+
+impl<'a> UserFunctions<'a> for MatrixEvaluator<'a> {
+    type Value = Value;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<Value> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Value, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Value>) -> Result<Value, EvaluatorError> {
+        self.call_builtin(name, args)
+    }
+
+    fn integer(&self, n: i64) -> Value {
+        Value::Scalar(n as f64)
+    }
+
+    fn as_integer(&self, value: &Value) -> Option<i64> {
+        match value {
+            Value::Scalar(x) => as_integer(*x),
+            Value::Matrix(_) => None,
+        }
+    }
+
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Value,
+        term: Value,
+    ) -> Result<Value, EvaluatorError> {
+        match kind {
+            SeriesKind::Sum => binary(Op::Add, total, term),
+            SeriesKind::Product => binary(Op::Multiply, total, term),
+        }
+    }
+}
+
+impl<'a> MatrixEvaluator<'a> {
+    fn new(ctx: &'a Context) -> Self {
+        Self {
+            ctx,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<Value> {
+        self.frames
+            .get(name)
+            .cloned()
+            .or_else(|| self.ctx.get_variable(name).map(Value::Scalar))
+    }
+
+    /// Calls a built-in function. The linear algebra functions take scalars
+    /// as 1×1 matrices, every other function takes scalars only.
+    fn call_builtin(&self, name: &str, args: Vec<Value>) -> Result<Value, EvaluatorError> {
+        let Some(arity) = builtin_arity(name) else {
+            let args = args
+                .into_iter()
+                .map(|arg| match arg {
+                    Value::Scalar(x) => Ok(x),
+                    Value::Matrix(_) => Err(domain_error(name, "arguments must be scalars")),
+                })
+                .collect::<Result<Vec<f64>, EvaluatorError>>()?;
+            let result = self
+                .ctx
+                .functions()
+                .call(name, &args, self.ctx.angle_mode())?;
+            return Ok(Value::Scalar(result));
+        };
+        if !arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            });
+        }
+
+        let args: Vec<Matrix> = args.into_iter().map(Value::into_matrix).collect();
+        let result = match (name, args.as_slice()) {
+            ("det", [a]) => Matrix::new(1, 1, vec![a.det().ok_or_else(|| not_square(name, a))?]),
+            ("inv", [a]) => {
+                if !a.is_square() {
+                    return Err(not_square(name, a));
+                }
+                a.inv()
+                    .ok_or_else(|| domain_error(name, "matrix is singular"))?
+            }
+            ("transpose", [a]) => a.transpose(),
+            ("rank", [a]) => Matrix::new(1, 1, vec![a.rank() as f64]),
+            ("trace", [a]) => {
+                Matrix::new(1, 1, vec![a.trace().ok_or_else(|| not_square(name, a))?])
+            }
+            ("norm", [a]) => Matrix::new(1, 1, vec![a.norm()]),
+            ("dot", [a, b]) => {
+                Matrix::new(1, 1, vec![a.dot(b).ok_or_else(|| not_vectors(name, a, b))?])
+            }
+            ("cross", [a, b]) => a.cross(b).ok_or_else(|| {
+                mismatch(
+                    name,
+                    format!(
+                        "expected two vectors of length 3, got {} and {}",
+                        shape(a),
+                        shape(b)
+                    ),
+                )
+            })?,
+            ("hadamard", [a, b]) => a.zip_with(b, |x, y| x * y).ok_or_else(|| {
+                mismatch(
+                    name,
+                    format!(
+                        "expected matrices of the same shape, got {} and {}",
+                        shape(a),
+                        shape(b)
+                    ),
+                )
+            })?,
+            ("solve", [a, b]) => {
+                if !a.is_square() {
+                    return Err(not_square(name, a));
+                }
+                // A row vector right hand side gives a row vector solution
+                let row = b.rows() == 1 && a.rows() != 1;
+                let rhs = if row { b.transpose() } else { b.clone() };
+                if rhs.rows() != a.rows() {
+                    return Err(mismatch(
+                        name,
+                        format!("can't solve {} against {}", shape(a), shape(b)),
+                    ));
+                }
+                let x = a
+                    .solve(&rhs)
+                    .ok_or_else(|| domain_error(name, "matrix is singular"))?;
+                if row {
+                    x.transpose()
+                } else {
+                    x
+                }
+            }
+            _ => return Err(EvaluatorError::UnknownFunction(name.to_string())),
+        };
+        Ok(Value::from(result))
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Value, EvaluatorError> {
+        match expr {
+            Expr::BinOp { op: Op::Equals, .. } => Err(EvaluatorError::EqualityInEval),
+            Expr::BinOp { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.evaluate_expr(lhs)?, self.evaluate_expr(rhs)?);
+                binary(*op, lhs, rhs)
+            }
+            Expr::Matrix(rows) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|entry| match self.evaluate_expr(entry)? {
+                                Value::Scalar(x) => Ok(x),
+                                Value::Matrix(_) => {
+                                    Err(domain_error("[]", "entries must be scalars"))
+                                }
+                            })
+                            .collect::<Result<Vec<f64>, EvaluatorError>>()
+                    })
+                    .collect::<Result<Vec<Vec<f64>>, EvaluatorError>>()?;
+                let matrix = Matrix::from_rows(rows)
+                    .ok_or_else(|| mismatch("[]", "rows must have the same length".to_string()))?;
+                Ok(Value::from(matrix))
+            }
+            Expr::Number(val) => Ok(Value::Scalar(*val)),
+            Expr::Rational(val) => Ok(Value::Scalar(val.to_f64())),
+            Expr::Constant(constant) => match constant.unit_index() {
+                Some(_) => Err(domain_error(
+                    constant.name(),
+                    "imaginary units have no matrix value",
+                )),
+                None => Ok(Value::Scalar(constant.value())),
+            },
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(self.evaluate_expr(op)?.map(|x| -x)),
+            Expr::Function { name, args } => match expr.as_series() {
+                Some(series) => self.evaluate_series(series),
+                None => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.evaluate_expr(arg))
+                        .collect::<Result<Vec<Value>, EvaluatorError>>()?;
+                    self.call(name, args)
+                }
+            },
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => match self.get_variable(variable) {
+                // Skipping trivial powers keeps `v` usable for non-square `v`
+                Some(value) if *exponent == 1.0 => {
+                    binary(Op::Multiply, Value::Scalar(*coefficient), value)
+                }
+                Some(value) => {
+                    let power = binary(Op::Power, value, Value::Scalar(*exponent))?;
+                    binary(Op::Multiply, Value::Scalar(*coefficient), power)
+                }
+                None => Err(EvaluatorError::UnknownVariable(variable.to_owned())),
+            },
+        }
+    }
+}
+
+/// Evaluates `expression`, which may contain matrix literals like
+/// `[1, 2; 3, 4]` and vectors like `[1, 2, 3]`.
+pub fn evaluate_matrix(expression: &str) -> Result<Value> {
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+    Ok(evaluate_matrix_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+    )?)
+}
+
+/// Evaluates an already parsed expression with matrices, resolving its
+/// variables from `ctx`. Shapes that don't fit an operation are reported as
+/// [`EvaluatorError::DimensionMismatch`].
+pub fn evaluate_matrix_with(expr: &Expr, ctx: &Context) -> Result<Value, EvaluatorError> {
+    let value = MatrixEvaluator::new(ctx).evaluate_expr(expr)?;
+    Ok(value.map(|x| round(x, 15)))
+}
@@ -1,3 +1,26 @@
+mod builtins;
+mod complex;
+mod context;
+mod decimal;
 mod evaluator;
+mod frames;
+mod functions;
+mod hypercomplex;
+mod matrix;
+mod units;
+mod user_function;
 
-pub use evaluator::evaluate;
+pub use crate::math::{
+    format_complex, AngleMode, ComplexFormat, Decimal, Dimension, Hypercomplex, Matrix, Octonion,
+    Precision, Quantity, Quaternion, RoundingMode, Sedenion, Unit,
+};
+pub use complex::{evaluate_complex, evaluate_complex_with};
+pub use context::Context;
+pub use decimal::{evaluate_decimal, evaluate_decimal_with};
+pub use evaluator::{evaluate, evaluate_with};
+pub use functions::{AngleConversion, Arity, Function, FunctionRegistry, NativeFunction};
+pub use hypercomplex::{evaluate_hypercomplex, evaluate_hypercomplex_with};
+pub use matrix::{evaluate_matrix, evaluate_matrix_with, Value};
+pub use num_complex::Complex64;
+pub use units::{evaluate_units, evaluate_units_with};
+pub use user_function::UserFunction;
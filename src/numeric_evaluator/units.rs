@@ -0,0 +1,247 @@
+use crate::error::{EvaluatorError, Result};
+use crate::math::{as_integer, round, Quantity, Unit};
+use crate::parser::{parse_with, Expr, Op, ParserOptions, SeriesKind};
+
+use super::builtins::domain_error;
+use super::evaluator::locate;
+use super::frames::{Frames, UserFunctions};
+use super::{AngleMode, Arity, Context};
+
+fn mismatch(operation: &str, lhs: &Quantity, rhs: &Quantity) -> EvaluatorError {
+    EvaluatorError::DimensionMismatch {
+        operation: operation.to_string(),
+        reason: format!(
+            "{} and {} don't have the same dimension",
+            name(lhs),
+            name(rhs)
+        ),
+    }
+}
+
+/// The unit of `quantity` for error messages, or `1` for plain numbers.
+fn name(quantity: &Quantity) -> String {
+    match quantity.unit().is_empty() {
+        true => "1".to_string(),
+        false => quantity.unit().to_string(),
+    }
+}
+
+/// The value of a quantity whose units cancel out, or a dimension mismatch
+/// for `operation`.
+fn dimensionless(operation: &str, quantity: &Quantity) -> Result<f64, EvaluatorError> {
+    quantity
+        .dimensionless_value()
+        .ok_or_else(|| EvaluatorError::DimensionMismatch {
+            operation: operation.to_string(),
+            reason: format!("{} is not dimensionless", quantity.dimension()),
+        })
+}
+
+fn binary(op: Op, lhs: Quantity, rhs: Quantity) -> Result<Quantity, EvaluatorError> {
+    let (symbol, result) = match op {
+        Op::Add => ("+", lhs.combine(&rhs, |a, b| a + b)),
+        Op::Subtract => ("-", lhs.combine(&rhs, |a, b| a - b)),
+        Op::Modulo => ("%", lhs.combine(&rhs, |a, b| (a % b).abs())),
+        Op::Multiply => return Ok(lhs.mul(&rhs)),
+        Op::Divide => return Ok(lhs.div(&rhs)),
+        Op::Power => {
+            let exponent = dimensionless("^", &rhs)?;
+            return lhs.powf(exponent).ok_or_else(|| {
+                domain_error(
+                    "^",
+                    "units can only be raised to powers that keep them whole",
+                )
+            });
+        }
+        Op::Convert => {
+            if rhs.value() != 1.0 {
+                return Err(domain_error("to", "target must be a unit without a number"));
+            }
+            ("to", lhs.convert(rhs.unit()))
+        }
+        Op::Equals => return Err(EvaluatorError::EqualityInEval),
+    };
+    result.ok_or_else(|| mismatch(symbol, &lhs, &rhs))
+}
+
+/// Number of arguments of the functions that keep the units of their
+/// argument.
+fn builtin_arity(name: &str) -> Option<Arity> {
+    match name {
+        "abs" | "sqrt" | "cbrt" => Some(Arity::Fixed(1)),
+        _ => None,
+    }
+}
+
+/// Walks an expression tree like the `f64` evaluator, but with quantities.
+/// Variables that aren't defined are looked up as unit names.
+struct UnitsEvaluator<'a> {
+    ctx: &'a Context,
+    frames: Frames<Quantity>,
+}
+
+impl<'a> UserFunctions<'a> for UnitsEvaluator<'a> {
+    type Value = Quantity;
+
+    fn ctx(&self) -> &'a Context {
+        self.ctx
+    }
+
+    fn frames(&mut self) -> &mut Frames<Quantity> {
+        &mut self.frames
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Quantity, EvaluatorError> {
+        self.evaluate_expr(expr)
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Quantity>) -> Result<Quantity, EvaluatorError> {
+        self.call_builtin(name, &args)
+    }
+
+    fn integer(&self, n: i64) -> Quantity {
+        Quantity::from(n as f64)
+    }
+
+    fn as_integer(&self, value: &Quantity) -> Option<i64> {
+        value
+            .unit()
+            .is_empty()
+            .then(|| as_integer(value.value()))
+            .flatten()
+    }
+
+    fn accumulate(
+        &self,
+        kind: SeriesKind,
+        total: Quantity,
+        term: Quantity,
+    ) -> Result<Quantity, EvaluatorError> {
+        match kind {
+            SeriesKind::Sum => binary(Op::Add, total, term),
+            SeriesKind::Product => binary(Op::Multiply, total, term),
+        }
+    }
+}
+
+impl<'a> UnitsEvaluator<'a> {
+    fn new(ctx: &'a Context) -> Self {
+        Self {
+            ctx,
+            frames: Frames::new(),
+        }
+    }
+
+    fn get_variable(&self, name: &str) -> Option<Quantity> {
+        self.frames
+            .get(name)
+            .cloned()
+            .or_else(|| self.ctx.get_variable(name).map(Quantity::from))
+            .or_else(|| Unit::named(name).map(|unit| Quantity::new(1.0, unit)))
+    }
+
+    /// Calls a built-in function. Roots and `abs` carry units through, every
+    /// other function takes plain numbers only, so angles in `deg` are turned
+    /// into radians before `sin` sees them. Those are already radians, so
+    /// they aren't converted again by the angle mode.
+    fn call_builtin(&self, name: &str, args: &[Quantity]) -> Result<Quantity, EvaluatorError> {
+        let Some(arity) = builtin_arity(name) else {
+            let angle_mode = match args.iter().any(|arg| !arg.unit().is_empty()) {
+                true => AngleMode::Radians,
+                false => self.ctx.angle_mode(),
+            };
+            let args = args
+                .iter()
+                .map(|arg| dimensionless(name, arg))
+                .collect::<Result<Vec<f64>, EvaluatorError>>()?;
+            let result = self.ctx.functions().call(name, &args, angle_mode)?;
+            return Ok(Quantity::from(result));
+        };
+        if !arity.accepts(args.len()) {
+            return Err(EvaluatorError::ArityMismatch {
+                name: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            });
+        }
+
+        match (name, args) {
+            ("abs", [x]) => Ok(x.map(f64::abs)),
+            ("sqrt", [x]) if x.value() < 0.0 => {
+                Err(domain_error("sqrt", "argument must be non-negative"))
+            }
+            ("sqrt", [x]) => x.powf(0.5).ok_or_else(|| {
+                domain_error("sqrt", "units must have even powers to take a square root")
+            }),
+            ("cbrt", [x]) => x
+                .powf(1.0 / 3.0)
+                .map(|root| Quantity::new(x.value().cbrt(), root.unit().clone()))
+                .ok_or_else(|| {
+                    domain_error(
+                        "cbrt",
+                        "units must have powers divisible by 3 to take a cube root",
+                    )
+                }),
+            _ => Err(EvaluatorError::UnknownFunction(name.to_string())),
+        }
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Quantity, EvaluatorError> {
+        match expr {
+            Expr::BinOp { op: Op::Equals, .. } => Err(EvaluatorError::EqualityInEval),
+            Expr::BinOp { lhs, op, rhs } => {
+                let (lhs, rhs) = (self.evaluate_expr(lhs)?, self.evaluate_expr(rhs)?);
+                binary(*op, lhs, rhs)
+            }
+            Expr::Number(val) => Ok(Quantity::from(*val)),
+            Expr::Rational(val) => Ok(Quantity::from(val.to_f64())),
+            Expr::Constant(constant) => Ok(Quantity::from(constant.value())),
+            Expr::Matrix(_) => Err(domain_error("[]", "matrices have no scalar value")),
+            Expr::Spanned { span, expr } => self
+                .evaluate_expr(expr)
+                .map_err(|error| locate(error, *span)),
+            Expr::UnaryMinus(op) => Ok(self.evaluate_expr(op)?.map(|x| -x)),
+            Expr::Function { name, args } => match expr.as_series() {
+                Some(series) => self.evaluate_series(series),
+                None => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.evaluate_expr(arg))
+                        .collect::<Result<Vec<Quantity>, EvaluatorError>>()?;
+                    self.call(name, args)
+                }
+            },
+            Expr::Monomial {
+                coefficient,
+                variable,
+                exponent,
+            } => match self.get_variable(variable) {
+                Some(value) => {
+                    let power = binary(Op::Power, value, Quantity::from(*exponent))?;
+                    Ok(power.map(|x| x * coefficient))
+                }
+                None => Err(EvaluatorError::UnknownVariable(variable.to_owned())),
+            },
+        }
+    }
+}
+
+/// Evaluates `expression` with units, like `3 m/s * 2 min` or `5 ft in cm`.
+pub fn evaluate_units(expression: &str) -> Result<Quantity> {
+    let options = ParserOptions {
+        spans: true,
+        ..Default::default()
+    };
+    Ok(evaluate_units_with(
+        &parse_with(expression, &options)?,
+        &Context::default(),
+    )?)
+}
+
+/// Evaluates an already parsed expression with units, resolving variables
+/// from `ctx` before unit names. Adding or converting quantities of different
+/// dimensions is an [`EvaluatorError::DimensionMismatch`].
+pub fn evaluate_units_with(expr: &Expr, ctx: &Context) -> Result<Quantity, EvaluatorError> {
+    let quantity = UnitsEvaluator::new(ctx).evaluate_expr(expr)?;
+    Ok(quantity.map(|x| round(x, 15)))
+}
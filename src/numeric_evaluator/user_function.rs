@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use crate::error::{EvaluatorError, Result};
+use crate::parser::{Expr, Op};
+
+/// A function defined from an expression, e.g. `f(X, Y) = X^2 + Y`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunction {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+impl UserFunction {
+    /// Builds a function from a parsed `name(params...) = body` equation.
+    pub fn from_equation(equation: Expr) -> Result<Self, EvaluatorError> {
+        // Spans would point into the definition rather than the expression
+        // calling the function, so errors are reported at the call instead.
+        let Expr::BinOp {
+            lhs,
+            op: Op::Equals,
+            rhs,
+        } = equation.strip_spans()
+        else {
+            return Err(EvaluatorError::InvalidDefinition(
+                "expected a definition of the form 'f(X) = ...'".to_string(),
+            ));
+        };
+
+        let Expr::Function { name, args } = *lhs else {
+            return Err(EvaluatorError::InvalidDefinition(format!(
+                "left hand side '{lhs}' is not a function signature"
+            )));
+        };
+
+        let mut params: Vec<String> = Vec::with_capacity(args.len());
+        for arg in args {
+            let param = match arg {
+                Expr::Monomial {
+                    coefficient,
+                    variable,
+                    exponent,
+                } if coefficient == 1.0 && exponent == 1.0 => variable,
+                arg => {
+                    return Err(EvaluatorError::InvalidDefinition(format!(
+                        "parameter '{arg}' of '{name}' is not a variable"
+                    )))
+                }
+            };
+
+            if params.contains(&param) {
+                return Err(EvaluatorError::InvalidDefinition(format!(
+                    "parameter '{param}' of '{name}' is declared more than once"
+                )));
+            }
+            params.push(param);
+        }
+
+        Ok(Self {
+            name,
+            params,
+            body: *rhs,
+        })
+    }
+}
+
+fn collect_calls<'a>(expr: &'a Expr, calls: &mut Vec<&'a str>) {
+    match expr {
+        Expr::BinOp { lhs, rhs, .. } => {
+            collect_calls(lhs, calls);
+            collect_calls(rhs, calls);
+        }
+        Expr::UnaryMinus(inner) | Expr::Spanned { expr: inner, .. } => collect_calls(inner, calls),
+        Expr::Function { name, args } => {
+            calls.push(name);
+            for arg in args {
+                collect_calls(arg, calls);
+            }
+        }
+        Expr::Matrix(rows) => rows
+            .iter()
+            .flatten()
+            .for_each(|entry| collect_calls(entry, calls)),
+        Expr::Number(_) | Expr::Rational(_) | Expr::Monomial { .. } | Expr::Constant(_) => {}
+    }
+}
+
+/// Returns an error if `function` would call itself, either directly or
+/// through the functions already in `defined`.
+pub(super) fn check_recursion(
+    function: &UserFunction,
+    defined: &HashMap<String, UserFunction>,
+) -> Result<(), EvaluatorError> {
+    let mut pending: Vec<&str> = Vec::new();
+    let mut visited: Vec<&str> = Vec::new();
+    collect_calls(&function.body, &mut pending);
+
+    while let Some(name) = pending.pop() {
+        if name == function.name {
+            return Err(EvaluatorError::RecursiveFunction(function.name.to_owned()));
+        }
+        if visited.contains(&name) {
+            continue;
+        }
+        visited.push(name);
+
+        if let Some(callee) = defined.get(name) {
+            collect_calls(&callee.body, &mut pending);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::math::Sedenion;
+use crate::parser::{Constant, Expr, Op, Rational};
+
+/// Largest power of a number, in bits, that is computed exactly.
+const MAX_POWER_BITS: u64 = 4096;
+
+/// The exact value of `expr` if it is a (possibly negated) number.
+fn exact_value(expr: &Expr) -> Option<Rational> {
+    match expr {
+        Expr::Number(n) => Rational::from_f64(*n),
+        Expr::Rational(n) => Some(n.clone()),
+        Expr::UnaryMinus(inner) => exact_value(inner).map(|n| -n),
+        _ => None,
+    }
+}
+
+/// `lhs op rhs` computed exactly when both sides are numbers. Operations
+/// without an exact rational result, like `2^0.5` or `1/0`, are left alone.
+pub(super) fn fold_numbers(lhs: &Expr, op: Op, rhs: &Expr) -> Option<Expr> {
+    let (a, b) = (exact_value(lhs)?, exact_value(rhs)?);
+    let result = match op {
+        Op::Add => &a + &b,
+        Op::Subtract => &a - &b,
+        Op::Multiply => &a * &b,
+        Op::Divide => a.checked_div(&b)?,
+        // The evaluator also takes the absolute value of the remainder
+        Op::Modulo => a.checked_rem(&b)?.abs(),
+        Op::Power => {
+            let exponent = b.to_i32()?;
+            if a.bits().saturating_mul(exponent.unsigned_abs().into()) > MAX_POWER_BITS {
+                return None;
+            }
+            a.checked_pow(exponent)?
+        }
+        Op::Equals | Op::Convert => return None,
+    };
+    Some(Expr::from(result))
+}
+
+/// The basis unit `e{index}` times `sign`, where index 0 stands for 1.
+fn signed_unit(index: usize, sign: f64) -> Expr {
+    let unit = match Constant::unit(index) {
+        Some(unit) => Expr::Constant(unit),
+        None => Expr::Number(1.0),
+    };
+    if sign < 0.0 {
+        Expr::UnaryMinus(Box::new(unit))
+    } else {
+        unit
+    }
+}
+
+/// Products and integer powers of basis units, like `i*j = k` and `i^2 = -1`.
+/// Units don't commute, so `j*i` is `-k` and the operands keep their order.
+pub(super) fn fold_units(lhs: &Expr, op: Op, rhs: &Expr) -> Option<Expr> {
+    let Expr::Constant(lhs) = lhs else {
+        return None;
+    };
+    let a = lhs.unit_index()?;
+    match (op, rhs) {
+        (Op::Multiply, Expr::Constant(rhs)) => {
+            let product = Sedenion::unit(a)? * Sedenion::unit(rhs.unit_index()?)?;
+            let (index, sign) = product
+                .components()
+                .iter()
+                .enumerate()
+                .find(|(_, x)| **x != 0.0)?;
+            Some(signed_unit(index, *sign))
+        }
+        // Every unit squares to -1, so its powers cycle through e, -1, -e, 1
+        (Op::Power, rhs) => match exact_value(rhs)?.to_i32()?.rem_euclid(4) {
+            0 => Some(Expr::Number(1.0)),
+            1 => Some(signed_unit(a, 1.0)),
+            2 => Some(signed_unit(0, -1.0)),
+            _ => Some(signed_unit(a, -1.0)),
+        },
+        _ => None,
+    }
+}
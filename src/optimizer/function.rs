@@ -0,0 +1,95 @@
+use crate::parser::{Constant, Expr, Op};
+
+use super::series::closed_series;
+
+/// Returns `k` if `expr` is a rational multiple `k*pi` written with numbers and
+/// `pi`/`tau` only.
+fn pi_multiple(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Constant(Constant::Pi) => Some(1.0),
+        Expr::Constant(Constant::Tau) => Some(2.0),
+        Expr::Number(n) if *n == 0.0 => Some(0.0),
+        Expr::UnaryMinus(inner) => pi_multiple(inner).map(|k| -k),
+        Expr::BinOp {
+            lhs,
+            op: Op::Multiply,
+            rhs,
+        } => match (lhs.as_ref(), rhs.as_ref()) {
+            (Expr::Number(n), other) | (other, Expr::Number(n)) => {
+                pi_multiple(other).map(|k| k * n)
+            }
+            (Expr::Rational(n), other) | (other, Expr::Rational(n)) => {
+                pi_multiple(other).map(|k| k * n.to_f64())
+            }
+            _ => None,
+        },
+        Expr::BinOp {
+            lhs,
+            op: Op::Divide,
+            rhs,
+        } => match rhs.as_ref() {
+            Expr::Number(n) if *n != 0.0 => pi_multiple(lhs).map(|k| k / n),
+            Expr::Rational(n) => pi_multiple(lhs).map(|k| k / n.to_f64()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Exact value of `sin(k*pi)` when it is one of `0`, `±1/2` or `±1`.
+fn exact_sin(k: f64) -> Option<f64> {
+    let sixths = k * 6.0;
+    if sixths.fract() != 0.0 {
+        return None;
+    }
+
+    match sixths.rem_euclid(12.0) as u8 {
+        0 | 6 => Some(0.0),
+        1 | 5 => Some(0.5),
+        3 => Some(1.0),
+        7 | 11 => Some(-0.5),
+        9 => Some(-1.0),
+        _ => None,
+    }
+}
+
+/// Exact value of `tan(k*pi)` when it is one of `0` or `±1`.
+fn exact_tan(k: f64) -> Option<f64> {
+    let quarters = k * 4.0;
+    if quarters.fract() != 0.0 {
+        return None;
+    }
+
+    match quarters.rem_euclid(4.0) as u8 {
+        0 => Some(0.0),
+        1 => Some(1.0),
+        3 => Some(-1.0),
+        _ => None,
+    }
+}
+
+/// Simplifies a call whose arguments have already been optimized. Angles are
+/// taken to be in radians, and sums and products with a bound index are
+/// replaced by their closed forms where there is one.
+pub(super) fn optimize_function(name: &str, args: Vec<Expr>) -> Expr {
+    let exact = match (name, args.as_slice()) {
+        ("sin", [arg]) => pi_multiple(arg).and_then(exact_sin).map(Expr::Number),
+        ("cos", [arg]) => pi_multiple(arg)
+            .and_then(|k| exact_sin(k + 0.5))
+            .map(Expr::Number),
+        ("tan", [arg]) => pi_multiple(arg).and_then(exact_tan).map(Expr::Number),
+        ("ln", [Expr::Constant(Constant::E)]) => Some(Expr::Number(1.0)),
+        ("ln", [Expr::Number(n)]) if *n == 1.0 => Some(Expr::Number(0.0)),
+        ("exp", [Expr::Number(n)]) if *n == 0.0 => Some(Expr::Number(1.0)),
+        ("exp", [Expr::Number(n)]) if *n == 1.0 => Some(Expr::Constant(Constant::E)),
+        _ => None,
+    };
+
+    let call = Expr::Function {
+        name: name.to_string(),
+        args,
+    };
+    exact
+        .or_else(|| call.as_series().and_then(closed_series))
+        .unwrap_or(call)
+}
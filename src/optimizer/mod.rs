@@ -1,4 +1,12 @@
+mod fold;
+mod function;
+mod series;
+
 use crate::parser::{Expr, Op, Optimize};
+use crate::solver::{solve, Solutions};
+
+use fold::{fold_numbers, fold_units};
+use function::optimize_function;
 
 impl Optimize for Expr {
     fn optimize_expression(self) -> Expr {
@@ -35,6 +43,16 @@ impl Optimize for Expr {
                 let optimized_lhs = lhs.optimize_node();
                 let optimized_rhs = rhs.optimize_node();
 
+                // Operations on numbers are carried out exactly
+                if let Some(folded) = fold_numbers(&optimized_lhs, *op, &optimized_rhs) {
+                    return folded;
+                }
+
+                // Products and powers of basis units
+                if let Some(folded) = fold_units(&optimized_lhs, *op, &optimized_rhs) {
+                    return folded;
+                }
+
                 // 0 + a = a
                 if let (Expr::Number(num), Op::Add) = (&optimized_lhs, &op) {
                     if num == &0.0 {
@@ -56,10 +74,10 @@ impl Optimize for Expr {
                     }
                 }
 
-                // 0 - a = a
+                // 0 - a = -a
                 if let (Expr::Number(num), Op::Subtract) = (&optimized_lhs, &op) {
                     if num == &0.0 {
-                        return optimized_rhs;
+                        return Expr::UnaryMinus(Box::new(optimized_rhs));
                     }
                 }
 
@@ -207,6 +225,30 @@ impl Optimize for Expr {
                     }
                 }
 
+                // aX^b - cX^b = (a-c)X^b
+                if let (
+                    Expr::Monomial {
+                        coefficient: left_coefficient,
+                        variable: left_variable,
+                        exponent: left_exponent,
+                    },
+                    Expr::Monomial {
+                        coefficient: right_coefficient,
+                        variable: right_variable,
+                        exponent: right_exponent,
+                    },
+                    Op::Subtract,
+                ) = (&optimized_lhs, &optimized_rhs, op)
+                {
+                    if left_variable == right_variable && left_exponent == right_exponent {
+                        return Expr::Monomial {
+                            coefficient: left_coefficient - right_coefficient,
+                            variable: left_variable.to_owned(),
+                            exponent: left_exponent.to_owned(),
+                        };
+                    }
+                }
+
                 // aX^b * cX^d = (a*b)X^(b*d)
                 if let (
                     Expr::Monomial {
@@ -238,12 +280,52 @@ impl Optimize for Expr {
                 }
             }
             Expr::Number(n) => Expr::Number(*n),
-            Expr::Monomial { .. } => return self.clone(),
-            token => todo!("Optimizing for '{token:?}' not implemented yet!"),
+            Expr::Rational(_) | Expr::Monomial { .. } | Expr::Constant(_) => self.clone(),
+            Expr::Spanned { expr, .. } => expr.optimize_node(),
+            Expr::Matrix(rows) => Expr::Matrix(
+                rows.iter()
+                    .map(|row| row.iter().map(Expr::optimize_node).collect())
+                    .collect(),
+            ),
+            Expr::Function { name, args } => {
+                optimize_function(name, args.iter().map(Expr::optimize_node).collect())
+            }
         }
     }
 
     fn optimize_equation(self) -> Expr {
-        todo!()
+        let equation = self.strip_spans();
+        let Expr::BinOp {
+            lhs,
+            op: Op::Equals,
+            rhs,
+        } = &equation
+        else {
+            return equation.optimize_expression();
+        };
+
+        // Equations in one variable with a single solution are solved for it
+        let variables = equation.variables();
+        if let Some(variable) = variables.first().filter(|_| variables.len() == 1) {
+            if let Ok(Solutions::Roots(roots)) = solve(&equation, variable) {
+                if let [root] = roots.as_slice() {
+                    return Expr::BinOp {
+                        lhs: Box::new(Expr::Monomial {
+                            coefficient: 1.0,
+                            variable: variable.to_owned(),
+                            exponent: 1.0,
+                        }),
+                        op: Op::Equals,
+                        rhs: Box::new(root.to_owned()),
+                    };
+                }
+            }
+        }
+
+        Expr::BinOp {
+            lhs: Box::new(lhs.to_owned().optimize_expression()),
+            op: Op::Equals,
+            rhs: Box::new(rhs.to_owned().optimize_expression()),
+        }
     }
 }
@@ -0,0 +1,162 @@
+use crate::calculus::build::{add, as_number, call, depends_on, div, mul, neg, number, pow, sub};
+use crate::parser::{Expr, Op, Series, SeriesKind};
+
+/// Closed form of a `sum` or `prod` with a bound index, when its terms are
+/// built from constants, powers `k`, `k^2` and `k^3` of the index and
+/// geometric terms `r^k`.
+pub(super) fn closed_series(series: Series) -> Option<Expr> {
+    let from = series.from.unspanned();
+    // The closed forms count empty ranges backwards instead of as empty, so
+    // an upper bound that isn't known is raised to `from - 1` at least, where
+    // every closed form is the empty sum or product.
+    let to = match (as_number(from), as_number(series.to.unspanned())) {
+        (Some(from), Some(to)) if from > to + 1.0 => return None,
+        (Some(_), Some(_)) => series.to.unspanned().clone(),
+        _ => call(
+            "max",
+            vec![
+                series.to.unspanned().clone(),
+                sub(from.clone(), Expr::Number(1.0)),
+            ],
+        ),
+    };
+
+    match series.kind {
+        SeriesKind::Sum => closed_sum(series.index, from, &to, series.body.unspanned()),
+        SeriesKind::Product => closed_product(series.index, from, &to, series.body.unspanned()),
+    }
+}
+
+/// `to - from + 1`, the number of terms.
+fn count(from: &Expr, to: &Expr) -> Expr {
+    add(sub(to.clone(), from.clone()), Expr::Number(1.0))
+}
+
+/// Whether `expr` is the index `k` itself.
+fn is_index(expr: &Expr, index: &str) -> bool {
+    matches!(
+        expr.unspanned(),
+        Expr::Monomial { coefficient, variable, exponent }
+            if variable == index && *coefficient == 1.0 && *exponent == 1.0
+    )
+}
+
+/// `1^p + 2^p + ... + n^p` for `p` from 1 to 3, by Faulhaber's formula.
+fn power_sum(p: f64, n: Expr) -> Option<Expr> {
+    // n(n+1)/2
+    let triangle = div(
+        mul(n.clone(), add(n.clone(), Expr::Number(1.0))),
+        Expr::Number(2.0),
+    );
+    match p {
+        1.0 => Some(triangle),
+        // n(n+1)(2n+1)/6
+        2.0 => Some(div(
+            mul(
+                mul(n.clone(), add(n.clone(), Expr::Number(1.0))),
+                add(mul(Expr::Number(2.0), n), Expr::Number(1.0)),
+            ),
+            Expr::Number(6.0),
+        )),
+        // (n(n+1)/2)^2
+        3.0 => Some(pow(triangle, Expr::Number(2.0))),
+        _ => None,
+    }
+}
+
+fn closed_sum(index: &str, from: &Expr, to: &Expr, body: &Expr) -> Option<Expr> {
+    // c + c + ... + c = (to - from + 1)*c
+    if !depends_on(body, index) {
+        return Some(mul(count(from, to), body.clone()));
+    }
+
+    match body {
+        Expr::Spanned { expr, .. } => closed_sum(index, from, to, expr),
+        Expr::UnaryMinus(inner) => closed_sum(index, from, to, inner).map(neg),
+        // Σ c*k^p = c*(S(to) - S(from - 1)) for the power sums S
+        Expr::Monomial {
+            coefficient,
+            exponent,
+            ..
+        } => {
+            let below = sub(from.clone(), Expr::Number(1.0));
+            let sum = sub(
+                power_sum(*exponent, to.clone())?,
+                power_sum(*exponent, below)?,
+            );
+            Some(mul(number(*coefficient), sum))
+        }
+        Expr::BinOp { lhs, op, rhs } => {
+            let (lhs, rhs) = (lhs.unspanned(), rhs.unspanned());
+            match op {
+                Op::Add => Some(add(
+                    closed_sum(index, from, to, lhs)?,
+                    closed_sum(index, from, to, rhs)?,
+                )),
+                Op::Subtract => Some(sub(
+                    closed_sum(index, from, to, lhs)?,
+                    closed_sum(index, from, to, rhs)?,
+                )),
+                Op::Multiply if !depends_on(lhs, index) => {
+                    Some(mul(lhs.clone(), closed_sum(index, from, to, rhs)?))
+                }
+                Op::Multiply if !depends_on(rhs, index) => {
+                    Some(mul(closed_sum(index, from, to, lhs)?, rhs.clone()))
+                }
+                Op::Divide if !depends_on(rhs, index) => {
+                    Some(div(closed_sum(index, from, to, lhs)?, rhs.clone()))
+                }
+                // r^from + ... + r^to = (r^(to+1) - r^from)/(r - 1), for r ≠ 1
+                Op::Power
+                    if !depends_on(lhs, index)
+                        && is_index(rhs, index)
+                        && as_number(lhs) != Some(1.0) =>
+                {
+                    Some(div(
+                        sub(
+                            pow(lhs.clone(), add(to.clone(), Expr::Number(1.0))),
+                            pow(lhs.clone(), from.clone()),
+                        ),
+                        sub(lhs.clone(), Expr::Number(1.0)),
+                    ))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn closed_product(index: &str, from: &Expr, to: &Expr, body: &Expr) -> Option<Expr> {
+    // c*c*...*c = c^(to - from + 1)
+    if !depends_on(body, index) {
+        return Some(pow(body.clone(), count(from, to)));
+    }
+
+    match body {
+        Expr::Spanned { expr, .. } => closed_product(index, from, to, expr),
+        // 1*2*...*n = n!
+        _ if is_index(body, index) && as_number(from) == Some(1.0) => {
+            Some(call("factorial", vec![to.clone()]))
+        }
+        Expr::BinOp { lhs, op, rhs } => {
+            let (lhs, rhs) = (lhs.unspanned(), rhs.unspanned());
+            match op {
+                Op::Multiply => Some(mul(
+                    closed_product(index, from, to, lhs)?,
+                    closed_product(index, from, to, rhs)?,
+                )),
+                Op::Divide => Some(div(
+                    closed_product(index, from, to, lhs)?,
+                    closed_product(index, from, to, rhs)?,
+                )),
+                // r^f(from) * ... * r^f(to) = r^(f(from) + ... + f(to))
+                Op::Power if !depends_on(lhs, index) => {
+                    Some(pow(lhs.clone(), closed_sum(index, from, to, rhs)?))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -1,5 +1,11 @@
+mod options;
+#[allow(clippy::module_inception)]
 mod parser;
 mod token;
 
-pub use parser::{parse, parse_equation};
-pub use token::{Expr, Op, Optimize};
\ No newline at end of file
+pub use crate::math::{ParseRationalError, Rational};
+pub use options::{ImplicitMultiplication, ParserOptions};
+pub use parser::{
+    parse, parse_equation, parse_equation_with, parse_system, parse_system_with, parse_with,
+};
+pub use token::{Constant, Expr, Op, Optimize, Series, SeriesKind, Span};
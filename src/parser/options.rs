@@ -0,0 +1,24 @@
+/// How tightly juxtaposed operands such as `3x` or `2(x+1)` bind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImplicitMultiplication {
+    /// Binds tighter than `*`, `/` and `%`, but looser than `^`:
+    /// `2/3x` is `2/(3x)` and `2^3x` is `(2^3)x`.
+    #[default]
+    HighPrecedence,
+    /// Binds exactly like `*`, evaluating left to right: `2/3x` is `(2/3)x`.
+    SamePrecedence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParserOptions {
+    pub implicit_multiplication: ImplicitMultiplication,
+    /// Wrap every node in an [`Expr::Spanned`](super::Expr::Spanned) recording
+    /// where it came from, so errors can point at the offending input.
+    pub spans: bool,
+    /// Read `i` as the imaginary unit rather than a variable, so `3+4i` is a
+    /// complex number.
+    pub complex: bool,
+    /// Read `i`, `j`, `k` and `e1`..`e15` as the basis units of the
+    /// quaternions, octonions and sedenions rather than variables.
+    pub hypercomplex: bool,
+}
@@ -1,11 +1,11 @@
-use anyhow::{bail, Result};
+use pest::error::InputLocation;
+use pest::iterators::{Pair, Pairs};
 use pest::pratt_parser::PrattParser;
 use pest::Parser;
-use pest::{iterators::Pairs, pratt_parser::Assoc};
 
-use crate::error::ParserError;
+use crate::error::{ParserError, Result};
 
-use super::{Expr, Op};
+use super::{Constant, Expr, ImplicitMultiplication, Op, ParserOptions, Rational, Span};
 
 #[derive(pest_derive::Parser)]
 #[grammar = "grammar/sedenion.pest"]
@@ -18,17 +18,51 @@ lazy_static::lazy_static! {
 
         // Precedence is defined lowest to highest
         PrattParser::new()
+            .op(Op::infix(convert, Left))
             .op(Op::infix(add, Left) | Op::infix(subtract, Left))
             .op(Op::infix(multiply, Left) | Op::infix(divide, Left) | Op::infix(modulo, Left))
+            .op(Op::infix(implicit_multiply, Left))
             .op(Op::infix(power, Right))
             .op(Op::prefix(unary_minus))
             .op(Op::infix(equals, Left))
         };
+
+    static ref SAME_PRECEDENCE_PRATT_PARSER: PrattParser<Rule> = {
+        use pest::pratt_parser::{Assoc::*, Op};
+        use Rule::*;
+
+        // Precedence is defined lowest to highest
+        PrattParser::new()
+            .op(Op::infix(convert, Left))
+            .op(Op::infix(add, Left) | Op::infix(subtract, Left))
+            .op(Op::infix(multiply, Left)
+                | Op::infix(divide, Left)
+                | Op::infix(modulo, Left)
+                | Op::infix(implicit_multiply, Left))
+            .op(Op::infix(power, Right))
+            .op(Op::prefix(unary_minus))
+            .op(Op::infix(equals, Left))
+        };
+}
+
+fn parse_number(pair: &Pair<Rule>) -> Result<f64, ParserError> {
+    pair.as_str()
+        .parse::<f64>()
+        .map_err(|_| ParserError::InvalidNumber(pair.as_str().to_string()))
+}
+
+/// Reads a number literal exactly. Literals an `f64` can't hold, like `0.1`,
+/// become [`Expr::Rational`].
+fn parse_literal(pair: &Pair<Rule>) -> Result<Expr, ParserError> {
+    pair.as_str()
+        .parse::<Rational>()
+        .map(Expr::from)
+        .map_err(|_| ParserError::InvalidNumber(pair.as_str().to_string()))
 }
 
-fn parse_function(pairs: Pairs<Rule>) -> Result<Expr> {
+fn parse_function(pairs: Pairs<Rule>, options: &ParserOptions) -> Result<Expr, ParserError> {
     let mut name = String::new();
-    let mut args: Vec<Box<Expr>> = Vec::new();
+    let mut args: Vec<Expr> = Vec::new();
 
     for pair in pairs {
         match pair.as_rule() {
@@ -36,103 +70,278 @@ fn parse_function(pairs: Pairs<Rule>) -> Result<Expr> {
             Rule::function_args => {
                 args = pair
                     .into_inner()
-                    .map(|arg| parse_expr(arg.into_inner()))
-                    .map(|arg| Box::new(arg.unwrap()))
-                    .collect()
-            }
-            rule => {
-                bail!(ParserError::InvalidToken(format!("{:?}", rule)))
+                    .map(|arg| parse_expr(arg.into_inner(), options))
+                    .collect::<Result<Vec<Expr>, ParserError>>()?
             }
+            rule => return Err(ParserError::InvalidToken(format!("{:?}", rule))),
         }
     }
 
-    if name != "" {
+    if !name.is_empty() {
         Ok(Expr::Function { name, args })
     } else {
-        bail!(ParserError::NoFunctionName)
+        Err(ParserError::NoFunctionName)
     }
 }
 
-fn parse_monomial(pairs: Pairs<Rule>) -> Result<Expr> {
-    let mut coefficient: Option<f64> = None;
+fn parse_monomial(pairs: Pairs<Rule>, options: &ParserOptions) -> Result<Expr, ParserError> {
     let mut exponent: Option<f64> = None;
     let mut variable: Option<String> = None;
     for pair in pairs {
         match pair.as_rule() {
-            Rule::coefficient => coefficient = Some(pair.as_str().parse::<f64>()?),
             Rule::variable => variable = Some(pair.as_str().to_string()),
-            Rule::exponent => {
-                let pair = match pair.as_str().strip_prefix("^") {
-                    Some(val) => val,
-                    None => bail!(ParserError::InvalidToken(format!("{:?}", pair.as_str()))),
-                };
-                exponent = Some(pair.parse::<f64>()?);
-            }
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
+            Rule::exponent => match pair
+                .into_inner()
+                .find(|pair| pair.as_rule() == Rule::number)
+            {
+                Some(number) => exponent = Some(parse_number(&number)?),
+                None => return Err(ParserError::InvalidToken("^".to_string())),
+            },
+            rule => return Err(ParserError::InvalidToken(format!("{:?}", rule))),
         }
     }
 
+    let variable = variable.ok_or(ParserError::NoVariable)?;
+    let unit = match variable.as_str() {
+        "i" if options.complex => Some(Constant::ImaginaryUnit),
+        name if options.hypercomplex => Constant::unit_from_name(name),
+        _ => None,
+    };
+    if let Some(unit) = unit {
+        let unit = Expr::Constant(unit);
+        return Ok(match exponent {
+            Some(exponent) => Expr::BinOp {
+                lhs: Box::new(unit),
+                op: Op::Power,
+                rhs: Box::new(Expr::Number(exponent)),
+            },
+            None => unit,
+        });
+    }
+
     Ok(Expr::Monomial {
-        coefficient: coefficient.unwrap_or(1.0),
-        variable: variable.unwrap(),
+        coefficient: 1.0,
+        variable,
         exponent: exponent.unwrap_or(1.0),
     })
 }
 
-fn parse_expr(pairs: Pairs<Rule>) -> Result<Expr> {
-    PRATT_PARSER
-        .map_primary(|primary| match primary.as_rule() {
-            Rule::number => Ok(Expr::Number(primary.as_str().parse::<f64>().unwrap())),
-            Rule::expr => parse_expr(primary.into_inner()),
-            Rule::function => parse_function(primary.into_inner()),
-            Rule::monomial => parse_monomial(primary.into_inner()),
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
+/// Multiplies two juxtaposed operands, folding `3` and `x^2` into the
+/// monomial `3x^2`.
+fn implicit_multiply(lhs: Expr, rhs: Expr) -> Expr {
+    let coefficient = match lhs.unspanned() {
+        Expr::Number(n) => Some(*n),
+        Expr::Rational(n) => Some(n.to_f64()),
+        _ => None,
+    };
+    match (coefficient, rhs.unspanned()) {
+        (
+            Some(coefficient),
+            Expr::Monomial {
+                coefficient: 1.0,
+                variable,
+                exponent,
+            },
+        ) => Expr::Monomial {
+            coefficient,
+            variable: variable.to_owned(),
+            exponent: *exponent,
+        },
+        _ => Expr::BinOp {
+            lhs: Box::new(lhs),
+            op: Op::Multiply,
+            rhs: Box::new(rhs),
+        },
+    }
+}
+
+fn with_span(expr: Expr, span: Option<Span>) -> Expr {
+    match span {
+        Some(span) => Expr::Spanned {
+            span,
+            expr: Box::new(expr),
+        },
+        None => expr,
+    }
+}
+
+/// Span of `pair`, leaving out whitespace its rule consumed after the last token.
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let start = pair.as_span().start();
+    Span::new(start, start + pair.as_str().trim_end().len())
+}
+
+fn join_spans(lhs: Option<Span>, rhs: Option<Span>) -> Option<Span> {
+    lhs.zip(rhs).map(|(lhs, rhs)| Span::new(lhs.start, rhs.end))
+}
+
+fn parse_matrix(pairs: Pairs<Rule>, options: &ParserOptions) -> Result<Expr, ParserError> {
+    let rows = pairs
+        .map(|row| {
+            row.into_inner()
+                .map(|entry| parse_expr(entry.into_inner(), options))
+                .collect::<Result<Vec<Expr>, ParserError>>()
+        })
+        .collect::<Result<Vec<Vec<Expr>>, ParserError>>()?;
+
+    if rows.iter().any(|row| row.len() != rows[0].len()) {
+        return Err(ParserError::RaggedMatrix);
+    }
+    Ok(Expr::Matrix(rows))
+}
+
+fn parse_expr(pairs: Pairs<Rule>, options: &ParserOptions) -> Result<Expr, ParserError> {
+    let pratt_parser: &PrattParser<Rule> = match options.implicit_multiplication {
+        ImplicitMultiplication::HighPrecedence => &PRATT_PARSER,
+        ImplicitMultiplication::SamePrecedence => &SAME_PRECEDENCE_PRATT_PARSER,
+    };
+
+    pratt_parser
+        .map_primary(|primary| {
+            let span = options.spans.then(|| span_of(&primary));
+            let expr = match primary.as_rule() {
+                Rule::number => parse_literal(&primary)?,
+                // Already carries the spans of its own nodes
+                Rule::expr => return parse_expr(primary.into_inner(), options),
+                Rule::function => parse_function(primary.into_inner(), options)?,
+                Rule::monomial => parse_monomial(primary.into_inner(), options)?,
+                Rule::matrix => parse_matrix(primary.into_inner(), options)?,
+                Rule::constant => match Constant::from_name(primary.as_str()) {
+                    Some(constant) => Expr::Constant(constant),
+                    None => return Err(ParserError::InvalidToken(primary.as_str().to_string())),
+                },
+                rule => return Err(ParserError::InvalidToken(format!("{:?}", rule))),
+            };
+            Ok(with_span(expr, span))
         })
         .map_infix(|lhs, op, rhs| {
-            let op: Result<Op> = match op.as_rule() {
-                Rule::add => Ok(Op::Add),
-                Rule::subtract => Ok(Op::Subtract),
-                Rule::multiply => Ok(Op::Multiply),
-                Rule::divide => Ok(Op::Divide),
-                Rule::modulo => Ok(Op::Modulo),
-                Rule::power => Ok(Op::Power),
-                rule => bail!(ParserError::InvalidOperator(format!("{:?}", rule))),
+            let (lhs, rhs) = (lhs?, rhs?);
+            let span = join_spans(lhs.span(), rhs.span());
+
+            if op.as_rule() == Rule::implicit_multiply {
+                return Ok(with_span(implicit_multiply(lhs, rhs), span));
+            }
+
+            let op = match op.as_rule() {
+                Rule::add => Op::Add,
+                Rule::subtract => Op::Subtract,
+                Rule::multiply => Op::Multiply,
+                Rule::divide => Op::Divide,
+                Rule::modulo => Op::Modulo,
+                Rule::power => Op::Power,
+                Rule::convert => Op::Convert,
+                rule => return Err(ParserError::InvalidOperator(format!("{:?}", rule))),
             };
-            let op = op?;
-            Ok(Expr::BinOp {
-                lhs: Box::new(lhs?),
+            let expr = Expr::BinOp {
+                lhs: Box::new(lhs),
                 op,
-                rhs: Box::new(rhs?),
-            })
+                rhs: Box::new(rhs),
+            };
+            Ok(with_span(expr, span))
         })
-        .map_prefix(|op, rhs| match op.as_rule() {
-            Rule::unary_minus => Ok(Expr::UnaryMinus(Box::new(rhs?))),
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
+        .map_prefix(|op, rhs| {
+            let rhs = rhs?;
+            let op_span = options.spans.then(|| span_of(&op));
+            let span = join_spans(op_span, rhs.span());
+
+            match op.as_rule() {
+                Rule::unary_minus => Ok(with_span(Expr::UnaryMinus(Box::new(rhs)), span)),
+                rule => Err(ParserError::InvalidToken(format!("{:?}", rule))),
+            }
         })
         .parse(pairs)
 }
 
-pub fn parse(expression: &str) -> Result<Expr> {
-    let mut pairs = CalculatorParser::parse(Rule::equation, expression)?;
-    parse_expr(pairs.next().unwrap().into_inner())
+pub fn parse(expression: &str) -> Result<Expr, ParserError> {
+    parse_with(expression, &ParserOptions::default())
+}
+
+pub fn parse_with(expression: &str, options: &ParserOptions) -> Result<Expr, ParserError> {
+    let mut pairs = CalculatorParser::parse(Rule::equation, expression).map_err(|error| {
+        ParserError::Syntax {
+            message: error.variant.message().to_string(),
+            span: match error.location {
+                InputLocation::Pos(pos) => Span::new(pos, pos),
+                InputLocation::Span((start, end)) => Span::new(start, end),
+            },
+        }
+    })?;
+    match pairs.next() {
+        Some(pair) => parse_expr(pair.into_inner(), options),
+        None => Err(ParserError::EmptyExpression),
+    }
+}
+
+pub fn parse_equation(expression: &str) -> Result<Expr, ParserError> {
+    parse_equation_with(expression, &ParserOptions::default())
 }
 
-pub fn parse_equation(expression: &str) -> Result<Expr> {
+pub fn parse_equation_with(expression: &str, options: &ParserOptions) -> Result<Expr, ParserError> {
     if !expression.contains("=") {
-        bail!(ParserError::NoEquals);
+        return Err(ParserError::NoEquals);
     }
 
-    let expression: Vec<&str> = expression.split("=").collect();
-    if expression.len() != 2 {
-        bail!(ParserError::EqualsCount);
+    if expression.matches('=').count() != 1 {
+        return Err(ParserError::EqualsCount);
     }
 
-    let lhs = parse(expression[0])?;
-    let rhs = parse(expression[1])?;
+    // The right hand side is padded with whitespace in place of the left hand
+    // side so its spans line up with the original input.
+    let (lhs, rhs) = expression.split_once('=').unwrap_or_default();
+    let rhs = format!("{}{rhs}", " ".repeat(lhs.len() + 1));
+
+    let lhs = parse_with(lhs, options)?;
+    let rhs = parse_with(&rhs, options)?;
+    let span = options.spans.then(|| Span::new(0, expression.len()));
 
-    Ok(Expr::BinOp {
+    let expr = Expr::BinOp {
         lhs: Box::new(lhs),
         op: Op::Equals,
         rhs: Box::new(rhs),
-    })
+    };
+    Ok(with_span(expr, span))
+}
+
+/// Parses a system of equations separated by `;`.
+pub fn parse_system(expression: &str) -> Result<Vec<Expr>, ParserError> {
+    parse_system_with(expression, &ParserOptions::default())
+}
+
+pub fn parse_system_with(
+    expression: &str,
+    options: &ParserOptions,
+) -> Result<Vec<Expr>, ParserError> {
+    let mut equations = Vec::new();
+    let mut start = 0;
+
+    // Semicolons inside brackets separate matrix rows, not equations
+    let mut depth = 0usize;
+    let parts = expression.split(|c| {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        c == ';' && depth == 0
+    });
+    for part in parts {
+        let end = start + part.len();
+        if !part.trim().is_empty() {
+            // Padded like the right hand side of an equation so its spans
+            // line up with the original input.
+            let padded = format!("{}{part}", " ".repeat(start));
+            let equation = parse_equation_with(&padded, options)?;
+            let leading = part.len() - part.trim_start().len();
+            let span = options
+                .spans
+                .then(|| Span::new(start + leading, start + part.trim_end().len()));
+            equations.push(with_span(equation.unspanned().clone(), span));
+        }
+        start = end + 1;
+    }
+
+    if equations.is_empty() {
+        return Err(ParserError::EmptyExpression);
+    }
+    Ok(equations)
 }
@@ -1,6 +1,30 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::math::Rational;
+
+/// Byte range of a node in the parsed input.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Number(f64),
+    /// An exact number that an `f64` can't hold, like `0.1` or `1/3`. Numbers
+    /// that fit are kept as [`Expr::Number`].
+    Rational(Rational),
     UnaryMinus(Box<Expr>),
     BinOp {
         lhs: Box<Expr>,
@@ -9,13 +33,319 @@ pub enum Expr {
     },
     Function {
         name: String,
-        args: Vec<Box<Expr>>,
+        args: Vec<Expr>,
     },
     Monomial {
         coefficient: f64,
         variable: String,
         exponent: f64,
     },
+    Constant(Constant),
+    /// A matrix literal, row by row. Vectors are matrices with a single row.
+    Matrix(Vec<Vec<Expr>>),
+    /// Location of `expr` in the input, only produced when
+    /// [`ParserOptions::spans`](super::ParserOptions::spans) is set.
+    Spanned {
+        span: Span,
+        expr: Box<Expr>,
+    },
+}
+
+/// Whether a [`Series`] adds or multiplies its terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesKind {
+    Sum,
+    Product,
+}
+
+impl SeriesKind {
+    /// The function the series is written with.
+    pub fn name(self) -> &'static str {
+        match self {
+            SeriesKind::Sum => "sum",
+            SeriesKind::Product => "prod",
+        }
+    }
+}
+
+/// A call `sum(k, from, to, body)` or `prod(k, from, to, body)`, which adds or
+/// multiplies `body` for every integer `k` from `from` to `to`. The index `k`
+/// is only bound inside `body`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Series<'a> {
+    pub kind: SeriesKind,
+    pub index: &'a str,
+    pub from: &'a Expr,
+    pub to: &'a Expr,
+    pub body: &'a Expr,
+}
+
+impl Expr {
+    /// The sum or product with a bound index this node is a call of, if any.
+    /// A `sum` whose first argument isn't a bare variable, or which doesn't
+    /// have four arguments, is the variadic `sum(a, b, ...)` instead.
+    pub fn as_series(&self) -> Option<Series<'_>> {
+        let Expr::Function { name, args } = self.unspanned() else {
+            return None;
+        };
+        let kind = [SeriesKind::Sum, SeriesKind::Product]
+            .into_iter()
+            .find(|kind| kind.name() == name)?;
+        let [index, from, to, body] = args.as_slice() else {
+            return None;
+        };
+        let Expr::Monomial {
+            coefficient,
+            variable,
+            exponent,
+        } = index.unspanned()
+        else {
+            return None;
+        };
+        (*coefficient == 1.0 && *exponent == 1.0).then_some(Series {
+            kind,
+            index: variable,
+            from,
+            to,
+            body,
+        })
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expr::Spanned { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+
+    /// The node itself, looking through any span wrappers.
+    pub fn unspanned(&self) -> &Expr {
+        match self {
+            Expr::Spanned { expr, .. } => expr.unspanned(),
+            expr => expr,
+        }
+    }
+
+    /// Removes every span from the tree.
+    pub fn strip_spans(self) -> Expr {
+        match self {
+            Expr::Spanned { expr, .. } => expr.strip_spans(),
+            Expr::UnaryMinus(expr) => Expr::UnaryMinus(Box::new(expr.strip_spans())),
+            Expr::BinOp { lhs, op, rhs } => Expr::BinOp {
+                lhs: Box::new(lhs.strip_spans()),
+                op,
+                rhs: Box::new(rhs.strip_spans()),
+            },
+            Expr::Function { name, args } => Expr::Function {
+                name,
+                args: args.into_iter().map(Expr::strip_spans).collect(),
+            },
+            Expr::Matrix(rows) => Expr::Matrix(
+                rows.into_iter()
+                    .map(|row| row.into_iter().map(Expr::strip_spans).collect())
+                    .collect(),
+            ),
+            expr => expr,
+        }
+    }
+
+    /// Names of the variables used in the tree.
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut BTreeSet<String>) {
+        match self {
+            Expr::Monomial { variable, .. } => {
+                variables.insert(variable.to_owned());
+            }
+            Expr::UnaryMinus(expr) | Expr::Spanned { expr, .. } => {
+                expr.collect_variables(variables)
+            }
+            Expr::BinOp { lhs, rhs, .. } => {
+                lhs.collect_variables(variables);
+                rhs.collect_variables(variables);
+            }
+            Expr::Function { args, .. } => match self.as_series() {
+                Some(series) => {
+                    series.from.collect_variables(variables);
+                    series.to.collect_variables(variables);
+                    let mut body = series.body.variables();
+                    body.remove(series.index);
+                    variables.extend(body);
+                }
+                None => args.iter().for_each(|arg| arg.collect_variables(variables)),
+            },
+            Expr::Matrix(rows) => rows
+                .iter()
+                .flatten()
+                .for_each(|entry| entry.collect_variables(variables)),
+            Expr::Number(_) | Expr::Rational(_) | Expr::Constant(_) => {}
+        }
+    }
+
+    /// Replaces every occurrence of `variable` with `value`. The index of a
+    /// [`Series`] is left alone inside its body.
+    pub fn substitute(&self, variable: &str, value: &Expr) -> Expr {
+        match self {
+            Expr::Function { name, args }
+                if self.as_series().map(|series| series.index) == Some(variable) =>
+            {
+                let mut args = args.clone();
+                for arg in &mut args[1..3] {
+                    *arg = arg.substitute(variable, value);
+                }
+                Expr::Function {
+                    name: name.to_owned(),
+                    args,
+                }
+            }
+            Expr::Monomial {
+                coefficient,
+                variable: name,
+                exponent,
+            } if name == variable => Expr::BinOp {
+                lhs: Box::new(Expr::Number(*coefficient)),
+                op: Op::Multiply,
+                rhs: Box::new(Expr::BinOp {
+                    lhs: Box::new(value.clone()),
+                    op: Op::Power,
+                    rhs: Box::new(Expr::Number(*exponent)),
+                }),
+            },
+            Expr::UnaryMinus(expr) => Expr::UnaryMinus(Box::new(expr.substitute(variable, value))),
+            Expr::BinOp { lhs, op, rhs } => Expr::BinOp {
+                lhs: Box::new(lhs.substitute(variable, value)),
+                op: *op,
+                rhs: Box::new(rhs.substitute(variable, value)),
+            },
+            Expr::Function { name, args } => Expr::Function {
+                name: name.to_owned(),
+                args: args
+                    .iter()
+                    .map(|arg| arg.substitute(variable, value))
+                    .collect(),
+            },
+            Expr::Matrix(rows) => Expr::Matrix(
+                rows.iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|entry| entry.substitute(variable, value))
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            Expr::Spanned { span, expr } => Expr::Spanned {
+                span: *span,
+                expr: Box::new(expr.substitute(variable, value)),
+            },
+            expr => expr.clone(),
+        }
+    }
+}
+
+impl From<Rational> for Expr {
+    /// The simplest node for `value`: a [`Expr::Number`] when it fits in an
+    /// `f64` exactly, and negative values wrapped in [`Expr::UnaryMinus`].
+    fn from(value: Rational) -> Self {
+        if value.is_negative() {
+            return Expr::UnaryMinus(Box::new(Expr::from(-value)));
+        }
+        match value.to_exact_f64() {
+            Some(n) if value.is_integer() => Expr::Number(n),
+            _ => Expr::Rational(value),
+        }
+    }
+}
+
+/// Named mathematical constants, kept symbolic until evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constant {
+    Pi,
+    E,
+    Tau,
+    Phi,
+    Infinity,
+    /// The imaginary unit `i`. It shows up in complex solutions but has no real
+    /// value.
+    ImaginaryUnit,
+    /// The Cayley–Dickson basis unit `e2`..`e15`, also written `j` and `k` for
+    /// `e2` and `e3`. The imaginary unit `i` is `e1`.
+    Unit(u8),
+}
+
+/// Names of the Cayley–Dickson basis units, by index.
+const UNIT_NAMES: [&str; 16] = [
+    "1", "i", "j", "k", "e4", "e5", "e6", "e7", "e8", "e9", "e10", "e11", "e12", "e13", "e14",
+    "e15",
+];
+
+impl Constant {
+    /// Looks up a constant by any of the names it can be written as.
+    pub fn from_name(name: &str) -> Option<Constant> {
+        match name {
+            "pi" | "π" => Some(Constant::Pi),
+            "e" => Some(Constant::E),
+            "tau" | "τ" => Some(Constant::Tau),
+            "phi" | "φ" => Some(Constant::Phi),
+            "inf" | "∞" => Some(Constant::Infinity),
+            _ => None,
+        }
+    }
+
+    /// The basis unit `e{index}`, for an index between 1 and 15.
+    pub fn unit(index: usize) -> Option<Constant> {
+        match index {
+            1 => Some(Constant::ImaginaryUnit),
+            2..=15 => Some(Constant::Unit(index as u8)),
+            _ => None,
+        }
+    }
+
+    /// Looks up a basis unit by name, `i`, `j`, `k` or `e1`..`e15`.
+    pub fn unit_from_name(name: &str) -> Option<Constant> {
+        let index = match name {
+            "e1" => 1,
+            "e2" => 2,
+            "e3" => 3,
+            name => UNIT_NAMES.iter().position(|&unit| unit == name)?,
+        };
+        Constant::unit(index)
+    }
+
+    /// The index of a basis unit, 1 for `i` up to 15 for `e15`.
+    pub fn unit_index(&self) -> Option<usize> {
+        match self {
+            Constant::ImaginaryUnit => Some(1),
+            Constant::Unit(index) => Some(*index as usize),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constant::Pi => "pi",
+            Constant::E => "e",
+            Constant::Tau => "tau",
+            Constant::Phi => "phi",
+            Constant::Infinity => "inf",
+            Constant::ImaginaryUnit => "i",
+            Constant::Unit(index) => UNIT_NAMES[*index as usize],
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        match self {
+            Constant::Pi => std::f64::consts::PI,
+            Constant::E => std::f64::consts::E,
+            Constant::Tau => std::f64::consts::TAU,
+            Constant::Phi => 1.618033988749895,
+            Constant::Infinity => f64::INFINITY,
+            Constant::ImaginaryUnit | Constant::Unit(_) => f64::NAN,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -27,38 +357,50 @@ pub enum Op {
     Modulo,
     Power,
     Equals,
+    /// Converts a quantity to the unit on the right, written `to` or `in`.
+    Convert,
 }
 
 pub trait Optimize {
     fn optimize_expression(self) -> Expr;
     fn optimize_node(&self) -> Expr;
     fn optimize_equation(self) -> Expr;
-} 
+}
 
-impl ToString for Expr {
-    fn to_string(&self) -> String {
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut out = String::new();
         match self {
             Expr::Number(val) => out.push_str(&val.to_string()),
-            Expr::UnaryMinus(expr) => out.push_str(&format!("-({})", expr.to_string())),
+            Expr::Rational(value) => {
+                // Fractions are wrapped like the quotient they read back as
+                let value = value.to_string();
+                if value.contains('/') {
+                    out.push_str(&format!("({value})"))
+                } else {
+                    out.push_str(&value)
+                }
+            }
+            Expr::UnaryMinus(expr) => out.push_str(&format!("-({expr})")),
             Expr::BinOp { lhs, op, rhs } => {
                 let lhs = lhs.to_string();
                 let rhs = rhs.to_string();
                 let op = match op {
-                    Op::Add => '+',
-                    Op::Subtract => '-',
-                    Op::Multiply => '*',
-                    Op::Divide => '/',
-                    Op::Modulo => '%',
-                    Op::Power => '^',
-                    Op::Equals => '=',
+                    Op::Add => "+",
+                    Op::Subtract => "-",
+                    Op::Multiply => "*",
+                    Op::Divide => "/",
+                    Op::Modulo => "%",
+                    Op::Power => "^",
+                    Op::Equals => "=",
+                    Op::Convert => " to ",
                 };
 
                 out.push_str(&format!("({lhs}{op}{rhs})"));
             }
             Expr::Function { name, args } => {
                 let args = args
-                    .into_iter()
+                    .iter()
                     .map(|arg| arg.to_string())
                     .collect::<Vec<String>>()
                     .join(", ");
@@ -69,7 +411,22 @@ impl ToString for Expr {
                 variable,
                 exponent,
             } => out.push_str(&format!("{coefficient}{variable}^({exponent})")),
+            Expr::Constant(constant) => out.push_str(constant.name()),
+            Expr::Matrix(rows) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|entry| entry.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    })
+                    .collect::<Vec<String>>()
+                    .join("; ");
+                out.push_str(&format!("[{rows}]"));
+            }
+            Expr::Spanned { expr, .. } => out.push_str(&expr.to_string()),
         }
-        return out;
+        write!(f, "{out}")
     }
 }
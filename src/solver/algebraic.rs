@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::calculus::build::{add, as_number, call, depends_on, div, mul, neg, number, pow, sub};
+use crate::calculus::differentiate;
+use crate::error::SolverError;
+use crate::math::round;
+use crate::numeric_evaluator::{evaluate_with, Context};
+use crate::parser::{Constant, Expr, Op, Optimize};
+
+/// The solutions of an equation for one of its variables.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solutions {
+    /// The distinct solutions, real ones in ascending order first.
+    Roots(Vec<Expr>),
+    NoSolution,
+    /// Every value of the variable solves the equation.
+    Infinite,
+}
+
+impl fmt::Display for Solutions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Solutions::Roots(roots) => {
+                let roots = roots
+                    .iter()
+                    .map(|root| root.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "{roots}")
+            }
+            Solutions::NoSolution => write!(f, "no solution"),
+            Solutions::Infinite => write!(f, "infinitely many solutions"),
+        }
+    }
+}
+
+/// Splits an equation into its two sides.
+pub(crate) fn sides(equation: &Expr) -> Result<(&Expr, &Expr), SolverError> {
+    match equation.unspanned() {
+        Expr::BinOp {
+            lhs,
+            op: Op::Equals,
+            rhs,
+        } => Ok((lhs, rhs)),
+        _ => Err(SolverError::NotAnEquation),
+    }
+}
+
+/// Whether `expr` is a polynomial in `variable`, allowing any coefficients that
+/// don't depend on it.
+fn is_polynomial(expr: &Expr, variable: &str) -> bool {
+    if !depends_on(expr, variable) {
+        return true;
+    }
+
+    match expr {
+        Expr::Monomial { exponent, .. } => exponent.fract() == 0.0 && *exponent >= 0.0,
+        Expr::UnaryMinus(inner) | Expr::Spanned { expr: inner, .. } => {
+            is_polynomial(inner, variable)
+        }
+        Expr::BinOp { lhs, op, rhs } => match op {
+            Op::Add | Op::Subtract | Op::Multiply => {
+                is_polynomial(lhs, variable) && is_polynomial(rhs, variable)
+            }
+            Op::Divide => is_polynomial(lhs, variable) && !depends_on(rhs, variable),
+            Op::Power => {
+                is_polynomial(lhs, variable)
+                    && as_number(rhs).is_some_and(|n| n.fract() == 0.0 && n >= 0.0)
+            }
+            Op::Modulo | Op::Equals | Op::Convert => false,
+        },
+        _ => false,
+    }
+}
+
+/// The value of a polynomial in `variable` at zero.
+fn at_zero(expr: &Expr, variable: &str) -> Expr {
+    match expr {
+        Expr::Monomial {
+            coefficient,
+            variable: name,
+            exponent,
+        } if name == variable => match exponent {
+            0.0 => Expr::Number(*coefficient),
+            _ => Expr::Number(0.0),
+        },
+        Expr::UnaryMinus(inner) => Expr::UnaryMinus(Box::new(at_zero(inner, variable))),
+        Expr::Spanned { expr, .. } => at_zero(expr, variable),
+        Expr::BinOp { lhs, op, rhs } => Expr::BinOp {
+            lhs: Box::new(at_zero(lhs, variable)),
+            op: *op,
+            rhs: Box::new(at_zero(rhs, variable)),
+        },
+        expr => expr.clone(),
+    }
+}
+
+/// The value of `expr` if it has no variables left.
+fn constant_value(expr: &Expr) -> Option<f64> {
+    if !expr.variables().is_empty() {
+        return None;
+    }
+    evaluate_with(expr, &Context::new()).ok()
+}
+
+/// `re + im*i`.
+fn complex(re: f64, im: f64) -> Expr {
+    let i = || Expr::Constant(Constant::ImaginaryUnit);
+    match im {
+        im if im < 0.0 => sub(number(re), mul(number(-im), i())),
+        im => add(number(re), mul(number(im), i())),
+    }
+}
+
+/// Solves a linear or quadratic `equation` for `variable`. Coefficients may
+/// contain other variables, in which case the solutions are expressed in terms
+/// of them.
+pub fn solve(equation: &Expr, variable: &str) -> Result<Solutions, SolverError> {
+    let (lhs, rhs) = sides(equation)?;
+    let unsupported = |reason: &str| SolverError::Unsupported {
+        variable: variable.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let f = sub(lhs.clone().strip_spans(), rhs.clone().strip_spans());
+    if !is_polynomial(&f, variable) {
+        return Err(unsupported("the equation is not polynomial"));
+    }
+
+    // f = a*x^2 + b*x + c, read off from the derivatives of f at zero
+    let first = differentiate(&f, variable).map_err(|error| unsupported(&error.to_string()))?;
+    let second =
+        differentiate(&first, variable).map_err(|error| unsupported(&error.to_string()))?;
+    if depends_on(&second, variable) {
+        return Err(unsupported(
+            "only linear and quadratic equations are supported",
+        ));
+    }
+
+    let a = div(at_zero(&second, variable), Expr::Number(2.0)).optimize_expression();
+    let b = at_zero(&first, variable).optimize_expression();
+    let c = at_zero(&f, variable).optimize_expression();
+
+    let values = (constant_value(&a), constant_value(&b), constant_value(&c));
+    if let (Some(a), Some(b), Some(c)) = values {
+        return Ok(solve_numeric(a, b, c));
+    }
+    // Coefficients that are known numbers are folded into the solutions
+    let fold = |expr: Expr, value: Option<f64>| value.map(number).unwrap_or(expr);
+    let (a, b, c) = (fold(a, values.0), fold(b, values.1), fold(c, values.2));
+
+    match values {
+        (Some(0.0), Some(0.0), _) => Err(unsupported(
+            "whether there are solutions depends on the other variables",
+        )),
+        (Some(0.0), _, _) => Ok(Solutions::Roots(vec![div(neg(c), b).optimize_expression()])),
+        _ => {
+            // (-b ± sqrt(b^2 - 4ac))/2a
+            let discriminant = sub(
+                pow(b.clone(), Expr::Number(2.0)),
+                mul(Expr::Number(4.0), mul(a.clone(), c)),
+            );
+            let root = call("sqrt", vec![discriminant]);
+            let denominator = mul(Expr::Number(2.0), a);
+            let roots = [sub(neg(b.clone()), root.clone()), add(neg(b), root)]
+                .into_iter()
+                .map(|numerator| div(numerator, denominator.clone()).optimize_expression())
+                .collect();
+            Ok(Solutions::Roots(roots))
+        }
+    }
+}
+
+fn solve_numeric(a: f64, b: f64, c: f64) -> Solutions {
+    if a == 0.0 {
+        return match (b, c) {
+            (0.0, 0.0) => Solutions::Infinite,
+            (0.0, _) => Solutions::NoSolution,
+            (b, c) => Solutions::Roots(vec![number(round(-c / b, 15))]),
+        };
+    }
+
+    let discriminant = round(b * b - 4.0 * a * c, 15);
+    let vertex = -b / (2.0 * a);
+    let spread = discriminant.abs().sqrt() / (2.0 * a).abs();
+
+    let roots = match discriminant {
+        0.0 => vec![number(round(vertex, 15))],
+        d if d > 0.0 => vec![
+            number(round(vertex - spread, 15)),
+            number(round(vertex + spread, 15)),
+        ],
+        _ => vec![
+            complex(round(vertex, 15), -round(spread, 15)),
+            complex(round(vertex, 15), round(spread, 15)),
+        ],
+    };
+    Solutions::Roots(roots)
+}
@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::algebra::multivariate::{Atom, MultiPolynomial};
+use crate::error::SolverError;
+use crate::parser::Expr;
+
+use super::algebraic::sides;
+
+/// The solution of a system of linear equations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemSolution {
+    /// The value of every variable, in alphabetical order.
+    Unique(Vec<(String, Expr)>),
+    /// The value of every variable in terms of the free `parameters`, which
+    /// can take any value. The parameters are their own values.
+    Parametric {
+        values: Vec<(String, Expr)>,
+        parameters: Vec<String>,
+    },
+    /// The equations contradict each other.
+    Inconsistent,
+}
+
+impl fmt::Display for SystemSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = |values: &[(String, Expr)], parameters: &[String]| {
+            values
+                .iter()
+                .filter(|(variable, _)| !parameters.contains(variable))
+                .map(|(variable, value)| format!("{variable} = {value}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        match self {
+            SystemSolution::Unique(solution) => write!(f, "{}", values(solution, &[])),
+            SystemSolution::Parametric {
+                values: solution,
+                parameters,
+            } => write!(
+                f,
+                "{} for any {}",
+                values(solution, parameters),
+                parameters.join(", ")
+            ),
+            SystemSolution::Inconsistent => write!(f, "no solution"),
+        }
+    }
+}
+
+/// The coefficient of each variable in a linear polynomial and its constant
+/// term, `None` if the polynomial isn't linear.
+fn linear_coefficients(
+    polynomial: &MultiPolynomial,
+) -> Option<(BTreeMap<String, BigRational>, BigRational)> {
+    let mut coefficients = BTreeMap::new();
+    let mut constant = BigRational::zero();
+    for (powers, coefficient) in polynomial.terms() {
+        let mut atoms = powers.iter();
+        match (atoms.next(), atoms.next()) {
+            (None, _) => constant = coefficient.clone(),
+            (Some((Atom::Variable(name), 1)), None) => {
+                coefficients.insert(name.clone(), coefficient.clone());
+            }
+            _ => return None,
+        }
+    }
+    Some((coefficients, constant))
+}
+
+/// Solves a system of linear equations for all of their variables with
+/// Gaussian elimination over the rationals, so the solution is exact.
+/// Variables left undetermined are taken as parameters, preferring the ones
+/// that come last alphabetically.
+pub fn solve_system(equations: &[Expr]) -> Result<SystemSolution, SolverError> {
+    let mut forms = Vec::with_capacity(equations.len());
+    for (index, equation) in equations.iter().enumerate() {
+        let (lhs, rhs) = sides(equation)?;
+        let polynomial = MultiPolynomial::from_expr(lhs).sub(&MultiPolynomial::from_expr(rhs));
+        let form = linear_coefficients(&polynomial).ok_or(SolverError::NotLinear(index + 1))?;
+        forms.push(form);
+    }
+
+    let variables: Vec<String> = forms
+        .iter()
+        .flat_map(|(coefficients, _)| coefficients.keys().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    // Each row holds the coefficients followed by the right hand side
+    let mut rows: Vec<Vec<BigRational>> = forms
+        .into_iter()
+        .map(|(mut coefficients, constant)| {
+            let mut row: Vec<BigRational> = variables
+                .iter()
+                .map(|variable| {
+                    coefficients
+                        .remove(variable)
+                        .unwrap_or_else(BigRational::zero)
+                })
+                .collect();
+            row.push(-constant);
+            row
+        })
+        .collect();
+
+    let pivots = reduce(&mut rows, variables.len());
+
+    let consistent = rows
+        .iter()
+        .skip(pivots.len())
+        .all(|row| row[variables.len()].is_zero());
+    if !consistent {
+        return Ok(SystemSolution::Inconsistent);
+    }
+
+    let parameters: Vec<String> = (0..variables.len())
+        .filter(|column| !pivots.contains(column))
+        .map(|column| variables[column].clone())
+        .collect();
+
+    let mut values: Vec<(String, Expr)> = Vec::with_capacity(variables.len());
+    for (column, variable) in variables.iter().enumerate() {
+        let value = match pivots.iter().position(|&pivot| pivot == column) {
+            Some(row) => {
+                let row = &rows[row];
+                let mut value = MultiPolynomial::constant(row[variables.len()].clone());
+                for free in (0..variables.len()).filter(|column| !pivots.contains(column)) {
+                    value =
+                        value.sub(&MultiPolynomial::variable(&variables[free]).scale(&row[free]));
+                }
+                value.to_expr()
+            }
+            None => MultiPolynomial::variable(variable).to_expr(),
+        };
+        values.push((variable.clone(), value));
+    }
+
+    if parameters.is_empty() {
+        Ok(SystemSolution::Unique(values))
+    } else {
+        Ok(SystemSolution::Parametric { values, parameters })
+    }
+}
+
+/// Brings `rows` into reduced row echelon form over their first `columns`
+/// columns, returning the column of the pivot in each leading row.
+fn reduce(rows: &mut [Vec<BigRational>], columns: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for column in 0..columns {
+        let row = pivots.len();
+        let Some(pivot) = (row..rows.len()).find(|&i| !rows[i][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(row, pivot);
+
+        let lead = rows[row][column].recip();
+        for value in rows[row].iter_mut() {
+            *value *= &lead;
+        }
+
+        let pivot_row = rows[row].clone();
+        for (i, other) in rows.iter_mut().enumerate() {
+            if i == row || other[column].is_zero() {
+                continue;
+            }
+            let factor = other[column].clone();
+            for (value, pivot_value) in other.iter_mut().zip(&pivot_row) {
+                *value -= &factor * pivot_value;
+            }
+        }
+        pivots.push(column);
+    }
+    pivots
+}
@@ -0,0 +1,9 @@
+//! Solving equations for their variables.
+
+mod algebraic;
+mod linear_system;
+mod numeric;
+
+pub use algebraic::{solve, Solutions};
+pub use linear_system::{solve_system, SystemSolution};
+pub use numeric::{numeric_solve, NumericSolveOptions, Root, RootMethod};
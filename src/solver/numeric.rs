@@ -0,0 +1,305 @@
+use crate::calculus::build::sub;
+use crate::calculus::differentiate;
+use crate::error::{Error, EvaluatorError};
+use crate::numeric_evaluator::{evaluate_with, Context};
+use crate::parser::Expr;
+
+use super::algebraic::sides;
+
+/// Settings for [`numeric_solve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericSolveOptions {
+    /// Interval searched for roots, both ends included.
+    pub start: f64,
+    pub end: f64,
+    /// Number of pieces the interval is split into when looking for sign
+    /// changes. Roots closer together than one piece may be missed.
+    pub subdivisions: usize,
+    /// Roots are refined until they move less than this.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for NumericSolveOptions {
+    fn default() -> Self {
+        Self {
+            start: -10.0,
+            end: 10.0,
+            subdivisions: 1000,
+            tolerance: 1e-12,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Method a root was found with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootMethod {
+    /// The equation holds exactly at one of the sampled points.
+    Exact,
+    /// Brent's method on an interval where the equation changes sign.
+    Brent,
+    /// Newton's method from a point where the equation comes close to zero
+    /// without changing sign.
+    Newton,
+}
+
+/// A root found by [`numeric_solve`] together with how it was found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Root {
+    pub value: f64,
+    pub method: RootMethod,
+    pub iterations: usize,
+    /// Difference between the sides of the equation at `value`.
+    pub residual: f64,
+    /// Whether `value` moved less than the tolerance in the last step.
+    pub converged: bool,
+}
+
+/// Finds the real roots of `equation` for `variable` within the interval given
+/// by `options`, sorted in ascending order. The derivative used by Newton's
+/// method is computed symbolically when possible.
+pub fn numeric_solve(
+    equation: &Expr,
+    variable: &str,
+    options: &NumericSolveOptions,
+) -> Result<Vec<Root>, Error> {
+    let (lhs, rhs) = sides(equation)?;
+    let f = sub(lhs.clone().strip_spans(), rhs.clone().strip_spans());
+    let derivative = differentiate(&f, variable).ok();
+
+    let mut ctx = Context::new();
+    let mut at = |expr: &Expr, x: f64| {
+        ctx.set_variable(variable, x);
+        evaluate_with(expr, &ctx)
+    };
+
+    let (start, end) = (
+        options.start.min(options.end),
+        options.start.max(options.end),
+    );
+    let pieces = options.subdivisions.max(1);
+    let mut samples = Vec::with_capacity(pieces + 1);
+    let mut first_error: Option<EvaluatorError> = None;
+    for i in 0..=pieces {
+        let x = start + (end - start) * i as f64 / pieces as f64;
+        let y = match at(&f, x) {
+            Ok(y) => y,
+            Err(error) => {
+                first_error.get_or_insert(error);
+                f64::NAN
+            }
+        };
+        samples.push((x, y));
+    }
+    if let (true, Some(error)) = (samples.iter().all(|(_, y)| y.is_nan()), first_error) {
+        return Err(error.into());
+    }
+
+    let mut roots: Vec<Root> = Vec::new();
+    let mut eval = |x: f64| at(&f, x).unwrap_or(f64::NAN);
+
+    for (i, &(x, y)) in samples.iter().enumerate() {
+        if y == 0.0 {
+            roots.push(Root {
+                value: x,
+                method: RootMethod::Exact,
+                iterations: 0,
+                residual: 0.0,
+                converged: true,
+            });
+            continue;
+        }
+
+        let Some(&(next_x, next_y)) = samples.get(i + 1) else {
+            continue;
+        };
+        if y * next_y < 0.0 {
+            roots.extend(brent(&mut eval, (x, y), (next_x, next_y), options));
+        }
+    }
+
+    // Roots where the equation touches zero without crossing it
+    for window in samples.windows(3) {
+        let [(_, a), (x, y), (_, b)] = [window[0], window[1], window[2]];
+        let dips = y.abs() < a.abs() && y.abs() < b.abs() && a * y > 0.0 && y * b > 0.0;
+        if dips {
+            let slope = Slope {
+                derivative: derivative.as_ref(),
+                variable,
+            };
+            if let Some(root) = newton(&mut eval, &slope, x, (start, end), options) {
+                roots.push(root);
+            }
+        }
+    }
+
+    roots.sort_by(|a, b| a.value.total_cmp(&b.value));
+    roots.dedup_by(|a, b| (a.value - b.value).abs() <= 1e3 * options.tolerance.max(f64::EPSILON));
+    Ok(roots)
+}
+
+/// Derivative of the equation, approximated by central differences when it
+/// couldn't be found symbolically.
+struct Slope<'a> {
+    derivative: Option<&'a Expr>,
+    variable: &'a str,
+}
+
+impl Slope<'_> {
+    fn at(&self, f: &mut dyn FnMut(f64) -> f64, x: f64) -> f64 {
+        match self.derivative {
+            Some(derivative) => {
+                let ctx = Context::new().with_variable(self.variable, x);
+                evaluate_with(derivative, &ctx).unwrap_or(f64::NAN)
+            }
+            None => {
+                let h = 1e-6 * x.abs().max(1.0);
+                (f(x + h) - f(x - h)) / (2.0 * h)
+            }
+        }
+    }
+}
+
+/// Whether `residual` is small enough for `x` to count as a root rather than a
+/// pole or a near miss.
+fn is_root(residual: f64, scale: f64) -> bool {
+    residual.abs() <= 1e-6 * scale.max(1.0)
+}
+
+/// Brent's method on an interval whose ends have values of opposite sign.
+fn brent(
+    f: &mut dyn FnMut(f64) -> f64,
+    (mut a, mut fa): (f64, f64),
+    (mut b, mut fb): (f64, f64),
+    options: &NumericSolveOptions,
+) -> Option<Root> {
+    let scale = fa.abs().min(fb.abs());
+    let (mut c, mut fc) = (a, fa);
+    let (mut d, mut e) = (b - a, b - a);
+
+    for iteration in 1..=options.max_iterations {
+        if fb * fc > 0.0 {
+            (c, fc) = (a, fa);
+            (d, e) = (b - a, b - a);
+        }
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + 0.5 * options.tolerance;
+        let midpoint = 0.5 * (c - b);
+        if midpoint.abs() <= tolerance || fb == 0.0 {
+            return is_root(fb, scale).then_some(Root {
+                value: b,
+                method: RootMethod::Brent,
+                iterations: iteration,
+                residual: fb,
+                converged: true,
+            });
+        }
+
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            // Inverse quadratic interpolation, or the secant method when only
+            // two points are distinct
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * midpoint * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * midpoint * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+
+            if 2.0 * p < (3.0 * midpoint * q - (tolerance * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                (d, e) = (midpoint, midpoint);
+            }
+        } else {
+            // Bisection
+            (d, e) = (midpoint, midpoint);
+        }
+
+        (a, fa) = (b, fb);
+        b += if d.abs() > tolerance {
+            d
+        } else {
+            tolerance.copysign(midpoint)
+        };
+        fb = f(b);
+        if fb.is_nan() {
+            return None;
+        }
+    }
+
+    is_root(fb, scale).then_some(Root {
+        value: b,
+        method: RootMethod::Brent,
+        iterations: options.max_iterations,
+        residual: fb,
+        converged: false,
+    })
+}
+
+/// Newton's method from `x`, giving up when it leaves `interval`.
+fn newton(
+    f: &mut dyn FnMut(f64) -> f64,
+    slope: &Slope,
+    mut x: f64,
+    (start, end): (f64, f64),
+    options: &NumericSolveOptions,
+) -> Option<Root> {
+    for iteration in 1..=options.max_iterations {
+        let fx = f(x);
+        let slope = slope.at(f, x);
+        if fx == 0.0 {
+            return Some(Root {
+                value: x,
+                method: RootMethod::Newton,
+                iterations: iteration,
+                residual: 0.0,
+                converged: true,
+            });
+        }
+        if slope == 0.0 || !slope.is_finite() || !fx.is_finite() {
+            return None;
+        }
+
+        let step = fx / slope;
+        x -= step;
+        if !(start..=end).contains(&x) {
+            return None;
+        }
+
+        if step.abs() <= options.tolerance * x.abs().max(1.0) {
+            let residual = f(x);
+            return is_root(residual, 1.0).then_some(Root {
+                value: x,
+                method: RootMethod::Newton,
+                iterations: iteration,
+                residual,
+                converged: true,
+            });
+        }
+    }
+
+    let residual = f(x);
+    is_root(residual, 1.0).then_some(Root {
+        value: x,
+        method: RootMethod::Newton,
+        iterations: options.max_iterations,
+        residual,
+        converged: false,
+    })
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod test {
+    use crate::algebra::{expand, factor};
+    use crate::parser::{parse, parse_equation};
+
+    fn expanded(expression: &str) -> String {
+        expand(&parse(expression).unwrap()).to_string()
+    }
+
+    fn factored(expression: &str) -> String {
+        factor(&parse(expression).unwrap()).to_string()
+    }
+
+    #[test]
+    fn can_expand_products_and_powers() {
+        assert_eq!("((1x^(2)+2x^(1))+1)", expanded("(x+1)^2"));
+        assert_eq!("(1x^(2)-1)", expanded("(x+1)(x-1)"));
+        assert_eq!("(1x^(2)-1y^(2))", expanded("(x+y)(x-y)"));
+        assert_eq!(
+            "(((1a^(3)+(3a^(2)*1b^(1)))+(3a^(1)*1b^(2)))+1b^(3))",
+            expanded("(a+b)^3")
+        );
+    }
+
+    #[test]
+    fn collects_like_terms() {
+        assert_eq!("(3x^(1)+3)", expanded("2x + 3 + x"));
+        assert_eq!("(2x^(1)*1y^(1))", expanded("x y + y x"));
+        assert_eq!("(5x^(1)/6)", expanded("x/2 + x/3"));
+        assert_eq!("0", expanded("(X+1)^2 - (X^2+2X+1)"));
+    }
+
+    #[test]
+    fn equivalent_polynomials_print_identically() {
+        let forms = [
+            "(x+1)^2 + y",
+            "y + 1 + x*(x+2)",
+            "(1+x)(x+1) + y",
+            "x^2 + y + 2x + 1",
+        ];
+        for form in forms {
+            assert_eq!(expanded(forms[0]), expanded(form));
+        }
+    }
+
+    #[test]
+    fn keeps_parts_that_are_not_polynomial() {
+        assert_eq!("(3*sin(2x^(1)))", expanded("sin(x+x) + 2sin(2x)"));
+        assert_eq!("(2x^(1)*pi)", expanded("pi x + x pi"));
+        assert_eq!("((1x^(1)+1)/(1x^(1)-1))", expanded("(x+1)/(x-1)"));
+    }
+
+    #[test]
+    fn keeps_large_powers_opaque() {
+        assert!(expanded("(x+1)^32").ends_with("+32x^(1))+1)"));
+        assert!(expanded("(a+b+c)^32").ends_with("+1c^(32))"));
+        assert!(expanded("((x+1)^32)^32").ends_with("^32)"));
+        assert!(expanded("(a+b+c+d+e+f)^32").ends_with("^32)"));
+    }
+
+    #[test]
+    fn keeps_matrix_products_in_order() {
+        assert_ne!(
+            "0",
+            expanded("[1, 2; 3, 4]*[5, 6; 7, 8] - [5, 6; 7, 8]*[1, 2; 3, 4]")
+        );
+        // (A + B)^2 is A^2 + AB + BA + B^2, not A^2 + 2AB + B^2
+        assert_ne!(
+            "0",
+            expanded("([1, 2; 3, 4] + [5, 6; 7, 8])^2 - ([1, 2; 3, 4]^2 + 2*[1, 2; 3, 4]*[5, 6; 7, 8] + [5, 6; 7, 8]^2)")
+        );
+        assert_ne!("0", factored("[1, 2; 3, 4]*x - x*[1, 2; 3, 4]"));
+        assert_eq!("0", expanded("[1, 2; 3, 4]*x - [1, 2; 3, 4]*x"));
+    }
+
+    #[test]
+    fn keeps_large_coefficients_exact() {
+        assert!(expanded("(x+1)^60").contains("118264581564861424"));
+        let result = expanded("100000000000000000001*x");
+        assert_eq!("(100000000000000000001*1x^(1))", result);
+        assert_eq!(result, expanded(&result));
+    }
+
+    #[test]
+    fn expanded_expressions_round_trip() {
+        for expression in ["(a+b)^3", "(x-1/2)^2", "-(x-1)y", "2^-1 x", "x^0.5 x^0.5"] {
+            let result = expanded(expression);
+            assert_eq!(result, expanded(&result));
+        }
+    }
+
+    #[test]
+    fn expands_both_sides_of_equations() {
+        let equation = parse_equation("(x+1)^2 = x*(x+2)").unwrap();
+        assert_eq!(
+            "(((1x^(2)+2x^(1))+1)=(1x^(2)+2x^(1)))",
+            expand(&equation).to_string()
+        );
+    }
+
+    #[test]
+    fn can_factor_out_common_factors() {
+        assert_eq!("(2x^(1)*(1x^(1)+2))", factored("2x^2 + 4x"));
+        assert_eq!("(3y^(1)*(1x^(1)+2))", factored("3x y + 6y"));
+        assert_eq!("(((1x^(1)-1)*(1x^(1)+1))/2)", factored("x^2/2 - 1/2"));
+        assert_eq!("-(((1x^(1)-1)*(1x^(1)+1)))", factored("1 - x^2"));
+    }
+
+    #[test]
+    fn can_factor_differences_of_squares() {
+        assert_eq!("((2x^(1)-3y^(1))*(2x^(1)+3y^(1)))", factored("4x^2 - 9y^2"));
+        assert_eq!("(((1x^(1)-2)*(1x^(1)+2))*(1x^(2)+4))", factored("x^4 - 16"));
+        assert_eq!(
+            "((sin(1x^(1))-1)*(sin(1x^(1))+1))",
+            factored("sin(x)^2 - 1")
+        );
+    }
+
+    #[test]
+    fn can_factor_by_rational_roots() {
+        assert_eq!("((2x^(1)-1)*(3x^(1)-1))", factored("6x^2 - 5x + 1"));
+        assert_eq!(
+            "(((1x^(1)-2)*(1x^(1)+1))*(2x^(1)-1))",
+            factored("2x^3 - 3x^2 - 3x + 2")
+        );
+        assert_eq!(
+            "(((1x^(1)-1)*(1x^(2)-2))*(1x^(2)+2))",
+            factored("x^5 - x^4 - 4x + 4")
+        );
+    }
+
+    #[test]
+    fn bounds_the_rational_root_search() {
+        assert_eq!(
+            "((1x^(1)+1)*((720720x^(2)-720720x^(1))+720721))",
+            factored("720720x^3 + x + 720721")
+        );
+        assert_eq!(
+            "((7207200x^(3)+1x^(1))+7207200)",
+            factored("7207200x^3 + x + 7207200")
+        );
+        assert_eq!(
+            "((963761198400x^(3)+1x^(1))+963761198400)",
+            factored("963761198400x^3 + x + 963761198400")
+        );
+    }
+
+    #[test]
+    fn can_factor_repeated_factors() {
+        assert_eq!("((1x^(1)+1)^2)", factored("x^2 + 2x + 1"));
+        assert_eq!(
+            "(((1x^(1)-1)^2)*((1x^(1)+2)^3))",
+            factored("(x-1)^2 (x+2)^3")
+        );
+    }
+
+    #[test]
+    fn keeps_irreducible_polynomials() {
+        assert_eq!("(1x^(2)-2)", factored("x^2 - 2"));
+        assert_eq!("(1x^(2)+1)", factored("x^2 + 1"));
+        assert_eq!("(1x^(3)-2)", factored("x^3 - 2"));
+        assert_eq!("12", factored("12"));
+    }
+
+    #[test]
+    fn keeps_large_contents_exact() {
+        for expression in [
+            "100000000000000000001*x",
+            "100000000000000000001*x^2 - 100000000000000000001",
+            "x/100000000000000000001 + 1/100000000000000000001",
+        ] {
+            let result = factored(expression);
+            assert_eq!(expanded(expression), expanded(&result), "{result}");
+        }
+        assert!(factored("100000000000000000001*x").contains("100000000000000000001"));
+    }
+
+    #[test]
+    fn factored_expressions_round_trip() {
+        let expressions = [
+            "x^3 - x",
+            "6x^2 - 5x + 1",
+            "x^2 y - y^3",
+            "(x^2-1)^2",
+            "-2x^3 + 2x",
+        ];
+        for expression in expressions {
+            let reparsed = parse(&factored(expression)).unwrap();
+            assert_eq!(expanded(expression), expand(&reparsed).to_string());
+        }
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{evaluate, evaluate_with, AngleMode, Context};
+    use crate::parser::parse;
+
+    fn eval(expression: &str) -> f64 {
+        evaluate(expression).unwrap()
+    }
+
+    fn eval_degrees(expression: &str) -> f64 {
+        let ctx = Context::new().with_angle_mode(AngleMode::Degrees);
+        evaluate_with(&parse(expression).unwrap(), &ctx).unwrap()
+    }
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn is_domain_error(expression: &str) -> bool {
+        match evaluate(expression).unwrap_err() {
+            Error::Evaluator(error) => {
+                matches!(error.without_span(), EvaluatorError::Domain { .. })
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn can_eval_inverse_trig() {
+        assert_close(30.0, eval_degrees("asin(0.5)"));
+        assert_close(60.0, eval_degrees("acos(0.5)"));
+        assert_eq!(135.0, eval_degrees("atan2(1, -1)"));
+        assert_eq!(0.0, eval("atan2(0, 1)"));
+    }
+
+    #[test]
+    fn can_eval_reciprocal_trig() {
+        assert_close(2.0, eval_degrees("sec(60)"));
+        assert_close(2.0, eval_degrees("csc(30)"));
+        assert_close(1.0, eval_degrees("cot(45)"));
+    }
+
+    #[test]
+    fn reports_reciprocal_trig_poles() {
+        let ctx = Context::new().with_angle_mode(AngleMode::Degrees);
+        for expression in ["sec(90)", "sec(270)", "csc(180)", "cot(180)"] {
+            let error = evaluate_with(&parse(expression).unwrap(), &ctx).unwrap_err();
+            assert!(
+                matches!(error.without_span(), EvaluatorError::Domain { .. }),
+                "{expression} gave {error:?}"
+            );
+        }
+        assert!(is_domain_error("sec(pi/2)"));
+        assert!(is_domain_error("csc(pi)"));
+    }
+
+    #[test]
+    fn can_eval_hyperbolic() {
+        assert_eq!(0.0, eval("sinh(0)"));
+        assert_eq!(1.0, eval("cosh(0)"));
+        assert_eq!(0.0, eval("tanh(0)"));
+        assert_eq!(1.0, eval("asinh(sinh(1))"));
+        assert_eq!(2.0, eval("acosh(cosh(2))"));
+        assert_eq!(0.5, eval("atanh(tanh(0.5))"));
+    }
+
+    #[test]
+    fn can_eval_logarithms() {
+        assert_eq!(1.0, eval("ln(exp(1))"));
+        assert_eq!(2.0, eval("log(100)"));
+        assert_eq!(3.0, eval("log10(1000)"));
+        assert_eq!(5.0, eval("log2(32)"));
+        assert_eq!(4.0, eval("log(3, 81)"));
+    }
+
+    #[test]
+    fn can_eval_algebraic() {
+        assert_eq!(3.5, eval("abs(-3.5)"));
+        assert_eq!(-1.0, eval("sign(-3.5)"));
+        assert_eq!(0.0, eval("sign(0)"));
+        assert_eq!(3.0, eval("cbrt(27)"));
+        assert_eq!(2.0, eval("nthroot(16, 4)"));
+        assert_eq!(-2.0, eval("nthroot(-8, 3)"));
+        assert_eq!(5.0, eval("hypot(3, 4)"));
+        assert_eq!(13.0, eval("hypot(3, 4, 12)"));
+    }
+
+    #[test]
+    fn can_eval_integer_functions() {
+        assert_eq!(6.0, eval("gcd(12, 18)"));
+        assert_eq!(4.0, eval("gcd(8, 12, 20)"));
+        assert_eq!(36.0, eval("lcm(12, 18)"));
+        assert_eq!(9999999990000000000.0, eval("lcm(10000000000, 9999999990)"));
+        assert_eq!(120.0, eval("factorial(5)"));
+        assert_eq!(1.0, eval("factorial(0)"));
+        assert_eq!(10.0, eval("binomial(5, 2)"));
+        assert_eq!(0.0, eval("binomial(2, 5)"));
+        assert_close(1.0, eval("factorial(150)") / 5.713383956445855e262);
+        assert_eq!(1_000_000_000_000.0, eval("binomial(1000000000000, 1)"));
+        assert_close(1.0, eval("binomial(200, 100)") / 9.054851465610328e58);
+    }
+
+    #[test]
+    fn reports_domain_errors() {
+        assert!(is_domain_error("sqrt(-1)"));
+        assert!(is_domain_error("ln(0)"));
+        assert!(is_domain_error("log(-2)"));
+        assert!(is_domain_error("log(1, 5)"));
+        assert!(is_domain_error("asin(2)"));
+        assert!(is_domain_error("acosh(0.5)"));
+        assert!(is_domain_error("atanh(1)"));
+        assert!(is_domain_error("nthroot(-16, 4)"));
+        assert!(is_domain_error("factorial(2.5)"));
+        assert!(is_domain_error("factorial(-1)"));
+        assert!(is_domain_error("factorial(171)"));
+        assert!(is_domain_error("factorial(1000000000000)"));
+        assert!(is_domain_error("binomial(2000, 1000)"));
+        assert!(is_domain_error("lcm(10000000000, 9999999999)"));
+        assert!(is_domain_error("gcd(1.5, 3)"));
+        assert!(is_domain_error("pow(-8, 0.5)"));
+    }
+}
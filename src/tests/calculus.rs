@@ -0,0 +1,294 @@
+#[cfg(test)]
+mod test {
+    use crate::calculus::{differentiate, integrate, integrate_definite};
+    use crate::error::{CalculusError, Error};
+    use crate::numeric_evaluator::{evaluate_with, Context};
+    use crate::parser::{parse, parse_equation};
+
+    fn derivative(expression: &str) -> String {
+        differentiate(&parse(expression).unwrap(), "x")
+            .unwrap()
+            .to_string()
+    }
+
+    /// Compares the derivative of `expression` at `x` with a central difference.
+    fn assert_slope(expression: &str, x: f64) {
+        let expr = parse(expression).unwrap();
+        let at = |x: f64, expr| {
+            let ctx = Context::new().with_variable("x", x);
+            evaluate_with(expr, &ctx).unwrap()
+        };
+
+        let h = 1e-6;
+        let expected = (at(x + h, &expr) - at(x - h, &expr)) / (2.0 * h);
+        let actual = at(x, &differentiate(&expr, "x").unwrap());
+        assert!(
+            (expected - actual).abs() < 1e-5 * expected.abs().max(1.0),
+            "d/dx {expression} at {x}: expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn can_differentiate_polynomials() {
+        assert_eq!("0", derivative("5"));
+        assert_eq!("1", derivative("x"));
+        assert_eq!("3x^(2)", derivative("x^3"));
+        assert_eq!("(6x^(1)+2)", derivative("3x^2 + 2x + 1"));
+        assert_eq!("-(2)", derivative("4 - 2x"));
+    }
+
+    #[test]
+    fn other_variables_are_constants() {
+        assert_eq!("0", derivative("y^2 + pi"));
+        assert_eq!("1y^(1)", derivative("y*x"));
+    }
+
+    #[test]
+    fn can_differentiate_functions() {
+        assert_eq!("cos(1x^(1))", derivative("sin(x)"));
+        assert_eq!("sin(1x^(1))", derivative("-cos(x)"));
+        assert_eq!("(2*exp(2x^(1)))", derivative("exp(2x)"));
+        assert_eq!("(2x^(1)/(1x^(2)+1))", derivative("ln(x^2 + 1)"));
+        assert_eq!("(1/(1x^(1)*ln(2)))", derivative("log(2, x)"));
+    }
+
+    #[test]
+    fn product_and_quotient_rules() {
+        assert_eq!("(sin(1x^(1))+(1x^(1)*cos(1x^(1))))", derivative("x sin(x)"));
+        assert_slope("sin(x)/x", 0.7);
+        assert_slope("(x^2 + 1)/(x - 3)", 1.5);
+        assert_slope("x % 3", 4.2);
+    }
+
+    #[test]
+    fn power_rule() {
+        assert_eq!("(3*((1x^(1)+1)^2))", derivative("(x+1)^3"));
+        assert_slope("2^x", 1.3);
+        assert_slope("x^x", 1.3);
+        assert_slope("e^(x^2)", 0.4);
+        assert_slope("pow(x, 2.5)", 2.0);
+    }
+
+    #[test]
+    fn chain_rule_for_builtins() {
+        for expression in [
+            "sin(2x)",
+            "cos(x^2)",
+            "tan(x)",
+            "sec(x)",
+            "csc(x)",
+            "cot(x)",
+            "asin(x/2)",
+            "acos(x/2)",
+            "atan(3x)",
+            "atan2(x, 2)",
+            "atan2(1, x)",
+            "sinh(x)",
+            "cosh(x)",
+            "tanh(x)",
+            "asinh(x)",
+            "acosh(x + 1)",
+            "atanh(x/2)",
+            "exp(x)",
+            "ln(x)",
+            "log(x)",
+            "log10(x)",
+            "log2(x)",
+            "log(x, 8)",
+            "abs(x - 3)",
+            "fract(x)",
+            "floor(x)",
+            "sqrt(x)",
+            "cbrt(x)",
+            "nthroot(x, 5)",
+            "hypot(x, 3)",
+            "hypot(x, 2x, 2)",
+            "min(x, 1)",
+            "max(x, 1)",
+            "min(x^2, 2)",
+            "min(x, 2, x^2)",
+            "max(x, 1, 3x)",
+            "sum(x, x^2, 3)",
+            "mean(x, x^2)",
+            "product(x, x^2, 2)",
+        ] {
+            assert_slope(expression, 0.8);
+        }
+    }
+
+    #[test]
+    fn differentiates_both_sides_of_equations() {
+        let equation = parse_equation("x^2 = 3x").unwrap();
+        assert_eq!(
+            "(2x^(1)=3)",
+            differentiate(&equation, "x").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn fails_on_unsupported_functions() {
+        assert!(matches!(
+            differentiate(&parse("f(x)").unwrap(), "x"),
+            Err(CalculusError::UnknownFunction { .. })
+        ));
+        assert!(matches!(
+            differentiate(&parse("median(x, 1, 2)").unwrap(), "x"),
+            Err(CalculusError::NotDifferentiable(_))
+        ));
+        assert!(matches!(
+            differentiate(&parse("factorial(x)").unwrap(), "x"),
+            Err(CalculusError::NotDifferentiable(_))
+        ));
+        assert_eq!("0", derivative("factorial(3) + f(2)"));
+    }
+
+    fn integral(expression: &str) -> String {
+        integrate(&parse(expression).unwrap(), "x")
+            .unwrap()
+            .to_string()
+    }
+
+    /// Checks that the derivative of the antiderivative of `expression` is
+    /// `expression` again at `x`.
+    fn assert_antiderivative(expression: &str, x: f64) {
+        let expr = parse(expression).unwrap();
+        let antiderivative = integrate(&expr, "x").unwrap();
+        let ctx = Context::new().with_variable("x", x);
+
+        let expected = evaluate_with(&expr, &ctx).unwrap();
+        let actual = evaluate_with(&differentiate(&antiderivative, "x").unwrap(), &ctx).unwrap();
+        assert!(
+            (expected - actual).abs() < 1e-9 * expected.abs().max(1.0),
+            "∫{expression} = {antiderivative} at {x}: expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn can_integrate_polynomials() {
+        assert_eq!("5x^(1)", integral("5"));
+        assert_eq!("0.25x^(4)", integral("x^3"));
+        assert_eq!("((1x^(3)+1x^(2))+1x^(1))", integral("3x^2 + 2x + 1"));
+        assert_eq!("ln(abs(1x^(1)))", integral("1/x"));
+        assert_eq!("-0.5x^(-2)", integral("1/x^3"));
+        assert_eq!("(1y^(1)*1x^(1))", integral("y"));
+    }
+
+    #[test]
+    fn can_integrate_elementary_functions() {
+        assert_eq!("-(cos(1x^(1)))", integral("sin(x)"));
+        assert_eq!("(0.5*exp(2x^(1)))", integral("exp(2x)"));
+        assert_eq!("((1x^(1)*ln(1x^(1)))-1x^(1))", integral("ln(x)"));
+        assert_eq!("tan(1x^(1))", integral("sec(x)^2"));
+        for expression in [
+            "cos(3x + 1)",
+            "tan(x)",
+            "sec(x)",
+            "csc(x)",
+            "cot(x)",
+            "sinh(x)",
+            "cosh(x)",
+            "tanh(x)",
+            "e^x",
+            "2^x",
+            "log(x)",
+            "log2(x)",
+            "asin(x)",
+            "acos(x)",
+            "atan(x)",
+            "sqrt(x)",
+            "cbrt(2x + 1)",
+            "abs(x - 1)",
+            "sin(x)^2",
+            "cos(x)^2",
+            "(2x + 1)^5",
+        ] {
+            assert_antiderivative(expression, 0.6);
+        }
+    }
+
+    #[test]
+    fn can_integrate_rational_functions() {
+        assert_eq!("atan(1x^(1))", integral("1/(x^2 + 1)"));
+        assert_eq!(
+            "ln(abs(((1x^(2)+3x^(1))+2)))",
+            integral("(2x + 3)/(x^2 + 3x + 2)")
+        );
+        for expression in [
+            "1/(x + 1)",
+            "1/(x^2 - 4)",
+            "x^3/(x - 1)",
+            "(x + 2)/(x^2 + 2x + 5)",
+            "1/(x + 1)^2",
+            "3/(2x^2 + x + 1)",
+        ] {
+            assert_antiderivative(expression, 0.6);
+        }
+    }
+
+    #[test]
+    fn can_integrate_by_substitution_and_parts() {
+        assert_eq!("sin(1x^(2))", integral("2x cos(x^2)"));
+        assert_eq!("((0.5x^(2)*ln(1x^(1)))-0.25x^(2))", integral("x ln(x)"));
+        for expression in [
+            "x sin(x)",
+            "x^2 exp(x)",
+            "x atan(x)",
+            "sin(x) cos(x)",
+            "x sqrt(x^2 + 1)",
+            "x exp(x^2)",
+            "sin(x)/cos(x)",
+            "x^2 cos(2x)",
+            "ln(x)/x",
+        ] {
+            assert_antiderivative(expression, 0.6);
+        }
+    }
+
+    #[test]
+    fn fails_on_unknown_integrals() {
+        for expression in ["exp(x^2)", "f(x)", "exp(x) sin(x)", "x % 2"] {
+            assert!(matches!(
+                integrate(&parse(expression).unwrap(), "x"),
+                Err(CalculusError::CannotIntegrate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn can_integrate_definite() {
+        let definite =
+            |expression, a, b| integrate_definite(&parse(expression).unwrap(), "x", a, b).unwrap();
+        assert_eq!(9.0, definite("x^2", 0.0, 3.0));
+        assert_eq!(2.0, definite("sin(x)", 0.0, std::f64::consts::PI));
+        // No antiderivative, so the integral is approximated
+        assert!((definite("exp(x^2)", 0.0, 1.0) - 1.4626517459071816).abs() < 1e-10);
+        assert!(integrate_definite(&parse("y x").unwrap(), "x", 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn only_substitutes_on_both_sides_of_zero() {
+        assert!(integrate(&parse("cos(abs(x))").unwrap(), "x").is_err());
+        let area = integrate_definite(&parse("cos(abs(x))").unwrap(), "x", -1.0, 1.0).unwrap();
+        assert!((area - 2.0 * 1f64.sin()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn reports_poles_between_the_limits() {
+        for (expression, a, b) in [
+            ("1/x^2", -1.0, 1.0),
+            ("1/x", -1.0, 1.0),
+            ("1/(x - 0.3)", 0.0, 1.0),
+            ("tan(x)", 0.0, 2.0),
+        ] {
+            assert!(
+                matches!(
+                    integrate_definite(&parse(expression).unwrap(), "x", a, b),
+                    Err(Error::Calculus(CalculusError::Unbounded(_)))
+                ),
+                "{expression}"
+            );
+        }
+        let area = integrate_definite(&parse("1/x").unwrap(), "x", 1.0, 2.0).unwrap();
+        assert!((area - 2f64.ln()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{
+        evaluate_complex, evaluate_complex_with, format_complex, AngleMode, Complex64,
+        ComplexFormat, Context,
+    };
+    use crate::parser::{parse, parse_with, ParserOptions};
+
+    fn setup(expression: &str) -> String {
+        format_complex(
+            evaluate_complex(expression).unwrap(),
+            ComplexFormat::Rectangular,
+        )
+    }
+
+    fn setup_polar(expression: &str) -> String {
+        format_complex(evaluate_complex(expression).unwrap(), ComplexFormat::Polar)
+    }
+
+    #[test]
+    fn can_parse_the_imaginary_unit() {
+        let options = ParserOptions {
+            complex: true,
+            ..Default::default()
+        };
+        let parse_complex = |expression| parse_with(expression, &options).unwrap().to_string();
+        assert_eq!("(3+(4*i))", parse_complex("3+4i"));
+        assert_eq!("(i^2)", parse_complex("i^2"));
+        assert_eq!("2x^(1)", parse_complex("2x"));
+        assert_eq!("4i^(1)", parse("4i").unwrap().to_string());
+    }
+
+    #[test]
+    fn can_eval_complex_arithmetic() {
+        assert_eq!("3 + 4i", setup("3+4i"));
+        assert_eq!("11 - 2i", setup("(3+4i)*(1-2i)"));
+        assert_eq!("-1", setup("i^2"));
+        assert_eq!("-i", setup("i^3"));
+        assert_eq!("-i", setup("1/i"));
+        assert_eq!("0.7 + 0.1i", setup("(3+4i)/(5+5i)"));
+        assert_eq!("2", setup("(1+i)(1-i)"));
+    }
+
+    #[test]
+    fn can_eval_complex_parts() {
+        assert_eq!("3", setup("re(3+4i)"));
+        assert_eq!("4", setup("im(3+4i)"));
+        assert_eq!("5", setup("abs(3+4i)"));
+        assert_eq!("3 - 4i", setup("conj(3+4i)"));
+        assert_eq!("0.785398163397448", setup("arg(1+i)"));
+        assert_eq!("3.141592653589793", setup("arg(-1)"));
+    }
+
+    #[test]
+    fn can_eval_principal_branches() {
+        assert_eq!("i", setup("sqrt(-1)"));
+        assert_eq!("2i", setup("sqrt(-4)"));
+        assert_eq!("3.141592653589793i", setup("ln(-1)"));
+        assert_eq!("-1", setup("exp(i pi)"));
+        assert_eq!("0.207879576350762", setup("i^i"));
+        assert_eq!("-2", setup("cbrt(-8)"));
+        assert_eq!("1 + i", setup("sqrt(2i)"));
+        assert_eq!("1.570796326794897", setup("re(asin(2))"));
+        assert_eq!("1.570796326794897i", setup("acosh(0)"));
+        assert_eq!("1.543080634815244", setup("cos(i)"));
+        assert_eq!("4", setup("max(1, 4)"));
+    }
+
+    #[test]
+    fn can_format_polar() {
+        assert_eq!("5e^(0.9272952180016122i)", setup_polar("3+4i"));
+        assert_eq!("1e^(1.5707963267948966i)", setup_polar("i"));
+        assert_eq!("2", setup_polar("2"));
+        assert_eq!(
+            "1.4142135623730951e^(-0.7853981633974483i)",
+            format_complex(Complex64::new(1.0, -1.0), ComplexFormat::Polar)
+        );
+    }
+
+    #[test]
+    fn can_eval_in_degrees() {
+        let ctx = Context::new().with_angle_mode(AngleMode::Degrees);
+        let options = ParserOptions {
+            complex: true,
+            ..Default::default()
+        };
+        let eval = |expression| {
+            let expr = parse_with(expression, &options).unwrap();
+            format_complex(
+                evaluate_complex_with(&expr, &ctx).unwrap(),
+                ComplexFormat::Rectangular,
+            )
+        };
+        assert_eq!("45", eval("arg(1+i)"));
+        assert_eq!("1", eval("sin(90)"));
+    }
+
+    #[test]
+    fn reports_complex_errors() {
+        let error = |expression: &str| match evaluate_complex(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        };
+
+        assert!(matches!(error("1/0"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("floor(i)"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("i % 2"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("ln(0)"), EvaluatorError::Domain { .. }));
+        assert_eq!(
+            EvaluatorError::UnknownVariable("x".to_string()),
+            error("x + i")
+        );
+    }
+}
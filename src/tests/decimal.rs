@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{
+        evaluate_decimal, evaluate_decimal_with, AngleMode, Context, Precision, RoundingMode,
+    };
+    use crate::parser::parse;
+
+    fn setup(expression: &str, digits: u32) -> String {
+        let precision = Precision::new(digits, RoundingMode::HalfEven);
+        evaluate_decimal(expression, precision).unwrap().to_string()
+    }
+
+    fn setup_rounded(expression: &str, digits: u32, rounding: RoundingMode) -> String {
+        evaluate_decimal(expression, Precision::new(digits, rounding))
+            .unwrap()
+            .to_string()
+    }
+
+    fn setup_in(expression: &str, angle_mode: AngleMode) -> String {
+        let ctx = Context::new().with_angle_mode(angle_mode);
+        evaluate_decimal_with(&parse(expression).unwrap(), &ctx, Precision::default())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn can_eval_exact_decimal_arithmetic() {
+        assert_eq!("0.3", setup("0.1+0.2", 34));
+        assert_eq!("1.21", setup("1.1^2", 34));
+        assert_eq!("0.0625", setup("-4^-2", 34));
+        assert_eq!("1", setup("-3%-2", 34));
+        assert_eq!(
+            "123456789012345678901234567891",
+            setup("123456789012345678901234567890+1", 34)
+        );
+        assert_eq!("1e40", setup("10^40", 34));
+        assert_eq!("1e99999", setup("10^99999 + 1", 34));
+        assert_eq!("1.5e-12", setup("1.5/10^12", 34));
+        assert_eq!("0.1", setup("min(0.3, 0.1, 0.2)", 34));
+        assert_eq!("0.3", setup("max(0.3, 0.1, 0.2)", 34));
+        assert_eq!("-2", setup("min(-2)", 34));
+    }
+
+    #[test]
+    fn can_eval_with_more_digits_than_f64() {
+        assert_eq!("0.3333333333333333333333333333333333", setup("1/3", 34));
+        assert_eq!("0.66666666666666666667", setup("2/3", 20));
+        assert_eq!("1.414213562373095048801688724209698", setup("sqrt(2)", 34));
+        assert_eq!("3.141592653589793238462643383279503", setup("pi", 34));
+        assert_eq!("2.718281828459045235360287471352662", setup("e", 34));
+        assert_eq!("1.618033988749894848204586834365638", setup("phi", 34));
+    }
+
+    #[test]
+    fn can_round_in_every_mode() {
+        assert_eq!("0.12", setup_rounded("0.125", 2, RoundingMode::HalfEven));
+        assert_eq!("0.13", setup_rounded("0.125", 2, RoundingMode::HalfUp));
+        assert_eq!("0.12", setup_rounded("0.125", 2, RoundingMode::HalfDown));
+        assert_eq!("0.67", setup_rounded("2/3", 2, RoundingMode::HalfDown));
+        assert_eq!("0.34", setup_rounded("1/3", 2, RoundingMode::Up));
+        assert_eq!("0.66", setup_rounded("2/3", 2, RoundingMode::Down));
+        assert_eq!("-0.33", setup_rounded("-1/3", 2, RoundingMode::Ceiling));
+        assert_eq!("-0.34", setup_rounded("-1/3", 2, RoundingMode::Floor));
+        assert_eq!("1000", setup_rounded("999.5", 3, RoundingMode::HalfUp));
+    }
+
+    #[test]
+    fn can_eval_exponentials_and_logarithms() {
+        assert_eq!("7.389056098930650227230427460575008", setup("exp(2)", 34));
+        assert_eq!("0.6931471805599453094172321214581766", setup("ln(2)", 34));
+        assert_eq!("3", setup("log10(1000)", 34));
+        assert_eq!("10", setup("log2(1024)", 34));
+        assert_eq!("2", setup("log(3, 9)", 34));
+        assert_eq!("0", setup("ln(1)", 34));
+        assert_eq!(
+            "9.999999999999999999999999999995e-31",
+            setup("ln(1.000000000000000000000000000001)", 34)
+        );
+        assert_eq!("1.4142135623730950488", setup("2^0.5", 20));
+        assert_eq!("3", setup("27^(1/3)", 20));
+        assert_eq!("-3", setup("cbrt(-27)", 20));
+        assert_eq!("2", setup("nthroot(16, 4)", 20));
+    }
+
+    #[test]
+    fn can_eval_trigonometric_functions() {
+        assert_eq!("0.841470984807896506652502321630299", setup("sin(1)", 34));
+        assert_eq!("0.5403023058681397174009366074429766", setup("cos(1)", 34));
+        assert_eq!("0", setup("sin(pi)", 34));
+        assert_eq!("-1", setup("cos(pi)", 34));
+        assert_eq!("0.7853981633974483096156608458198757", setup("atan(1)", 34));
+        assert_eq!("0.5", setup_in("sin(30)", AngleMode::Degrees));
+        assert_eq!("0", setup_in("cos(90)", AngleMode::Degrees));
+        assert_eq!("1", setup_in("tan(45)", AngleMode::Degrees));
+        assert_eq!("2", setup_in("sec(60)", AngleMode::Degrees));
+        assert!(evaluate_decimal_with(
+            &parse("sec(90)").unwrap(),
+            &Context::new().with_angle_mode(AngleMode::Degrees),
+            Precision::default()
+        )
+        .is_err());
+        assert_eq!("90", setup_in("asin(1)", AngleMode::Degrees));
+        assert_eq!("180", setup_in("acos(-1)", AngleMode::Degrees));
+        assert_eq!("100", setup_in("asin(1)", AngleMode::Gradians));
+        assert_eq!("-135", setup_in("atan2(-1, -1)", AngleMode::Degrees));
+    }
+
+    #[test]
+    fn reduces_large_angles_without_losing_digits() {
+        assert_eq!(
+            "-0.5696334009536363273080341815735687",
+            setup("sin(10^40)", 34)
+        );
+        assert_eq!(
+            "-0.8218988919070239214448025364432558",
+            setup("cos(10^40)", 34)
+        );
+        assert_eq!(
+            "-1.755515035329524373350155564602701",
+            setup("csc(10^40)", 34)
+        );
+        assert_eq!(
+            "-0.984807753012208059366743024589523",
+            setup_in("sin(10^40)", AngleMode::Degrees)
+        );
+        assert_eq!("0.5", setup("sin(pi/6)", 34));
+        assert_eq!("-1", setup_in("sin(270)", AngleMode::Degrees));
+        assert_eq!("0", setup_in("cos(300)", AngleMode::Gradians));
+    }
+
+    #[test]
+    fn can_eval_variables_and_user_functions() {
+        let mut ctx = Context::new().with_variable("X", 0.1);
+        ctx.define("f(X) = X^2 + 2X").unwrap();
+        let precision = Precision::default();
+
+        assert_eq!(
+            "0.21",
+            evaluate_decimal_with(&parse("f(X)").unwrap(), &ctx, precision)
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn reports_decimal_errors() {
+        let precision = Precision::default();
+        let error = |expression: &str| match evaluate_decimal(expression, precision) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        };
+
+        assert!(matches!(error("1/0"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("sqrt(-1)"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("(-8)^0.5"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("tan(pi/2)"), EvaluatorError::Domain { .. }));
+        for (function, expression) in [
+            ("^", "10^(10^9) + 1"),
+            ("^", "10^-(10^9) + 1"),
+            ("^", "(10^60000)^2"),
+            ("exp", "exp(10^6)"),
+            ("*", "10^60000 * 10^60000"),
+        ] {
+            assert_eq!(
+                EvaluatorError::Domain {
+                    function: function.to_string(),
+                    reason: "result is out of range".to_string()
+                },
+                error(expression),
+                "{expression}"
+            );
+        }
+        assert!(matches!(error("csc(-2pi)"), EvaluatorError::Domain { .. }));
+        assert_eq!(
+            EvaluatorError::NotDecimal("sinh".to_string()),
+            error("sinh(1)")
+        );
+        assert_eq!(
+            EvaluatorError::UnknownFunction("nope".to_string()),
+            error("nope(1)")
+        );
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{render_snippet, Diagnostic, Error, EvaluatorError, ParserError};
+    use crate::numeric_evaluator::evaluate;
+    use crate::parser::{parse_equation_with, parse_with, Expr, ParserOptions, Span};
+
+    fn spanned(expression: &str) -> Expr {
+        let options = ParserOptions {
+            spans: true,
+            ..Default::default()
+        };
+        parse_with(expression, &options).unwrap()
+    }
+
+    fn diagnose(expression: &str) -> Diagnostic {
+        Diagnostic::new(&evaluate(expression).unwrap_err(), expression)
+    }
+
+    #[test]
+    fn spans_do_not_change_output() {
+        assert_eq!("(2+(3*sin(1x^(1))))", spanned("2 + 3 sin(x)").to_string());
+        assert_eq!("3x^(2)", spanned("3x^2").to_string());
+        assert_eq!("(3x^(2)+1)", spanned("3x^2+1").strip_spans().to_string());
+    }
+
+    #[test]
+    fn nodes_carry_spans() {
+        let expr = spanned("12 + foo(3)");
+        assert_eq!(Some(Span::new(0, 11)), expr.span());
+
+        let Expr::BinOp { lhs, rhs, .. } = expr.unspanned() else {
+            panic!("expected a binary operation");
+        };
+        assert_eq!(Some(Span::new(0, 2)), lhs.span());
+        assert_eq!(Some(Span::new(5, 11)), rhs.span());
+    }
+
+    #[test]
+    fn equation_spans_match_input() {
+        let options = ParserOptions {
+            spans: true,
+            ..Default::default()
+        };
+        let expr = parse_equation_with("x + 1 = 2y", &options).unwrap();
+
+        let Expr::BinOp { lhs, rhs, .. } = expr.unspanned() else {
+            panic!("expected an equation");
+        };
+        assert_eq!(Some(Span::new(0, 5)), lhs.span());
+        assert_eq!(Some(Span::new(8, 10)), rhs.span());
+    }
+
+    #[test]
+    fn syntax_errors_have_spans() {
+        let error = parse_with("2 + * 3", &ParserOptions::default()).unwrap_err();
+        assert!(matches!(error, ParserError::Syntax { .. }));
+        assert_eq!(Some(Span::new(4, 4)), error.span());
+    }
+
+    #[test]
+    fn evaluation_errors_have_spans() {
+        let error = evaluate("1 + sqrt(-4)").unwrap_err();
+        assert_eq!(Some(Span::new(4, 12)), error.span());
+        let Error::Evaluator(error) = error else {
+            panic!("expected an evaluator error");
+        };
+        assert!(matches!(
+            error.without_span(),
+            EvaluatorError::Domain { .. }
+        ));
+    }
+
+    #[test]
+    fn renders_caret_snippets() {
+        assert_eq!(
+            "2 + foo\n    ^^^",
+            render_snippet("2 + foo", Span::new(4, 7))
+        );
+        assert_eq!("2 +\n   ^", render_snippet("2 +", Span::new(3, 3)));
+        assert_eq!("θ + y\n    ^", render_snippet("θ + y", Span::new(5, 6)));
+    }
+
+    #[test]
+    fn builds_diagnostics() {
+        let diagnostic = diagnose("2 * y + 1");
+        assert_eq!(
+            "Can't find a value for the variable 'y'",
+            diagnostic.message()
+        );
+        assert_eq!(Some(4), diagnostic.start());
+        assert_eq!(Some(5), diagnostic.end());
+        assert_eq!(Some("2 * y + 1\n    ^".to_string()), diagnostic.snippet());
+
+        let diagnostic = diagnose("abs(1, 2, 3)");
+        assert_eq!(Some(0), diagnostic.start());
+        assert_eq!(Some(12), diagnostic.end());
+
+        let diagnostic = diagnose("(1 + 2");
+        assert!(diagnostic.start().is_some());
+        assert!(diagnostic.message().starts_with("Syntax error"));
+    }
+}
@@ -1,6 +1,16 @@
 #[cfg(test)]
 mod test {
-    use crate::numeric_evaluator::evaluate;
+    use std::f64::consts::FRAC_PI_4;
+
+    use crate::error::{Error, EvaluatorError, ParserError};
+    use crate::math::round;
+    use crate::numeric_evaluator::{evaluate, evaluate_with, AngleMode, Context};
+    use crate::parser::parse;
+
+    fn evaluate_in(expression: &str, angle_mode: AngleMode) -> f64 {
+        let ctx = Context::new().with_angle_mode(angle_mode);
+        evaluate_with(&parse(expression).unwrap(), &ctx).unwrap()
+    }
 
     #[test]
     fn can_eval_plus() {
@@ -75,10 +85,10 @@ mod test {
 
     #[test]
     fn can_eval_functions() {
-        assert_eq!(0.5, evaluate("cos(60)").unwrap());
-        assert_eq!(0.5, evaluate("sin(30)").unwrap());
-        assert_eq!(1.0, evaluate("tan(45)").unwrap());
-        assert_eq!(1.0, evaluate("tan(45)").unwrap());
+        assert_eq!(0.5, evaluate_in("cos(60)", AngleMode::Degrees));
+        assert_eq!(0.5, evaluate_in("sin(30)", AngleMode::Degrees));
+        assert_eq!(1.0, evaluate_in("tan(45)", AngleMode::Degrees));
+        assert_eq!(1.0, evaluate_in("tan(45)", AngleMode::Degrees));
         assert_eq!(4.0, evaluate("floor(4.5)").unwrap());
         assert_eq!(5.0, evaluate("ceil(4.5)").unwrap());
         assert_eq!(5.0, evaluate("round(4.6)").unwrap());
@@ -96,4 +106,123 @@ mod test {
             evaluate("7 + max(2, min(47.94, trunc(22.54)))").unwrap()
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn can_eval_variables() {
+        let ctx = Context::new()
+            .with_variable("X", 3.0)
+            .with_variable("Y", 2.0);
+
+        assert_eq!(3.0, evaluate_with(&parse("X").unwrap(), &ctx).unwrap());
+        assert_eq!(27.0, evaluate_with(&parse("3X^2").unwrap(), &ctx).unwrap());
+        assert_eq!(11.0, evaluate_with(&parse("X^2+Y").unwrap(), &ctx).unwrap());
+        assert_eq!(
+            7.0,
+            evaluate_with(&parse("max(X, Y) + 2Y").unwrap(), &ctx).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_reuse_parsed_expression() {
+        let expr = parse("2X+1").unwrap();
+        let mut ctx = Context::new();
+
+        ctx.set_variable("X", 1.0);
+        assert_eq!(3.0, evaluate_with(&expr, &ctx).unwrap());
+
+        ctx.set_variable("X", 4.0);
+        assert_eq!(9.0, evaluate_with(&expr, &ctx).unwrap());
+    }
+
+    #[test]
+    fn fails_on_unknown_variable() {
+        assert!(evaluate("2X+1").is_err());
+        assert!(evaluate_with(
+            &parse("X+Y").unwrap(),
+            &Context::new().with_variable("X", 1.0)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn defaults_to_radians() {
+        assert_eq!(1.0, evaluate("cos(0)").unwrap());
+        assert_eq!(0.841470984807897, evaluate("sin(1)").unwrap());
+        assert_eq!(1.0, evaluate_in("cos(0)", AngleMode::Radians));
+    }
+
+    #[test]
+    fn can_eval_trig_in_gradians() {
+        assert_eq!(1.0, evaluate_in("sin(100)", AngleMode::Gradians));
+        assert_eq!(-1.0, evaluate_in("cos(200)", AngleMode::Gradians));
+        assert_eq!(1.0, evaluate_in("tan(50)", AngleMode::Gradians));
+    }
+
+    #[test]
+    fn converts_inverse_trig_results() {
+        assert_eq!(90.0, evaluate_in("asin(1)", AngleMode::Degrees));
+        assert_eq!(100.0, evaluate_in("asin(1)", AngleMode::Gradians));
+        assert_eq!(180.0, evaluate_in("acos(-1)", AngleMode::Degrees));
+        assert_eq!(45.0, evaluate_in("atan(1)", AngleMode::Degrees));
+        assert_eq!(
+            round(FRAC_PI_4, 15),
+            evaluate_in("atan(1)", AngleMode::Radians)
+        );
+    }
+
+    #[test]
+    fn can_eval_constants() {
+        assert_eq!(round(std::f64::consts::PI, 15), evaluate("pi").unwrap());
+        assert_eq!(round(std::f64::consts::PI, 15), evaluate("π").unwrap());
+        assert_eq!(round(std::f64::consts::E, 15), evaluate("e").unwrap());
+        assert_eq!(round(std::f64::consts::TAU, 15), evaluate("tau").unwrap());
+        assert_eq!(1.0, evaluate("phi^2 - phi").unwrap());
+        assert_eq!(f64::INFINITY, evaluate("inf").unwrap());
+        assert_eq!(-1.0, evaluate("cos(pi)").unwrap());
+        assert_eq!(1.0, evaluate("ln(e)").unwrap());
+    }
+
+    #[test]
+    fn can_eval_named_variables() {
+        let ctx = Context::new()
+            .with_variable("velocity", 3.0)
+            .with_variable("x_1", 2.0)
+            .with_variable("θ", 0.0);
+
+        assert_eq!(
+            13.0,
+            evaluate_with(&parse("velocity^2 + 2x_1").unwrap(), &ctx).unwrap()
+        );
+        assert_eq!(1.0, evaluate_with(&parse("cos(θ)").unwrap(), &ctx).unwrap());
+    }
+
+    #[test]
+    fn can_eval_implicit_multiplication() {
+        assert_eq!(14.0, evaluate("2(3+4)").unwrap());
+        assert_eq!(21.0, evaluate("(1+2)(3+4)").unwrap());
+        assert_eq!(
+            round(2.0 * std::f64::consts::PI, 15),
+            evaluate("2pi").unwrap()
+        );
+        assert_eq!(0.0, evaluate("3 sin(0)").unwrap());
+
+        let ctx = Context::new().with_variable("x", 2.0);
+        assert_eq!(
+            0.333333333333333,
+            evaluate_with(&parse("2/3x").unwrap(), &ctx).unwrap()
+        );
+    }
+
+    #[test]
+    fn errors_keep_their_kind() {
+        assert!(matches!(evaluate("2 +"), Err(Error::Parser(_))));
+        assert!(matches!(
+            evaluate("1 = 1"),
+            Err(Error::Parser(ParserError::InvalidOperator(_)))
+        ));
+        assert!(matches!(
+            evaluate_with(&parse("y").unwrap(), &Context::new()),
+            Err(EvaluatorError::UnknownVariable(name)) if name == "y"
+        ));
+    }
+}
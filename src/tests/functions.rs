@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{evaluate, evaluate_with, Arity, Context, FunctionRegistry};
+    use crate::parser::parse;
+
+    fn eval(expression: &str, ctx: &Context) -> f64 {
+        evaluate_with(&parse(expression).unwrap(), ctx).unwrap()
+    }
+
+    fn evaluator_error(error: Error) -> EvaluatorError {
+        match error {
+            Error::Evaluator(error) => error.without_span().clone(),
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn arity_accepts_counts() {
+        assert!(Arity::Fixed(2).accepts(2));
+        assert!(!Arity::Fixed(2).accepts(1));
+        assert!(Arity::Range(1, 3).accepts(1));
+        assert!(Arity::Range(1, 3).accepts(3));
+        assert!(!Arity::Range(1, 3).accepts(4));
+        assert!(Arity::Variadic(1).accepts(12));
+        assert!(!Arity::Variadic(1).accepts(0));
+    }
+
+    #[test]
+    fn can_register_fixed_function() {
+        let mut ctx = Context::new();
+        ctx.register_function("clamp", Arity::Fixed(3), |args| {
+            Ok(args[0].max(args[1]).min(args[2]))
+        });
+
+        assert_eq!(5.0, eval("clamp(7, 0, 5)", &ctx));
+        assert_eq!(0.0, eval("clamp(-2, 0, 5)", &ctx));
+        assert_eq!(8.0, eval("clamp(3, 0, 5) + 5", &ctx));
+    }
+
+    #[test]
+    fn can_register_variadic_function() {
+        let mut ctx = Context::new();
+        ctx.register_function("npv", Arity::Variadic(2), |args| {
+            let rate = args[0];
+            Ok(args[1..]
+                .iter()
+                .enumerate()
+                .map(|(i, cash)| cash / (1.0 + rate).powi(i as i32 + 1))
+                .sum())
+        });
+
+        assert_eq!(200.0, eval("npv(0, 100, 100)", &ctx));
+        assert_eq!(100.0, eval("npv(1, 200)", &ctx));
+    }
+
+    #[test]
+    fn can_override_builtin() {
+        let mut ctx = Context::new();
+        ctx.register_function("sqrt", Arity::Fixed(1), |args| Ok(args[0] / 2.0));
+
+        assert_eq!(8.0, eval("sqrt(16)", &ctx));
+        assert_eq!(4.0, evaluate("sqrt(16)").unwrap());
+    }
+
+    #[test]
+    fn fails_on_arity_mismatch() {
+        let err = evaluate("sin(1, 2)").unwrap_err();
+        assert!(matches!(
+            evaluator_error(err),
+            EvaluatorError::ArityMismatch { found: 2, .. }
+        ));
+
+        assert!(evaluate("pow(2)").is_err());
+    }
+
+    #[test]
+    fn fails_on_unknown_function() {
+        let err = evaluate("frobnicate(2)").unwrap_err();
+        assert!(matches!(
+            evaluator_error(err),
+            EvaluatorError::UnknownFunction(_)
+        ));
+    }
+
+    #[test]
+    fn empty_registry_has_no_builtins() {
+        let registry = FunctionRegistry::new();
+        assert!(!registry.contains("sin"));
+        assert!(FunctionRegistry::with_builtins().contains("sin"));
+    }
+
+    #[test]
+    fn can_define_user_function() {
+        let mut ctx = Context::new();
+        ctx.define("f(X, Y) = X^2 + Y").unwrap();
+
+        assert_eq!(7.0, eval("f(2, 3)", &ctx));
+        assert_eq!(19.0, eval("f(f(1, 1), 3) + 12", &ctx));
+    }
+
+    #[test]
+    fn user_function_can_call_other_functions() {
+        let mut ctx = Context::new().with_variable("C", 10.0);
+        ctx.define("sq(X) = X*X").unwrap();
+        ctx.define("g(X) = sq(X) + max(X, C)").unwrap();
+
+        assert_eq!(19.0, eval("g(3)", &ctx));
+    }
+
+    #[test]
+    fn parameters_shadow_globals_only_inside_body() {
+        let mut ctx = Context::new().with_variable("X", 100.0);
+        ctx.define("f(X) = X + 1").unwrap();
+        ctx.define("g(Y) = f(2) + X").unwrap();
+
+        assert_eq!(3.0, eval("f(2)", &ctx));
+        assert_eq!(103.0, eval("g(5)", &ctx));
+        assert_eq!(100.0, eval("X", &ctx));
+    }
+
+    #[test]
+    fn fails_on_user_function_arity_mismatch() {
+        let mut ctx = Context::new();
+        ctx.define("f(X, Y) = X + Y").unwrap();
+
+        let err = evaluate_with(&parse("f(1)").unwrap(), &ctx).unwrap_err();
+        assert!(matches!(
+            err.without_span(),
+            EvaluatorError::ArityMismatch { found: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_recursive_definitions() {
+        let mut ctx = Context::new();
+        let err = ctx.define("f(X) = f(X - 1)").unwrap_err();
+        assert!(matches!(
+            evaluator_error(err),
+            EvaluatorError::RecursiveFunction(_)
+        ));
+
+        ctx.define("g(X) = h(X) + 1").unwrap();
+        let err = ctx.define("h(X) = g(X) * 2").unwrap_err();
+        assert!(matches!(
+            evaluator_error(err),
+            EvaluatorError::RecursiveFunction(_)
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let mut ctx = Context::new();
+        assert!(ctx.define("f(2) = 3").is_err());
+        assert!(ctx.define("f(X, X) = X").is_err());
+        assert!(ctx.define("3 = X").is_err());
+        assert!(ctx.define("f(X) + 1").is_err());
+    }
+
+    #[test]
+    fn can_define_function_with_named_parameters() {
+        let mut ctx = Context::new();
+        ctx.define("area(width, height) = width*height").unwrap();
+        ctx.define("f(x, y) = x^2 + y").unwrap();
+
+        assert_eq!(12.0, eval("area(3, 4)", &ctx));
+        assert_eq!(7.0, eval("f(2, 3)", &ctx));
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{
+        evaluate_hypercomplex, evaluate_hypercomplex_with, evaluate_with, Context, Octonion,
+        Quaternion,
+    };
+    use crate::parser::{parse, parse_with, Optimize, ParserOptions};
+
+    fn setup(expression: &str) -> String {
+        evaluate_hypercomplex(expression).unwrap().to_string()
+    }
+
+    fn setup_optimized(expression: &str) -> String {
+        let options = ParserOptions {
+            hypercomplex: true,
+            ..Default::default()
+        };
+        parse_with(expression, &options)
+            .unwrap()
+            .optimize_expression()
+            .to_string()
+    }
+
+    #[test]
+    fn can_parse_basis_units() {
+        let options = ParserOptions {
+            hypercomplex: true,
+            ..Default::default()
+        };
+        let parse_units = |expression| parse_with(expression, &options).unwrap().to_string();
+        assert_eq!("((1+(2*i))-(3*j))", parse_units("1+2i-3j"));
+        assert_eq!("(i*j)", parse_units("e1*e2"));
+        assert_eq!("(e15^2)", parse_units("e15^2"));
+        assert_eq!("1e16^(1)", parse_units("e16"));
+        assert_eq!("1j^(1)", parse("j").unwrap().to_string());
+    }
+
+    #[test]
+    fn can_multiply_quaternions() {
+        assert_eq!("k", setup("i*j"));
+        assert_eq!("-k", setup("j*i"));
+        assert_eq!("i", setup("j k"));
+        assert_eq!("-1", setup("i*j*k"));
+        assert_eq!("-1", setup("k^2"));
+        assert_eq!("-28 + 4i + 6j + 8k", setup("(1+2i+3j+4k)^2"));
+        assert_eq!(
+            "-5 + 3i + 6j + 2k",
+            setup("(1+i)*(2+j-3k) - (2+j-3k)*(1+i) - 5 + 3i")
+        );
+    }
+
+    #[test]
+    fn octonions_dont_associate() {
+        assert_eq!("e7", setup("(e1*e2)*e4"));
+        assert_eq!("-e7", setup("e1*(e2*e4)"));
+    }
+
+    #[test]
+    fn sedenions_have_zero_divisors() {
+        assert_eq!("0", setup("(e3 + e10)*(e6 - e15)"));
+        assert_eq!("2e5 + 2e12", setup("(e3 + e10)*(e6 + e15)"));
+    }
+
+    #[test]
+    fn can_eval_norm_conj_and_inv() {
+        assert_eq!("2", setup("norm(1+i+j+k)"));
+        assert_eq!("5", setup("abs(3e4 + 4e9)"));
+        assert_eq!("1 - 2i + 3e7", setup("conj(1+2i-3e7)"));
+        assert_eq!("0.25 - 0.25i - 0.25j - 0.25k", setup("inv(1+i+j+k)"));
+        assert_eq!("1", setup("(1+2i+3j+4k)/(1+2i+3j+4k)"));
+        assert_eq!("-0.5j", setup("(1+j)^-2"));
+        assert_eq!("2", setup("re(2-e12)"));
+        assert_eq!("1.414213562373095", setup("sqrt(2)"));
+    }
+
+    #[test]
+    fn can_eval_with_variables() {
+        let ctx = Context::new().with_variable("x", 2.0);
+        let options = ParserOptions {
+            hypercomplex: true,
+            ..Default::default()
+        };
+        let expr = parse_with("x*j + x^2", &options).unwrap();
+        assert_eq!(
+            "4 + 2j",
+            evaluate_hypercomplex_with(&expr, &ctx).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn can_use_lower_algebras() {
+        let i = Quaternion::unit(1).unwrap();
+        let j = Quaternion::unit(2).unwrap();
+        assert_eq!(Quaternion::unit(3).unwrap(), i * j);
+        assert_eq!(-(j * i), i * j);
+        assert_eq!(None, Octonion::unit(8));
+        assert_eq!(
+            Some(Octonion::from_real(0.5)),
+            Octonion::from_real(2.0).inv()
+        );
+    }
+
+    #[test]
+    fn optimizer_keeps_unit_order() {
+        assert_eq!("k", setup_optimized("i*j"));
+        assert_eq!("-(k)", setup_optimized("j*i"));
+        assert_eq!("-(1)", setup_optimized("i*j*k"));
+        assert_eq!("-(e7)", setup_optimized("e1*(e2*e4)"));
+        assert_eq!("-(i)", setup_optimized("i^3"));
+        assert_eq!("1", setup_optimized("e9^-4"));
+        assert_eq!("(1x^(1)*j)", setup_optimized("x*j"));
+    }
+
+    #[test]
+    fn reports_hypercomplex_errors() {
+        let error = |expression: &str| match evaluate_hypercomplex(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        };
+
+        assert!(matches!(error("i/0"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("inv(0)"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("j^0.5"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("2^k"), EvaluatorError::Domain { .. }));
+        assert!(matches!(error("sin(k)"), EvaluatorError::Domain { .. }));
+
+        let options = ParserOptions {
+            hypercomplex: true,
+            ..Default::default()
+        };
+        let expr = parse_with("2k", &options).unwrap();
+        assert!(matches!(
+            evaluate_with(&expr, &Context::new()),
+            Err(EvaluatorError::Domain { .. })
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError, ParserError};
+    use crate::numeric_evaluator::{
+        evaluate, evaluate_matrix, evaluate_matrix_with, Context, Matrix, Value,
+    };
+    use crate::parser::{parse, parse_system};
+
+    fn setup(expression: &str) -> String {
+        evaluate_matrix(expression).unwrap().to_string()
+    }
+
+    fn error(expression: &str) -> EvaluatorError {
+        match evaluate_matrix(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn can_parse_matrix_literals() {
+        assert_eq!("[1, 2; 3, 4]", parse("[1, 2; 3, 4]").unwrap().to_string());
+        assert_eq!(
+            "[1, (2+3), 1x^(1)]",
+            parse("[1, 2+3, x]").unwrap().to_string()
+        );
+        assert_eq!("(2*[1; 2])", parse("2*[1;2]").unwrap().to_string());
+        assert_eq!(Err(ParserError::RaggedMatrix), parse("[1, 2; 3]"));
+        assert!(parse("[]").is_err());
+        assert_eq!(2, parse_system("x = [1; 2]; y = 3").unwrap().len());
+    }
+
+    #[test]
+    fn can_eval_matrix_arithmetic() {
+        assert_eq!("[6, 8; 10, 12]", setup("[1, 2; 3, 4] + [5, 6; 7, 8]"));
+        assert_eq!("[19, 22; 43, 50]", setup("[1, 2; 3, 4] * [5, 6; 7, 8]"));
+        assert_eq!("[23, 34; 31, 46]", setup("[5, 6; 7, 8] * [1, 2; 3, 4]"));
+        assert_eq!("[2, 4, 6]", setup("2[1, 2, 3]"));
+        assert_eq!("[0.5, 1]", setup("[1, 2]/2"));
+        assert_eq!("[-1, -2]", setup("-[1, 2]"));
+        assert_eq!("14", setup("[1, 2, 3] * [1; 2; 3]"));
+        assert_eq!(
+            "[1, 2, 3; 2, 4, 6; 3, 6, 9]",
+            setup("[1; 2; 3] * [1, 2, 3]")
+        );
+        assert_eq!("[7, 10; 15, 22]", setup("[1, 2; 3, 4]^2"));
+        assert_eq!("[1, 0; 0, 1]", setup("[1, 2; 3, 4]^0"));
+        assert_eq!("[1, 0; 0, 1]", setup("[1, 0; 0, 1]^-2147483648"));
+        assert_eq!("[1, 0; 0, 1]", setup("[1, 2; 3, 4] / [1, 2; 3, 4]"));
+        assert_eq!("5", setup("[5]"));
+    }
+
+    #[test]
+    fn can_eval_linear_algebra_functions() {
+        assert_eq!("-2", setup("det([1, 2; 3, 4])"));
+        assert_eq!("0", setup("det([1, 2; 2, 4])"));
+        assert_eq!("4", setup("det([2, -1, 0; -1, 2, -1; 0, -1, 2])"));
+        assert_eq!("[-2, 1; 1.5, -0.5]", setup("inv([1, 2; 3, 4])"));
+        assert_eq!("[1, 4; 2, 5; 3, 6]", setup("transpose([1, 2, 3; 4, 5, 6])"));
+        assert_eq!("2", setup("rank([1, 2, 3; 4, 5, 6; 7, 8, 9])"));
+        assert_eq!("0", setup("rank([0, 0; 0, 0])"));
+        assert_eq!("5", setup("trace([1, 2; 3, 4])"));
+        assert_eq!("32", setup("dot([1, 2, 3], [4; 5; 6])"));
+        assert_eq!("[-3, 6, -3]", setup("cross([1, 2, 3], [4, 5, 6])"));
+        assert_eq!("[0, 0, 1]", setup("cross([1, 0, 0], [0, 1, 0])"));
+        assert_eq!(
+            "[5, 12; 21, 32]",
+            setup("hadamard([1, 2; 3, 4], [5, 6; 7, 8])")
+        );
+        assert_eq!("5", setup("norm([3, 4])"));
+        assert_eq!("[1; 2]", setup("solve([2, 1; 1, 3], [4; 7])"));
+        assert_eq!("[1, 2]", setup("solve([2, 1; 1, 3], [4, 7])"));
+        assert_eq!("0.5", setup("inv(2)"));
+        assert_eq!("1", setup("sin(pi/2)"));
+    }
+
+    #[test]
+    fn can_eval_with_variables_and_user_functions() {
+        let mut ctx = Context::new().with_variable("x", 2.0);
+        ctx.define("f(v) = v*transpose(v)").unwrap();
+        ctx.define("sq(A) = A^2").unwrap();
+
+        let eval = |expression| {
+            evaluate_matrix_with(&parse(expression).unwrap(), &ctx)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!("[2, 4]", eval("[x, x^2]"));
+        assert_eq!("14", eval("f([1, 2, 3])"));
+        assert_eq!("[7, 10; 15, 22]", eval("sq([1, 2; 3, 4])"));
+        assert_eq!(
+            Value::Matrix(Matrix::new(2, 1, vec![1.0, 2.0])),
+            evaluate_matrix_with(&parse("[1; 2]").unwrap(), &ctx).unwrap()
+        );
+    }
+
+    #[test]
+    fn reports_dimension_mismatches() {
+        let mismatch =
+            |expression| matches!(error(expression), EvaluatorError::DimensionMismatch { .. });
+        assert!(mismatch("[1, 2] + [1, 2, 3]"));
+        assert!(mismatch("[1, 2] * [1, 2]"));
+        assert!(mismatch("det([1, 2, 3; 4, 5, 6])"));
+        assert!(mismatch("inv([1, 2])"));
+        assert!(mismatch("trace([1, 2])"));
+        assert!(mismatch("dot([1, 2], [1, 2, 3])"));
+        assert!(mismatch("cross([1, 2], [3, 4])"));
+        assert!(mismatch("hadamard([1, 2], [1; 2])"));
+        assert!(mismatch("hadamard(3, [1, 2])"));
+        assert!(mismatch("solve([1, 2; 3, 4], [1, 2, 3])"));
+        assert!(mismatch("[1, 2]^2"));
+        assert_eq!(
+            EvaluatorError::DimensionMismatch {
+                operation: "*".to_string(),
+                reason: "can't multiply 2x3 by 2x3".to_string(),
+            },
+            error("[1, 2, 3; 4, 5, 6] * [1, 2, 3; 4, 5, 6]")
+        );
+    }
+
+    #[test]
+    fn reports_matrix_domain_errors() {
+        let domain = |expression| matches!(error(expression), EvaluatorError::Domain { .. });
+        assert!(domain("inv([1, 2; 2, 4])"));
+        assert!(domain("solve([1, 2; 2, 4], [1; 2])"));
+        assert!(domain("[1, 2; 2, 4]^-1"));
+        assert!(domain("[1, 0; 0, 1]^0.5"));
+        assert!(domain("[2, 0; 0, 2]^10000"));
+        assert!(domain("[1, 0; 0, 1]^(10^10)"));
+        assert!(domain("sin([1, 2])"));
+        assert!(domain("[[1, 2], 3]"));
+        assert!(matches!(evaluate("[1, 2]"), Err(Error::Evaluator(_))));
+    }
+}
@@ -1,4 +1,17 @@
-mod parser;
+mod algebra;
+mod builtins;
+mod calculus;
+mod complex;
+mod decimal;
+mod diagnostics;
+mod evaluator;
+mod functions;
+mod hypercomplex;
+mod matrix;
 mod optimizer;
+mod parser;
 mod round;
-mod evaluator;
\ No newline at end of file
+mod series;
+mod solver;
+mod statistics;
+mod units;
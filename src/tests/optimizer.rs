@@ -17,7 +17,7 @@ mod test {
 
     #[test]
     fn can_optimize_double_unary_in_expression() {
-        assert_eq!("((3*5)+25)", setup_single("3*5+(-(-25))"));
+        assert_eq!("((3*1x^(1))+25)", setup_single("3*x+(-(-25))"));
     }
 
     #[test]
@@ -28,20 +28,20 @@ mod test {
 
     #[test]
     fn can_optimize_zero_addition_in_expression() {
-        assert_eq!("(55*645)", setup_single("55*(0+645)"));
-        assert_eq!("(24*645)", setup_single("24*645+0"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*(0+645)"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*645+0"));
     }
 
     #[test]
     fn can_optimize_zero_subtraction() {
-        assert_eq!("645", setup_single("0-645"));
+        assert_eq!("-(645)", setup_single("0-645"));
         assert_eq!("645", setup_single("645-0"));
     }
 
     #[test]
     fn can_optimize_zero_subtraction_in_expression() {
-        assert_eq!("(55*645)", setup_single("55*(0-645)"));
-        assert_eq!("(24*645)", setup_single("24*645-0"));
+        assert_eq!("(1x^(1)*-(645))", setup_single("x*(0-645)"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*645-0"));
     }
 
     #[test]
@@ -62,8 +62,8 @@ mod test {
 
     #[test]
     fn can_optimize_one_multiplication_in_expression() {
-        assert_eq!("(55*645)", setup_single("55*1*645"));
-        assert_eq!("(24*645)", setup_single("24*645*1"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*1*645"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*645*1"));
     }
 
     #[test]
@@ -73,7 +73,7 @@ mod test {
 
     #[test]
     fn can_optimize_one_division_in_expression() {
-        assert_eq!("(24*645)", setup_single("24*645/1"));
+        assert_eq!("(1x^(1)*645)", setup_single("x*645/1"));
     }
 
     #[test]
@@ -88,14 +88,14 @@ mod test {
 
     #[test]
     fn can_optimize_double_powers() {
-        assert_eq!("(3^(5+10))", setup_single("3^5*3^10"));
+        assert_eq!("(3^(1x^(1)+1y^(1)))", setup_single("3^x*3^y"));
     }
 
     #[test]
     fn can_optimize_double_powers_in_expression() {
         assert_eq!(
-            "(3^((3213*2)+(421*23)))",
-            setup_single("3^(3213*2)*3^(421*23)")
+            "(3^((1x^(1)*2)+(1y^(1)*23)))",
+            setup_single("3^(x*2)*3^(y*23)")
         );
     }
 
@@ -106,22 +106,22 @@ mod test {
 
     #[test]
     fn can_optimize_power_of_one_in_expression() {
-        assert_eq!("(3213*2)", setup_single("(3213*2)^1"));
+        assert_eq!("(1x^(1)*2)", setup_single("(x*2)^1"));
     }
 
     #[test]
     fn can_optimize_power_of_negative_one() {
-        assert_eq!("(1/(3^1))", setup_single("3^(-1)"));
+        assert_eq!("(1/((1x^(1)+3)^1))", setup_single("(x+3)^(-1)"));
     }
 
     #[test]
     fn can_optimize_power_of_negative_one_in_expression() {
-        assert_eq!("(1/((3213*2)^1))", setup_single("(3213*2)^(-1)"));
+        assert_eq!("(1/((1x^(1)*2)^1))", setup_single("(x*2)^(-1)"));
     }
 
     #[test]
     fn can_optimize_multiple_layers() {
-        assert_eq!("(1/(3213*2))", setup_multi("(3213*2)^(-1)"));
+        assert_eq!("(1/(1x^(1)*2))", setup_multi("(x*2)^(-1)"));
         assert_eq!("(1/0)", setup_multi("(53*88*(52-52))^(-(125/125))"));
     }
 
@@ -136,4 +136,51 @@ mod test {
         assert_eq!("12X^(10)", setup_single("2X^8*6X^2"));
         assert_eq!("1X^(2)", setup_single("X*X"));
     }
+
+    #[test]
+    fn keeps_constants_symbolic() {
+        assert_eq!("(2*pi)", setup_multi("2*pi"));
+        assert_eq!("e", setup_multi("e*1"));
+    }
+
+    #[test]
+    fn can_optimize_trig_of_pi_exactly() {
+        assert_eq!("0", setup_multi("sin(pi)"));
+        assert_eq!("-1", setup_multi("cos(pi)"));
+        assert_eq!("1", setup_multi("sin(pi/2)"));
+        assert_eq!("0.5", setup_multi("sin(pi/6)"));
+        assert_eq!("0.5", setup_multi("cos(pi/3)"));
+        assert_eq!("0", setup_multi("sin(2*pi)"));
+        assert_eq!("1", setup_multi("cos(tau)"));
+        assert_eq!("-1", setup_multi("tan(3*pi/4)"));
+        assert_eq!("0", setup_multi("sin(-pi)"));
+        assert_eq!("tan((pi/2))", setup_multi("tan(pi/2)"));
+    }
+
+    #[test]
+    fn can_optimize_logarithms_of_constants() {
+        assert_eq!("1", setup_multi("ln(e)"));
+        assert_eq!("e", setup_multi("exp(1)"));
+        assert_eq!("1", setup_single("exp(0)+ln(1)"));
+    }
+
+    #[test]
+    fn can_fold_numbers_exactly() {
+        assert_eq!("1", setup_multi("1/3+1/3+1/3"));
+        assert_eq!("0.3", setup_multi("0.1+0.2"));
+        assert_eq!("(2/3)", setup_multi("4/6"));
+        assert_eq!("-(0.125)", setup_multi("(-2)^(-3)"));
+        assert_eq!("1", setup_multi("7%3"));
+        assert_eq!("(2^0.5)", setup_multi("2^0.5"));
+        assert_eq!("0.5", setup_multi("sin((1/6)*pi)"));
+        assert_eq!("40", setup_single("3*5+(-(-25))"));
+        assert_eq!("35475", setup_single("55*(0+645)"));
+        assert_eq!("-(35475)", setup_single("55*(0-645)"));
+        assert_eq!("15480", setup_single("24*645*1"));
+        assert_eq!("14348907", setup_single("3^5*3^10"));
+        assert_eq!("(3^(6426+9683))", setup_single("3^(3213*2)*3^(421*23)"));
+        assert_eq!("6426", setup_single("(3213*2)^1"));
+        assert_eq!("(1/3)", setup_single("3^(-1)"));
+        assert_eq!("(1/6426)", setup_multi("(3213*2)^(-1)"));
+    }
 }
@@ -1,7 +1,10 @@
-
 #[cfg(test)]
 mod test {
-    use crate::parser::{parse, parse_equation};
+    use crate::error::ParserError;
+    use crate::parser::{
+        parse, parse_equation, parse_system, parse_system_with, parse_with, ImplicitMultiplication,
+        ParserOptions, Span,
+    };
 
     fn setup_basic(expression: &str) -> String {
         parse(expression).unwrap().to_string()
@@ -57,6 +60,15 @@ mod test {
     fn can_parse_decimal() {
         assert_eq!("3.2", setup_basic("3.2"));
         assert_eq!("-(3.2)", setup_basic("-3.2"));
+        assert_eq!("0.1", setup_basic("0.1"));
+    }
+
+    #[test]
+    fn can_parse_large_integers_exactly() {
+        assert_eq!(
+            "123456789012345678901234567890",
+            setup_basic("123456789012345678901234567890")
+        );
     }
 
     #[test]
@@ -107,4 +119,107 @@ mod test {
     fn can_parse_equations() {
         assert_eq!("((1+1)=(4-2))", setup_equation("1+1=4-2"))
     }
+
+    #[test]
+    fn can_parse_constants() {
+        assert_eq!("pi", setup_basic("pi"));
+        assert_eq!("pi", setup_basic("π"));
+        assert_eq!("(2*tau)", setup_basic("2*tau"));
+        assert_eq!("-(e)", setup_basic("-e"));
+        assert_eq!("(phi+inf)", setup_basic("phi + inf"));
+        assert_eq!("exp(1)", setup_basic("exp(1)"));
+        assert_eq!("sin((pi/2))", setup_basic("sin(pi/2)"));
+    }
+
+    #[test]
+    fn can_parse_identifiers() {
+        assert_eq!("1x^(1)", setup_basic("x"));
+        assert_eq!("2velocity^(3)", setup_basic("2velocity^3"));
+        assert_eq!("(1x_1^(1)+1x_2^(1))", setup_basic("x_1 + x_2"));
+        assert_eq!("1θ^(2)", setup_basic("θ^2"));
+        assert_eq!("1xy^(1)", setup_basic("xy"));
+        assert_eq!("(1x^(1)*1y^(1))", setup_basic("x*y"));
+    }
+
+    #[test]
+    fn constants_are_reserved_identifiers() {
+        assert_eq!("e", setup_basic("e"));
+        assert_eq!("1e_1^(1)", setup_basic("e_1"));
+        assert_eq!("1pivot^(1)", setup_basic("pivot"));
+        assert_eq!("1eθ^(1)", setup_basic("eθ"));
+    }
+
+    #[test]
+    fn calls_need_parenthesis_right_after_name() {
+        assert_eq!("f_1(1x^(1))", setup_basic("f_1(x)"));
+        assert_eq!("log10(100)", setup_basic("log10(100)"));
+        assert!(parse("sin").is_ok());
+        assert_eq!("(1f^(1)*1x^(1))", setup_basic("f (x)"));
+    }
+
+    #[test]
+    fn can_parse_implicit_multiplication() {
+        assert_eq!("(2*(3+4))", setup_basic("2(3+4)"));
+        assert_eq!("((1+2)*(3+4))", setup_basic("(1+2)(3+4)"));
+        assert_eq!("(2*pi)", setup_basic("2pi"));
+        assert_eq!("(3*sin(1x^(1)))", setup_basic("3 sin(x)"));
+        assert_eq!("(3*sin(1x^(1)))", setup_basic("3sin(x)"));
+        assert_eq!("(1x^(1)*1y^(1))", setup_basic("x y"));
+        assert_eq!("(2*(1x^(1)+1))", setup_basic("2(x+1)"));
+        assert_eq!("3x^(2)", setup_basic("3 x^2"));
+        assert_eq!("((2*pi)*1r^(1))", setup_basic("2pi r"));
+    }
+
+    #[test]
+    fn implicit_multiplication_precedence() {
+        assert_eq!("(2/3x^(1))", setup_basic("2/3x"));
+        assert_eq!("(1+(2*(1x^(1)+1)))", setup_basic("1+2(x+1)"));
+        assert_eq!("((2^3)*1x^(1))", setup_basic("2^3x"));
+        assert_eq!("(-(2)*1x^(1))", setup_basic("-2x"));
+        assert_eq!("(2-3)", setup_basic("2 -3"));
+        assert!(parse("2 3").is_err());
+
+        let options = ParserOptions {
+            implicit_multiplication: ImplicitMultiplication::SamePrecedence,
+            ..Default::default()
+        };
+        let parse_same = |expression| parse_with(expression, &options).unwrap().to_string();
+        assert_eq!("((2/3)*1x^(1))", parse_same("2/3x"));
+        assert_eq!("((6/2)*(1+2))", parse_same("6/2(1+2)"));
+        assert_eq!("3x^(2)", parse_same("3x^2"));
+    }
+
+    #[test]
+    fn can_parse_unary_minus_on_any_operand() {
+        assert_eq!("-(1x^(1))", setup_basic("-x"));
+        assert_eq!("-(sin(1x^(1)))", setup_basic("-sin(x)"));
+        assert_eq!("(2*-(pi))", setup_basic("2*-pi"));
+    }
+
+    #[test]
+    fn malformed_input_returns_errors() {
+        assert_eq!(Err(ParserError::NoEquals), parse_equation("x + 1"));
+        assert_eq!(Err(ParserError::EqualsCount), parse_equation("x = 1 = 2"));
+        for expression in ["", "(", ")", "2 +", "x^", "1..2", "sin(", "f(,)", "*"] {
+            assert!(matches!(parse(expression), Err(ParserError::Syntax { .. })));
+        }
+    }
+
+    #[test]
+    fn can_parse_systems() {
+        let system = parse_system("2X + 3Y = 5; X - Y = 1;").unwrap();
+        assert_eq!(2, system.len());
+        assert_eq!("((1X^(1)-1Y^(1))=1)", system[1].to_string());
+        assert_eq!(None, system[1].span());
+
+        let options = ParserOptions {
+            spans: true,
+            ..Default::default()
+        };
+        let system = parse_system_with("2X + 3Y = 5; X - Y = 1;", &options).unwrap();
+        assert_eq!(Some(Span::new(13, 22)), system[1].span());
+
+        assert_eq!(Err(ParserError::EmptyExpression), parse_system(" ; "));
+        assert_eq!(Err(ParserError::NoEquals), parse_system("x = 1; y"));
+    }
 }
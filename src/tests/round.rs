@@ -15,12 +15,14 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_rounding_positive_number_with_decimals() {
         assert_eq!(round(3.14159, 2), 3.14);
         assert_eq!(round(1.2345678, 4), 1.2346);
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_rounding_negative_number_with_decimals() {
         assert_eq!(round(-3.14159, 2), -3.14);
         assert_eq!(round(-1.2345678, 4), -1.2346);
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod test {
+    use crate::calculus::differentiate;
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{
+        evaluate, evaluate_complex, evaluate_decimal, evaluate_hypercomplex, evaluate_matrix,
+        evaluate_units, evaluate_with, Complex64, Context, Precision, RoundingMode, Sedenion,
+    };
+    use crate::parser::{parse, Optimize};
+
+    fn eval(expression: &str) -> f64 {
+        evaluate(expression).unwrap()
+    }
+
+    fn optimized(expression: &str) -> String {
+        parse(expression).unwrap().optimize_expression().to_string()
+    }
+
+    /// Checks the closed form of `expression` against evaluating it term by
+    /// term, for every `n` from -3 to 12, which makes some ranges empty.
+    fn assert_closed_form(expression: &str) {
+        let closed = parse(expression).unwrap().optimize_expression();
+        assert!(
+            closed.as_series().is_none(),
+            "{expression} has no closed form"
+        );
+        for n in -3..=12 {
+            let ctx = Context::new().with_variable("n", n as f64);
+            let expected = evaluate_with(&parse(expression).unwrap(), &ctx).unwrap();
+            let actual = evaluate_with(&closed, &ctx).unwrap();
+            assert!(
+                (expected - actual).abs() < 1e-9 * expected.abs().max(1.0),
+                "{expression} at n = {n}: expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    fn error(expression: &str) -> EvaluatorError {
+        match evaluate(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn can_eval_sums_and_products() {
+        assert_eq!(385.0, eval("sum(k, 1, 10, k^2)"));
+        assert_eq!(120.0, eval("prod(k, 1, 5, k)"));
+        assert_eq!(0.0, eval("sum(k, 5, 4, k)"));
+        assert_eq!(1.0, eval("prod(k, 5, 4, k)"));
+        assert_eq!(25.0, eval("sum(k, 1, 3, sum(j, 1, k, j*k))"));
+        // The variadic sum is unaffected
+        assert_eq!(6.0, eval("sum(1, 2, 3)"));
+    }
+
+    #[test]
+    fn variadic_calls_are_not_series() {
+        let ctx = Context::new()
+            .with_variable("a", 1.0)
+            .with_variable("b", 2.0)
+            .with_variable("c", 3.0)
+            .with_variable("d", 4.0);
+        let value = |expression: &str| evaluate_with(&parse(expression).unwrap(), &ctx).unwrap();
+        assert_eq!(10.0, value("sum(1, b, c, d)"));
+        assert_eq!(11.0, value("sum(2a, b, c, d)"));
+        assert_eq!(6.0, value("sum(a, b, c)"));
+        assert_eq!(24.0, value("product(a, b, c, d)"));
+        assert!(parse("sum(1, b, c, d)").unwrap().as_series().is_none());
+        assert!(parse("sum(a, b, c, d)").unwrap().as_series().is_some());
+    }
+
+    #[test]
+    fn can_eval_series_in_every_mode() {
+        let expression = "sum(k, 1, 4, k^2)";
+        assert_eq!(
+            "30",
+            evaluate_decimal(expression, Precision::new(20, RoundingMode::HalfEven))
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            Complex64::new(0.0, 10.0),
+            evaluate_complex("sum(k, 1, 4, k i)").unwrap()
+        );
+        assert_eq!(
+            "[1, 10; 0, 1]",
+            evaluate_matrix("prod(k, 1, 4, [1, k; 0, 1])")
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "6 m",
+            evaluate_units("sum(k, 1, 3, k m)").unwrap().to_string()
+        );
+        assert_eq!(
+            Sedenion::from_real(-1.0),
+            evaluate_hypercomplex("prod(n, 1, 2, i)").unwrap()
+        );
+    }
+
+    #[test]
+    fn binds_the_index_in_scope() {
+        let ctx = Context::new()
+            .with_variable("k", 100.0)
+            .with_variable("n", 4.0);
+        let value = |expression: &str| evaluate_with(&parse(expression).unwrap(), &ctx).unwrap();
+        assert_eq!(110.0, value("sum(k, 1, n, k) + k"));
+        assert_eq!(400.0, value("sum(j, 1, n, k)"));
+
+        let mut ctx = Context::new();
+        ctx.define("f(X) = sum(k, 1, X, X*k)").unwrap();
+        ctx.define("g(X) = X + 1").unwrap();
+        assert_eq!(18.0, evaluate_with(&parse("f(3)").unwrap(), &ctx).unwrap());
+        assert_eq!(
+            9.0,
+            evaluate_with(&parse("sum(k, 1, 3, g(k))").unwrap(), &ctx).unwrap()
+        );
+    }
+
+    #[test]
+    fn reports_series_errors() {
+        assert!(matches!(
+            error("sum(k, 1, 2.5, k)"),
+            EvaluatorError::Domain { .. }
+        ));
+        assert!(matches!(
+            error("prod(k, 1, 10^9, k)"),
+            EvaluatorError::Domain { .. }
+        ));
+        assert!(matches!(
+            error("sum(k, 1, 3, j)"),
+            EvaluatorError::UnknownVariable(_)
+        ));
+    }
+
+    #[test]
+    fn index_is_not_a_free_variable() {
+        let expr = parse("sum(k, 1, n, k*x)").unwrap();
+        let variables: Vec<String> = expr.variables().into_iter().collect();
+        assert_eq!(vec!["n", "x"], variables);
+        assert_eq!(expr, expr.substitute("k", &parse("2").unwrap()));
+        // Differentiated term by term, then summed in closed form
+        assert_eq!(
+            "(((max(1n^(1), 0)*(max(1n^(1), 0)+1))/2)*2x^(1))",
+            differentiate(&parse("sum(k, 1, n, k*x^2)").unwrap(), "x")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn finds_closed_forms() {
+        assert_eq!("385", optimized("sum(k, 1, 10, k^2)"));
+        assert_eq!("5050", optimized("sum(k, 1, 100, k)"));
+        assert_eq!("1023", optimized("sum(k, 0, 9, 2^k)"));
+        assert_eq!("1024", optimized("prod(k, 1, 10, 2)"));
+        for expression in [
+            "sum(k, 1, n, k)",
+            "sum(k, 1, n, k^2)",
+            "sum(k, 1, n, k^3)",
+            "sum(k, 3, n + 3, 2k + 1)",
+            "sum(k, 0, n, 3 - k/2)",
+            "sum(k, 0, n, 3^k)",
+            "sum(k, 1, n, 5*0.5^k)",
+            "sum(k, 1, n, 4)",
+            "prod(k, 1, n, 3)",
+            "prod(k, 1, n, 2^k)",
+            "sum(k, n, 1, k)",
+            "sum(k, n, 5, k^2 + 1)",
+            "prod(k, n, 3, 2)",
+        ] {
+            assert_closed_form(expression);
+        }
+        assert_eq!("factorial(max(1n^(1), 0))", optimized("prod(k, 1, n, k)"));
+        assert!(parse("sum(k, 1, n, 1/k)")
+            .unwrap()
+            .optimize_expression()
+            .as_series()
+            .is_some());
+    }
+}
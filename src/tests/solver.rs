@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod test {
+    use crate::error::SolverError;
+    use crate::numeric_evaluator::{evaluate_with, Context};
+    use crate::parser::{parse, parse_equation, parse_system, Optimize};
+    use crate::solver::{
+        numeric_solve, solve, solve_system, NumericSolveOptions, RootMethod, Solutions,
+        SystemSolution,
+    };
+
+    fn solutions(equation: &str) -> String {
+        solve(&parse_equation(equation).unwrap(), "x")
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn can_solve_linear_equations() {
+        assert_eq!("2", solutions("2x + 3 = 7"));
+        assert_eq!("4", solutions("x/2 + 1 = 3"));
+        assert_eq!("-(1)", solutions("2(x - 1) = 4x"));
+        assert_eq!("3", solutions("0.1x = 0.3"));
+        assert_eq!("0", solutions("y x = 0"));
+    }
+
+    #[test]
+    fn reports_no_or_infinite_solutions() {
+        let equation = |equation| solve(&parse_equation(equation).unwrap(), "x").unwrap();
+        assert_eq!(Solutions::NoSolution, equation("x + 1 = x"));
+        assert_eq!(Solutions::Infinite, equation("2(x + 1) = 2x + 2"));
+        assert_eq!(Solutions::Infinite, equation("y = y"));
+    }
+
+    #[test]
+    fn can_solve_quadratic_equations() {
+        assert_eq!("-(2), 2", solutions("x^2 = 4"));
+        assert_eq!("1", solutions("x^2 - 2x + 1 = 0"));
+        assert_eq!("-(4), 2", solutions("(x + 1)^2 = 9"));
+        assert_eq!("-(i), i", solutions("x^2 + 1 = 0"));
+        assert_eq!("(-(1)-(2*i)), (-(1)+(2*i))", solutions("x^2 + 2x + 5 = 0"));
+    }
+
+    #[test]
+    fn solutions_can_use_other_variables() {
+        assert_eq!("(-((1b^(1)-1c^(1)))/1a^(1))", solutions("a x + b = c"));
+
+        let Solutions::Roots(roots) =
+            solve(&parse_equation("a x^2 + b x + c = 0").unwrap(), "x").unwrap()
+        else {
+            panic!("expected roots");
+        };
+        let ctx = Context::new()
+            .with_variable("a", 2.0)
+            .with_variable("b", -2.0)
+            .with_variable("c", -12.0);
+        let roots: Vec<f64> = roots
+            .iter()
+            .map(|root| evaluate_with(root, &ctx).unwrap())
+            .collect();
+        assert_eq!(vec![-2.0, 3.0], roots);
+    }
+
+    #[test]
+    fn fails_on_unsupported_equations() {
+        for equation in ["x^3 = 1", "sin(x) = 0", "1/x = 2", "0 x = y"] {
+            assert!(matches!(
+                solve(&parse_equation(equation).unwrap(), "x"),
+                Err(SolverError::Unsupported { .. })
+            ));
+        }
+        assert_eq!(
+            Err(SolverError::NotAnEquation),
+            solve(&parse("x + 1").unwrap(), "x")
+        );
+    }
+
+    #[test]
+    fn can_optimize_equations() {
+        let optimize = |equation| {
+            parse_equation(equation)
+                .unwrap()
+                .optimize_equation()
+                .to_string()
+        };
+        assert_eq!("(1x^(1)=2)", optimize("2x + 3 = 7"));
+        assert_eq!("(1y^(1)=4)", optimize("y + 1 = 2 + 3"));
+        assert_eq!("(1x^(2)=4)", optimize("x^2 = 4"));
+        assert_eq!("(1x^(1)=1y^(1))", optimize("x = y + 0"));
+    }
+
+    fn numeric_roots(equation: &str, start: f64, end: f64) -> Vec<f64> {
+        let options = NumericSolveOptions {
+            start,
+            end,
+            ..Default::default()
+        };
+        numeric_solve(&parse_equation(equation).unwrap(), "x", &options)
+            .unwrap()
+            .iter()
+            .map(|root| root.value)
+            .collect()
+    }
+
+    fn assert_roots(expected: &[f64], actual: Vec<f64>) {
+        assert_eq!(
+            expected.len(),
+            actual.len(),
+            "expected {expected:?}, got {actual:?}"
+        );
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert!(
+                (expected - actual).abs() < 1e-7,
+                "expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn can_solve_transcendental_equations() {
+        assert_roots(
+            &[0.7390851332151607],
+            numeric_roots("cos(x) = x", -10.0, 10.0),
+        );
+        assert_roots(
+            &[0.6190612867359452, 1.512134551657842],
+            numeric_roots("exp(x) = 3x", 0.0, 3.0),
+        );
+        let pi = std::f64::consts::PI;
+        assert_roots(&[-pi, 0.0, pi], numeric_roots("sin(x) = 0", -4.0, 4.0));
+    }
+
+    #[test]
+    fn numeric_roots_report_convergence() {
+        let options = NumericSolveOptions::default();
+        let roots = numeric_solve(&parse_equation("x^2 = 2").unwrap(), "x", &options).unwrap();
+        assert_eq!(2, roots.len());
+        for root in roots {
+            assert_eq!(RootMethod::Brent, root.method);
+            assert!(root.converged);
+            assert!(root.iterations > 0);
+            assert!(root.residual.abs() < 1e-12);
+        }
+
+        // Touches zero without crossing it
+        let roots =
+            numeric_solve(&parse_equation("(x - 1.001)^2 = 0").unwrap(), "x", &options).unwrap();
+        assert_eq!(1, roots.len());
+        assert_eq!(RootMethod::Newton, roots[0].method);
+        assert!((roots[0].value - 1.001).abs() < 1e-6);
+
+        let roots = numeric_solve(&parse_equation("x^3 = x").unwrap(), "x", &options).unwrap();
+        assert!(roots.iter().all(|root| root.method == RootMethod::Exact));
+    }
+
+    #[test]
+    fn numeric_solve_skips_poles_and_domain_errors() {
+        assert_roots(&[], numeric_roots("tan(x) = 0", 1.0, 2.0));
+        assert_roots(&[1.0], numeric_roots("ln(x) = 0", -1.0, 3.0));
+        assert_roots(&[], numeric_roots("x^2 = -1", -5.0, 5.0));
+    }
+
+    #[test]
+    fn numeric_solve_fails_on_unknown_variables() {
+        let options = NumericSolveOptions::default();
+        assert!(numeric_solve(&parse_equation("x = y").unwrap(), "x", &options).is_err());
+        assert!(numeric_solve(&parse("x").unwrap(), "x", &options).is_err());
+    }
+
+    fn system(equations: &str) -> SystemSolution {
+        solve_system(&parse_system(equations).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn can_solve_linear_systems() {
+        assert_eq!(
+            "X = (8/5), Y = (3/5)",
+            system("2X + 3Y = 5; X - Y = 1").to_string()
+        );
+        assert_eq!(
+            "x = 1, y = 2, z = 3",
+            system("x + y + z = 6; 2x - y = 0; 3z = 9").to_string()
+        );
+        assert_eq!("x = (1/3)", system("0.3x = 0.1").to_string());
+    }
+
+    #[test]
+    fn reports_inconsistent_and_underdetermined_systems() {
+        assert_eq!(
+            SystemSolution::Inconsistent,
+            system("x + y = 1; 2x + 2y = 3")
+        );
+
+        let solution = system("x + y = 3; 2x + 2y = 6");
+        assert_eq!("x = (-1y^(1)+3) for any y", solution.to_string());
+        let SystemSolution::Parametric { values, parameters } = solution else {
+            panic!("expected a parametric solution");
+        };
+        assert_eq!(vec!["y".to_string()], parameters);
+        assert_eq!(2, values.len());
+    }
+
+    #[test]
+    fn rejects_nonlinear_systems() {
+        let solve = |equations| solve_system(&parse_system(equations).unwrap());
+        assert_eq!(Err(SolverError::NotLinear(2)), solve("x + y = 1; x y = 2"));
+        assert_eq!(Err(SolverError::NotLinear(1)), solve("x^2 = 1"));
+        assert_eq!(Err(SolverError::NotLinear(1)), solve("sin(x) = 0"));
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{evaluate, evaluate_decimal, Precision};
+
+    fn eval(expression: &str) -> f64 {
+        evaluate(expression).unwrap()
+    }
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn error(expression: &str) -> EvaluatorError {
+        match evaluate(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn can_eval_variadic_min_max() {
+        assert_eq!(1.0, eval("min(3, 1, 2)"));
+        assert_eq!(3.0, eval("max(3, 1, 2)"));
+        assert_eq!(-4.0, eval("min(-4)"));
+        assert_eq!(
+            "1",
+            evaluate_decimal("min(3, 1, 2)", Precision::default())
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "0.3",
+            evaluate_decimal("sum(0.1, 0.2)", Precision::default())
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn can_eval_descriptive_statistics() {
+        assert_eq!(10.0, eval("sum(1, 2, 3, 4)"));
+        assert_eq!(24.0, eval("product(1, 2, 3, 4)"));
+        assert_eq!(2.5, eval("mean(1, 2, 3, 4)"));
+        assert_eq!(3.0, eval("median(5, 1, 3)"));
+        assert_eq!(2.5, eval("median(4, 1, 3, 2)"));
+        assert_eq!(2.0, eval("mode(1, 2, 2, 3, 3)"));
+        assert_eq!(5.0, eval("mode(5)"));
+        assert_close(32.0 / 7.0, eval("variance(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_eq!(4.0, eval("pvariance(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_eq!(2.0, eval("pstdev(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_close(
+            (32.0f64 / 7.0).sqrt(),
+            eval("stdev(2, 4, 4, 4, 5, 5, 7, 9)"),
+        );
+    }
+
+    #[test]
+    fn can_eval_percentiles() {
+        assert_eq!(3.0, eval("percentile(50, 1, 2, 3, 4, 5)"));
+        assert_eq!(4.6, eval("percentile(90, 1, 2, 3, 4, 5)"));
+        assert_eq!(1.75, eval("quantile(0.25, 4, 3, 2, 1)"));
+        assert_eq!(1.0, eval("quantile(0, 1, 2)"));
+        assert_eq!(2.0, eval("quantile(1, 1, 2)"));
+    }
+
+    #[test]
+    fn can_eval_distributions() {
+        assert_close(0.3989422804014327, eval("normpdf(0)"));
+        assert_close(0.05399096651318806, eval("normpdf(3, 1, 1)"));
+        assert_eq!(0.5, eval("normcdf(0)"));
+        assert_close(0.9750021048517795, eval("normcdf(1.96)"));
+        assert_close(0.15865525393145707, eval("normcdf(90, 100, 10)"));
+        assert_close(2.866515718791939e-7, eval("normcdf(-5)"));
+        assert_eq!(0.0, eval("invnorm(0.5)"));
+        assert_close(1.959963984540054, eval("invnorm(0.975)"));
+        assert_close(-2.3263478740408408, eval("invnorm(0.01)"));
+        assert_close(115.0, eval("invnorm(normcdf(115, 100, 15), 100, 15)"));
+        assert_close(0.8427007929497149, eval("erf(1)"));
+        assert_close(0.3125, eval("binompdf(5, 0.5, 2)"));
+        assert_eq!(0.0, eval("binompdf(5, 0.5, 6)"));
+        assert_close(0.6766764161830634, eval("poissoncdf(2, 2)"));
+        assert_eq!(1.0, eval("binompdf(5, 1, 5)"));
+        assert_eq!(0.0, eval("binompdf(5, 0, 2)"));
+        assert_eq!(1.0, eval("poissoncdf(0, 3)"));
+    }
+
+    #[test]
+    fn can_eval_distributions_with_large_parameters() {
+        assert_close(0.0178390111458543, eval("binompdf(2000, 0.5, 1000)"));
+        assert_close(0.508409367168506, eval("poissoncdf(1000, 1000)"));
+        assert_eq!(0.0, eval("poissoncdf(10^11, 3)"));
+        assert_eq!(1.0, eval("poissoncdf(3, 10^11)"));
+        assert!((0.500000841044174 - eval("poissoncdf(10^11, 10^11)")).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_statistics_errors() {
+        for expression in [
+            "percentile(101, 1, 2)",
+            "quantile(-0.5, 1, 2)",
+            "normpdf(0, 0, 0)",
+            "invnorm(2)",
+            "binompdf(2.5, 0.5, 1)",
+            "binompdf(5, 1.5, 1)",
+            "poissoncdf(-1, 2)",
+        ] {
+            assert!(
+                matches!(error(expression), EvaluatorError::Domain { .. }),
+                "{expression}"
+            );
+        }
+        assert!(matches!(
+            error("variance(1)"),
+            EvaluatorError::ArityMismatch { .. }
+        ));
+        assert!(matches!(
+            error("percentile(50)"),
+            EvaluatorError::ArityMismatch { .. }
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{Error, EvaluatorError};
+    use crate::numeric_evaluator::{
+        evaluate, evaluate_units, evaluate_units_with, AngleMode, Context,
+    };
+    use crate::parser::{parse, ParserOptions};
+
+    fn setup(expression: &str) -> String {
+        evaluate_units(expression).unwrap().to_string()
+    }
+
+    fn error(expression: &str) -> EvaluatorError {
+        match evaluate_units(expression) {
+            Err(Error::Evaluator(error)) => error.without_span().clone(),
+            result => panic!("expected an evaluator error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn can_parse_conversions() {
+        assert_eq!(
+            "(5ft^(1) to 1cm^(1))",
+            parse("5 ft to cm").unwrap().to_string()
+        );
+        assert_eq!(
+            "(5ft^(1) to 1cm^(1))",
+            parse("5 ft in cm").unwrap().to_string()
+        );
+        assert_eq!("5in^(1)", parse("5 in").unwrap().to_string());
+        assert_eq!("1inch^(1)", parse("inch").unwrap().to_string());
+        assert_eq!(
+            "(1in^(1)-1cm^(1))",
+            parse("1 in - 1 cm").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn can_eval_units() {
+        assert_eq!("360 m", setup("3 m/s * 2 min"));
+        assert_eq!("6 m^2", setup("2 m * 3 m"));
+        assert_eq!("2 m/s^2", setup("4 m/s / 2 s"));
+        assert_eq!("0.5 s^-1", setup("1/(2 s)"));
+        assert_eq!("5 kg*m/s^2", setup("5 kg*m/s^2"));
+        assert_eq!("1.5 km", setup("1 km + 500 m"));
+        assert_eq!("1.5 in", setup("2 in - 1.27 cm"));
+        assert_eq!("3", setup("6 m / 2 m"));
+        assert_eq!("5 m", setup("sqrt(25 m^2)"));
+        assert_eq!("2 m", setup("abs(-2 m)"));
+    }
+
+    #[test]
+    fn can_convert_units() {
+        assert_eq!("152.4 cm", setup("5 ft in cm"));
+        assert_eq!("12.7 cm", setup("5 in to cm"));
+        assert_eq!("1000 g", setup("1 kg to g"));
+        assert_eq!("100 km/h", setup("100000 m/h to km/h"));
+        assert_eq!("3.6 km/h", setup("1 m/s to km/h"));
+        assert_eq!("1 kW*h", setup("3600000 J to kW*h"));
+        assert_eq!("180 deg", setup("pi rad to deg"));
+    }
+
+    #[test]
+    fn can_eval_functions_of_angles() {
+        assert_eq!("0.5", setup("sin(30 deg)"));
+        assert_eq!("1", setup("cos(0 rad)"));
+
+        let ctx = Context::new().with_angle_mode(AngleMode::Degrees);
+        let eval = |expression| {
+            let expr = parse(expression).unwrap();
+            evaluate_units_with(&expr, &ctx).unwrap().to_string()
+        };
+        assert_eq!("0.5", eval("sin(30 deg)"));
+        assert_eq!("1", eval("sin(pi/2 rad)"));
+        assert_eq!("0.5", eval("sin(30)"));
+    }
+
+    #[test]
+    fn can_resolve_variables_before_units() {
+        let ctx = Context::new().with_variable("m", 2.0);
+        let options = ParserOptions::default();
+        let expr = crate::parser::parse_with("3m", &options).unwrap();
+        assert_eq!("6", evaluate_units_with(&expr, &ctx).unwrap().to_string());
+    }
+
+    #[test]
+    fn can_check_dimensions() {
+        assert!(matches!(
+            error("m + s"),
+            EvaluatorError::DimensionMismatch { operation, .. } if operation == "+"
+        ));
+        assert!(matches!(
+            error("5 ft to s"),
+            EvaluatorError::DimensionMismatch { operation, .. } if operation == "to"
+        ));
+        assert!(matches!(
+            error("sin(2 m)"),
+            EvaluatorError::DimensionMismatch { .. }
+        ));
+        assert!(matches!(
+            error("2^(1 s)"),
+            EvaluatorError::DimensionMismatch { .. }
+        ));
+        assert_eq!(
+            "Dimension mismatch in '+': m and s don't have the same dimension",
+            error("1 m + 1 s").to_string()
+        );
+        assert!(matches!(error("sqrt(2 m)"), EvaluatorError::Domain { .. }));
+        assert!(matches!(
+            error("5 ft to 2 cm"),
+            EvaluatorError::Domain { .. }
+        ));
+    }
+
+    #[test]
+    fn cant_convert_without_units() {
+        assert!(evaluate("5 to 3").is_err());
+        assert_eq!(
+            Err(EvaluatorError::UnknownVariable("furlong".to_string())),
+            evaluate_units("1 furlong").map_err(|error| match error {
+                Error::Evaluator(error) => error.without_span().clone(),
+                error => panic!("unexpected error {error:?}"),
+            })
+        );
+    }
+}